@@ -5,6 +5,36 @@ use std::{env, error::Error, fs, path::Path};
 use syn::{parse_file, Ident, Item, Type};
 use vergen_gix::{Emitter, Gix};
 
+/// Qualifies a field type so that it resolves from the generated `Values`
+/// struct, which lives outside of the feature module.
+///
+/// Primitive types are kept as-is, `Vec<T>` is qualified element-wise and any
+/// other type is assumed to be declared in the feature module itself.
+fn qualify_type(ty: &Type, mod_ident: &Ident) -> TokenStream {
+    let Type::Path(type_path) = ty else {
+        return quote! { #ty };
+    };
+
+    let type_name = type_path.clone().into_token_stream().to_string();
+    if type_name == "String" || type_name == "i64" || type_name == "bool" {
+        return quote! { #ty };
+    }
+
+    if let Some(segment) = type_path.path.segments.last() {
+        if segment.ident == "Vec" {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                    let inner = qualify_type(inner, mod_ident);
+                    return quote! { Vec<#inner> };
+                }
+            }
+        }
+    }
+
+    println!("cargo-warning: {} is not a primitive type", type_name);
+    quote! { crate::features::#mod_ident::#ty }
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let gix = Gix::builder()
         .describe(true, false, None)
@@ -52,26 +82,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                             let attrs = &field.attrs;
                             let ty = &field.ty;
 
-                            let qualified_ty = match &field.ty {
-                                Type::Path(type_path) => {
-                                    let type_name =
-                                        type_path.clone().into_token_stream().to_string();
-                                    if type_name == "String"
-                                        || type_name == "i64"
-                                        || type_name == "bool"
-                                        || type_name == "Vec < String >"
-                                    {
-                                        quote! { #ty }
-                                    } else {
-                                        println!(
-                                            "cargo-warning: {} is not a primitive type",
-                                            type_name
-                                        );
-                                        quote! { crate::features::#mod_ident::#ty }
-                                    }
-                                }
-                                _ => quote! { #field.ty },
-                            };
+                            let qualified_ty = qualify_type(ty, &mod_ident);
 
                             let tokens = quote! {
                                 #(#attrs)*
@@ -5,7 +5,7 @@ pub trait ToGtmplValue {
     fn to_gtmpl_value(&self, version: &str) -> gtmpl_value::Value;
 }
 
-fn json_to_gtmpl_value(json: &serde_json::Value, version: &str) -> gtmpl_value::Value {
+fn json_to_gtmpl_value(json: &serde_json::Value) -> gtmpl_value::Value {
     match json {
         serde_json::Value::Null => gtmpl_value::Value::Nil,
         serde_json::Value::Bool(b) => (*b).into(),
@@ -23,63 +23,67 @@ fn json_to_gtmpl_value(json: &serde_json::Value, version: &str) -> gtmpl_value::
         serde_json::Value::String(s) => s.into(),
         serde_json::Value::Array(arr) => arr
             .iter()
-            .map(|v| json_to_gtmpl_value(v, version))
+            .map(json_to_gtmpl_value)
             .collect::<Vec<_>>()
             .into(),
-        serde_json::Value::Object(map) => {
-            let mut object = map
-                .iter()
-                .map(|(k, v)| (k.clone(), json_to_gtmpl_value(v, version)))
-                .collect::<std::collections::HashMap<_, _>>();
+        serde_json::Value::Object(map) => gtmpl_value::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), json_to_gtmpl_value(v)))
+                .collect(),
+        ),
+    }
+}
 
-            // XXX(mnaser): This is stinky, but we only use this in test anyways.
-            object.insert(
-                "builtin".to_string(),
+fn builtin_gtmpl_value(version: &str) -> gtmpl_value::Value {
+    gtmpl_value::Value::Object(
+        vec![
+            (
+                "cluster".to_string(),
                 gtmpl_value::Value::Object(
                     vec![
                         (
-                            "cluster".to_string(),
-                            gtmpl_value::Value::Object(
-                                vec![
-                                    (
-                                        "name".to_string(),
-                                        gtmpl_value::Value::String("kube-abcde".to_string()),
-                                    ),
-                                    (
-                                        "namespace".to_string(),
-                                        gtmpl_value::Value::String("magnum-system".to_string()),
-                                    ),
-                                ]
-                                .into_iter()
-                                .collect(),
-                            ),
+                            "name".to_string(),
+                            gtmpl_value::Value::String("kube-abcde".to_string()),
                         ),
                         (
-                            "controlPlane".to_string(),
-                            gtmpl_value::Value::Object(
-                                vec![(
-                                    "version".to_string(),
-                                    gtmpl_value::Value::String(version.to_string()),
-                                )]
-                                .into_iter()
-                                .collect(),
-                            ),
+                            "namespace".to_string(),
+                            gtmpl_value::Value::String("magnum-system".to_string()),
                         ),
                     ]
                     .into_iter()
                     .collect(),
                 ),
-            );
-
-            gtmpl_value::Value::Object(object)
-        }
-    }
+            ),
+            (
+                "controlPlane".to_string(),
+                gtmpl_value::Value::Object(
+                    vec![(
+                        "version".to_string(),
+                        gtmpl_value::Value::String(version.to_string()),
+                    )]
+                    .into_iter()
+                    .collect(),
+                ),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    )
 }
 
 impl<T: Serialize + DeserializeOwned> ToGtmplValue for T {
     fn to_gtmpl_value(&self, version: &str) -> gtmpl_value::Value {
         let json = json!(self);
-        json_to_gtmpl_value(&json, version)
+
+        match json_to_gtmpl_value(&json) {
+            // XXX(mnaser): This is stinky, but we only use this in test anyways.  It is
+            //              only added at the top level so ranging over maps skips it.
+            gtmpl_value::Value::Object(mut object) => {
+                object.insert("builtin".to_string(), builtin_gtmpl_value(version));
+                gtmpl_value::Value::Object(object)
+            }
+            value => value,
+        }
     }
 }
 
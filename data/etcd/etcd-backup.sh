@@ -0,0 +1,79 @@
+#!/usr/bin/env bash
+
+# SPDX-License-Identifier: Apache-2.0
+
+set -o pipefail
+set -o errexit
+set -o nounset
+
+# shellcheck source=/dev/null
+source /etc/kubernetes/etcd-backup/credentials
+# shellcheck source=/dev/null
+source /etc/kubernetes/etcd-backup/config
+
+EMPTY_SHA256="e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+
+function s3() {
+    curl --fail --silent --show-error \
+        --aws-sigv4 "aws:amz:${S3_REGION}:s3" \
+        --user "${S3_ACCESS_KEY}:${S3_SECRET_KEY}" \
+        "$@"
+}
+
+function etcdctl() {
+    crictl exec "${CONTAINER_ID}" etcdctl \
+        --endpoints=https://127.0.0.1:2379 \
+        --cacert=/etc/kubernetes/pki/etcd/ca.crt \
+        --cert=/etc/kubernetes/pki/etcd/healthcheck-client.crt \
+        --key=/etc/kubernetes/pki/etcd/healthcheck-client.key \
+        "$@"
+}
+
+CONTAINER_ID=$(crictl ps --quiet --name etcd --state running | head -n 1)
+if [ -z "${CONTAINER_ID}" ]; then
+    echo "etcd is not running, skipping snapshot"
+    exit 0
+fi
+
+# Only the leader takes a snapshot, so that a single snapshot is uploaded for
+# every run of the timer across all the control plane nodes.
+STATUS=$(etcdctl endpoint status --write-out=fields)
+MEMBER_ID=$(echo "${STATUS}" | awk -F' : ' '/"MemberID"/ {print $2}')
+LEADER_ID=$(echo "${STATUS}" | awk -F' : ' '/"Leader"/ {print $2}')
+if [ "${MEMBER_ID}" != "${LEADER_ID}" ]; then
+    echo "etcd member is not the leader, skipping snapshot"
+    exit 0
+fi
+
+SNAPSHOT="etcd-snapshot-$(date --utc +%Y%m%dT%H%M%SZ).db"
+trap 'rm -f "/var/lib/etcd/${SNAPSHOT}"' EXIT
+
+etcdctl snapshot save "/var/lib/etcd/${SNAPSHOT}"
+
+# Every project has its own bucket, which is created with the credential of
+# the cluster by the first snapshot uploaded so that the project owns it.
+if ! s3 --head --output /dev/null \
+    --header "x-amz-content-sha256: ${EMPTY_SHA256}" \
+    "${S3_ENDPOINT}/${S3_BUCKET}" 2>/dev/null; then
+    s3 --request PUT \
+        --header "x-amz-content-sha256: ${EMPTY_SHA256}" \
+        "${S3_ENDPOINT}/${S3_BUCKET}"
+fi
+
+s3 --upload-file "/var/lib/etcd/${SNAPSHOT}" \
+    --header "x-amz-content-sha256: $(sha256sum "/var/lib/etcd/${SNAPSHOT}" | cut -d' ' -f1)" \
+    "${S3_ENDPOINT}/${S3_BUCKET}/${ETCD_BACKUP_PREFIX}/${SNAPSHOT}"
+
+# The snapshots are named after the time they were taken, so sorting them by
+# name returns the oldest snapshots first.
+s3 --header "x-amz-content-sha256: ${EMPTY_SHA256}" \
+    "${S3_ENDPOINT}/${S3_BUCKET}?list-type=2&prefix=${ETCD_BACKUP_PREFIX}/etcd-snapshot-" |
+    grep -o '<Key>[^<]*</Key>' |
+    sed -e 's/<Key>\(.*\)<\/Key>/\1/' |
+    sort |
+    head -n "-${ETCD_BACKUP_RETENTION}" |
+    while read -r KEY; do
+        s3 --request DELETE \
+            --header "x-amz-content-sha256: ${EMPTY_SHA256}" \
+            "${S3_ENDPOINT}/${S3_BUCKET}/${KEY}"
+    done
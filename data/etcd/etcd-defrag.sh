@@ -0,0 +1,26 @@
+#!/usr/bin/env bash
+
+# SPDX-License-Identifier: Apache-2.0
+
+set -o pipefail
+set -o errexit
+set -o nounset
+
+ETCDCTL_FLAGS=(
+    --endpoints=https://127.0.0.1:2379
+    --cacert=/etc/kubernetes/pki/etcd/ca.crt
+    --cert=/etc/kubernetes/pki/etcd/healthcheck-client.crt
+    --key=/etc/kubernetes/pki/etcd/healthcheck-client.key
+)
+
+CONTAINER_ID=$(crictl ps --quiet --name etcd --state running | head -n 1)
+if [ -z "${CONTAINER_ID}" ]; then
+    echo "etcd is not running, skipping defragmentation"
+    exit 0
+fi
+
+# A member does not serve any request while it is defragmented, so the members
+# take turns through a lock in etcd to keep the quorum of the cluster.
+crictl exec "${CONTAINER_ID}" etcdctl "${ETCDCTL_FLAGS[@]}" \
+    lock etcd-defrag -- \
+    etcdctl "${ETCDCTL_FLAGS[@]}" --command-timeout=60s defrag
@@ -0,0 +1,50 @@
+#!/usr/bin/env bash
+
+# SPDX-License-Identifier: Apache-2.0
+
+set -o pipefail
+set -o errexit
+set -o nounset
+
+# shellcheck source=/dev/null
+source /etc/kubernetes/etcd-backup/credentials
+# shellcheck source=/dev/null
+source /etc/kubernetes/etcd-backup/config
+
+# The snapshot is only restored by the first control plane node, the other
+# ones join the restored cluster.
+if [ -z "${ETCD_BACKUP_RESTORE_SNAPSHOT}" ] || ! grep -q "kind: InitConfiguration" /run/kubeadm/kubeadm.yaml; then
+    exit 0
+fi
+
+EMPTY_SHA256="e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+RESTORE_DIR=/var/lib/etcd-restore
+
+rm -rf "${RESTORE_DIR}"
+mkdir -p "${RESTORE_DIR}"
+
+curl --fail --silent --show-error \
+    --aws-sigv4 "aws:amz:${S3_REGION}:s3" \
+    --user "${S3_ACCESS_KEY}:${S3_SECRET_KEY}" \
+    --header "x-amz-content-sha256: ${EMPTY_SHA256}" \
+    --output "${RESTORE_DIR}/snapshot.db" \
+    "${S3_ENDPOINT}/${S3_BUCKET}/${ETCD_BACKUP_RESTORE_SNAPSHOT}"
+
+NODE_NAME=$(hostname)
+NODE_IP=$(ip -4 route get 1.1.1.1 | awk '{for (i = 1; i < NF; i++) if ($i == "src") print $(i + 1)}')
+ETCD_IMAGE=$(kubeadm config images list --config /run/kubeadm/kubeadm.yaml 2>/dev/null | grep '/etcd:')
+
+crictl pull "${ETCD_IMAGE}"
+ctr --namespace k8s.io run --rm \
+    --mount "type=bind,src=${RESTORE_DIR},dst=${RESTORE_DIR},options=rbind:rw" \
+    "${ETCD_IMAGE}" etcd-restore \
+    etcdutl snapshot restore "${RESTORE_DIR}/snapshot.db" \
+    --data-dir "${RESTORE_DIR}/data" \
+    --name "${NODE_NAME}" \
+    --initial-cluster "${NODE_NAME}=https://${NODE_IP}:2380" \
+    --initial-advertise-peer-urls "https://${NODE_IP}:2380"
+
+mkdir -p /var/lib/etcd
+mv "${RESTORE_DIR}/data/member" /var/lib/etcd/member
+chmod 0700 /var/lib/etcd
+rm -rf "${RESTORE_DIR}"
@@ -0,0 +1,23 @@
+#!/usr/bin/env bash
+
+# SPDX-License-Identifier: Apache-2.0
+
+set -o pipefail
+set -o errexit
+set -o nounset
+
+SWAP_FILE=/swapfile
+SWAP_SIZE="${1:?usage: $0 <size in MiB>}"
+
+if swapon --show=NAME --noheadings | grep -qx "${SWAP_FILE}"; then
+    exit 0
+fi
+
+if [ ! -f "${SWAP_FILE}" ] || [ "$(stat -c %s "${SWAP_FILE}")" -ne "$((SWAP_SIZE * 1024 * 1024))" ]; then
+    rm -f "${SWAP_FILE}"
+    dd if=/dev/zero of="${SWAP_FILE}" bs=1M count="${SWAP_SIZE}" status=none
+    chmod 0600 "${SWAP_FILE}"
+    mkswap "${SWAP_FILE}" >/dev/null
+fi
+
+swapon "${SWAP_FILE}"
@@ -12,7 +12,9 @@
 # License for the specific language governing permissions and limitations
 # under the License.
 
+import hashlib
 import types
+import uuid
 
 import keystoneauth1  # type: ignore
 import openstack.exceptions as sdk_exceptions  # type: ignore
@@ -20,6 +22,7 @@ import pykube  # type: ignore
 from magnum.common import clients, exception  # type: ignore
 from manilaclient.v2 import client as manilaclient  # type: ignore
 from novaclient import exceptions as nova_exception  # type: ignore
+from oslo_serialization import jsonutils  # type: ignore
 
 
 class OpenStackClients(clients.OpenStackClients):
@@ -71,6 +74,42 @@ class OpenStackClients(clients.OpenStackClients):
         ):
             return
 
+    def create_ec2_credential(self, user_id, project_id):
+        identity = self.keystone().client
+        if self._is_callable(identity, "create_credential"):
+            blob = {"access": uuid.uuid4().hex, "secret": uuid.uuid4().hex}
+            identity.create_credential(
+                type="ec2",
+                user_id=user_id,
+                project_id=project_id,
+                blob=jsonutils.dumps(blob),
+            )
+            return types.SimpleNamespace(**blob)
+
+        credential = identity.ec2.create(user_id, project_id)
+        return types.SimpleNamespace(access=credential.access, secret=credential.secret)
+
+    def delete_ec2_credential(self, user_id, access):
+        identity = self.keystone().client
+        try:
+            # NOTE: Keystone uses the SHA-256 hash of the access key as the
+            #       identifier of EC2 credentials.
+            if self._is_callable(identity, "delete_credential"):
+                identity.delete_credential(
+                    hashlib.sha256(access.encode("utf-8")).hexdigest(),
+                    ignore_missing=True,
+                )
+                return
+
+            identity.ec2.delete(user_id, access)
+        except (
+            keystoneauth1.exceptions.http.NotFound,
+            keystoneauth1.exceptions.http.Forbidden,
+            sdk_exceptions.NotFoundException,
+            sdk_exceptions.ForbiddenException,
+        ):
+            return
+
     def is_service_enabled(self, service_type):
         identity = self.keystone().client
         if self._is_callable(identity, "services"):
@@ -17,14 +17,34 @@ from oslo_config import cfg
 
 auto_scaling_group = cfg.OptGroup(name="auto_scaling", title="Options for auto scaling")
 
+bootstrap_hooks_group = cfg.OptGroup(
+    name="bootstrap_hooks", title="Options for user commands and files on the nodes"
+)
+
 capi_client_group = cfg.OptGroup(
     name="capi_client", title="Options for the Cluster API client"
 )
 
+cinder_csi_group = cfg.OptGroup(
+    name="cinder_csi", title="Options for the Cinder CSI driver"
+)
+
+etcd_backup_group = cfg.OptGroup(
+    name="etcd_backup", title="Options for etcd snapshots in object storage"
+)
+
+extra_args_group = cfg.OptGroup(
+    name="extra_args", title="Options for control plane component extra arguments"
+)
+
 manila_client_group = cfg.OptGroup(
     name="manila_client", title="Options for the Manila client"
 )
 
+manila_csi_group = cfg.OptGroup(
+    name="manila_csi", title="Options for the Manila CSI driver"
+)
+
 proxy_group = cfg.OptGroup(name="proxy", title="Options for Cluster API proxy")
 
 
@@ -37,6 +57,27 @@ auto_scaling_opts = [
 ]
 
 
+bootstrap_hooks_opts = [
+    cfg.BoolOpt(
+        "enabled",
+        default=False,
+        help=_(
+            "Allow users to run their own commands and write their own files on "
+            "the nodes when they are bootstrapped."
+        ),
+    ),
+    cfg.IntOpt(
+        "max_size",
+        default=16384,
+        min=0,
+        help=_(
+            "Maximum size (in bytes) of the commands and files of the cluster or "
+            "of a node group, which are part of the user data of the servers."
+        ),
+    ),
+]
+
+
 capi_client_opts = [
     cfg.StrOpt(
         "endpoint_type",
@@ -49,6 +90,76 @@ capi_client_opts = [
 ]
 
 
+cinder_csi_opts = [
+    cfg.StrOpt(
+        "storage_classes",
+        help=_(
+            "JSON list of the storage classes to create for the Cinder volume "
+            "types, which is used when the `cinder_csi_storage_classes` label "
+            "is not set."
+        ),
+    ),
+]
+
+
+etcd_backup_opts = [
+    cfg.StrOpt(
+        "endpoint",
+        help=_("Endpoint of the S3 compatible object storage for etcd snapshots."),
+    ),
+    cfg.StrOpt(
+        "bucket",
+        help=_(
+            "Prefix of the buckets which the etcd snapshots are uploaded to, the "
+            "snapshots of every project are stored in a <bucket>-<project id> "
+            "bucket."
+        ),
+    ),
+    cfg.StrOpt(
+        "region",
+        default="us-east-1",
+        help=_("Region of the S3 compatible object storage."),
+    ),
+]
+
+
+extra_args_opts = [
+    cfg.ListOpt(
+        "api_server",
+        default=[],
+        help=_(
+            "List of flags which users are allowed to set on the Kubernetes API "
+            "server, use '*' to allow any flag not managed by the driver."
+        ),
+    ),
+    cfg.ListOpt(
+        "controller_manager",
+        default=[],
+        help=_(
+            "List of flags which users are allowed to set on the Kubernetes "
+            "controller manager, use '*' to allow any flag not managed by the "
+            "driver."
+        ),
+    ),
+    cfg.ListOpt(
+        "scheduler",
+        default=[],
+        help=_(
+            "List of flags which users are allowed to set on the Kubernetes "
+            "scheduler, use '*' to allow any flag not managed by the driver."
+        ),
+    ),
+    cfg.ListOpt(
+        "etcd",
+        default=[],
+        help=_(
+            "List of flags which users are allowed to set on etcd, use '*' to "
+            "allow any flag not managed by the driver."
+        ),
+    ),
+]
+
+
 manila_client_opts = [
     cfg.StrOpt(
         "region_name",
@@ -73,6 +184,18 @@ manila_client_opts = [
 ]
 
 
+manila_csi_opts = [
+    cfg.BoolOpt(
+        "cephfs_enabled",
+        default=False,
+        help=_(
+            "Allow the CEPHFS share protocol, which requires the CephFS node "
+            "plugin of ceph-csi to be installed in the clusters separately."
+        ),
+    ),
+]
+
+
 proxy_opts = [
     cfg.StrOpt(
         "haproxy_pid_path",
@@ -98,17 +221,27 @@ common_security_opts = [
 
 ALL_GROUPS = [
     auto_scaling_group,
+    bootstrap_hooks_group,
     capi_client_group,
+    cinder_csi_group,
+    etcd_backup_group,
+    extra_args_group,
     manila_client_group,
+    manila_csi_group,
     proxy_group,
 ]
 
 ALL_OPTS = [
     (auto_scaling_group, auto_scaling_opts),
+    (bootstrap_hooks_group, bootstrap_hooks_opts),
     (capi_client_group, capi_client_opts),
     (capi_client_group, common_security_opts),
+    (cinder_csi_group, cinder_csi_opts),
+    (etcd_backup_group, etcd_backup_opts),
+    (extra_args_group, extra_args_opts),
     (manila_client_group, manila_client_opts),
     (manila_client_group, common_security_opts),
+    (manila_csi_group, manila_csi_opts),
     (proxy_group, proxy_opts),
 ]
 
@@ -22,6 +22,7 @@ from magnum.common import exception as magnum_exception  # type: ignore
 from magnum.conductor import scale_manager  # type: ignore
 from magnum.drivers.common import driver  # type: ignore
 from magnum.objects import fields  # type: ignore
+from oslo_serialization import base64  # type: ignore
 
 from magnum_cluster_api import (
     clients,
@@ -112,11 +113,24 @@ class BaseDriver(driver.Driver):
             context, self.kube_client, self.k8s_api, cluster
         ).apply()
 
+        etcd_backup = utils.get_etcd_backup(cluster)
+        if etcd_backup["enabled"] or etcd_backup["restore"]:
+            ec2_credential = osc.create_ec2_credential(
+                user_id=cluster.user_id,
+                project_id=cluster.project_id,
+            )
+            resources.EtcdBackupSecret(
+                self.kube_client, cluster, ec2_credential
+            ).apply()
+        if etcd_backup["restore"]:
+            self.rust_driver.restore_etcd_snapshot(cluster, etcd_backup["restore"])
+
         resources.apply_cluster_from_magnum_cluster(
             context,
             self.kube_client,
             self.k8s_api,
             cluster,
+            self.rust_driver,
             skip_auto_scaling_release=True,
         )
         resources.Cluster(
@@ -127,6 +141,11 @@ class BaseDriver(driver.Driver):
             rust_driver=self.rust_driver,
         ).apply(),
 
+        # NOTE: The server groups are created before the Cluster API cluster,
+        #       so they are applied again to reference it as their owner.
+        if utils.is_server_group_orc_enabled(cluster):
+            resources.ClusterServerGroups(context, cluster, self.rust_driver).apply()
+
     def _get_cluster_status_reason(self, capi_cluster):
         capi_cluster_status_reason = ""
         capi_ops_cluster_status_reason = ""
@@ -220,6 +239,42 @@ class BaseDriver(driver.Driver):
                 return
 
             capi_cluster.reload()
+
+            # NOTE: The nodes of Flatcar clusters never finish booting when their
+            #       bootstrap configuration can not be translated into Ignition,
+            #       so fail early with the reason instead of timing out.
+            if utils.get_operating_system(cluster) == "flatcar":
+                try:
+                    self.rust_driver.validate_ignition(cluster)
+                except magnum_cluster_api.IgnitionError as e:
+                    cluster.status = (
+                        fields.ClusterStatus.CREATE_FAILED
+                        if cluster.status == fields.ClusterStatus.CREATE_IN_PROGRESS
+                        else fields.ClusterStatus.UPDATE_FAILED
+                    )
+                    cluster.status_reason = f"Invalid Ignition configuration: {e}"
+                    cluster.save()
+                    return
+
+            # NOTE: When Cilium replaces kube-proxy, it can only be deployed
+            #       once the control plane endpoint is known, and the nodes
+            #       will not become ready until it is deployed.
+            api_endpoint = capi_cluster.obj["spec"].get("controlPlaneEndpoint", {})
+            if (
+                cluster.status == fields.ClusterStatus.CREATE_IN_PROGRESS
+                and utils.get_cilium_kube_proxy_replacement(cluster)
+                and api_endpoint.get("host")
+                and not cluster.api_address
+            ):
+                cluster.api_address = (
+                    f"https://{api_endpoint['host']}:{api_endpoint['port']}"
+                )
+                cluster.save()
+
+                resources.CloudProviderClusterResourcesSecret(
+                    context, self.kube_client, self.k8s_api, cluster
+                ).apply()
+
             status_map = {
                 c["type"]: c["status"] for c in capi_cluster.obj["status"]["conditions"]
             }
@@ -273,6 +328,11 @@ class BaseDriver(driver.Driver):
                 cluster.save()
                 return
 
+            # NOTE: ORC deletes the server groups from OpenStack with the
+            #       application credential of the cluster, so they must be
+            #       gone before it is revoked.
+            resources.ClusterServerGroups(context, cluster, self.rust_driver).delete()
+
             # NOTE(mnaser): We delete the application credentials at this stage
             #               to make sure CAPI doesn't lose access to OpenStack.
             # NOTE(maximmonin): Keystone policy may forbid extraction of project
@@ -282,6 +342,19 @@ class BaseDriver(driver.Driver):
                 name=cluster.uuid,
             )
 
+            etcd_backup_secret = resources.EtcdBackupSecret(
+                self.kube_client, cluster
+            ).get_or_none(self.k8s_api)
+            if etcd_backup_secret:
+                ec2_access_key = etcd_backup_secret.obj.get("data", {}).get(
+                    "ec2-access-key"
+                )
+                if ec2_access_key:
+                    osc.delete_ec2_credential(
+                        user_id=cluster.user_id,
+                        access=base64.decode_as_text(ec2_access_key),
+                    )
+
             resources.CloudConfigSecret(context, self.kube_client, cluster).delete()
             resources.ApiCertificateAuthoritySecret(
                 context, self.kube_client, self.k8s_api, cluster
@@ -295,7 +368,7 @@ class BaseDriver(driver.Driver):
             resources.ServiceAccountCertificateAuthoritySecret(
                 context, self.kube_client, self.k8s_api, cluster
             ).delete()
-            resources.ClusterServerGroups(context, cluster).delete()
+            resources.EtcdBackupSecret(self.kube_client, cluster).delete()
 
             cluster.status_reason = None
             cluster.status = fields.ClusterStatus.DELETE_COMPLETE
@@ -408,7 +481,7 @@ class BaseDriver(driver.Driver):
         #               the cluster in some way.
         self.rust_driver.upgrade_cluster(cluster)
         resources.apply_cluster_from_magnum_cluster(
-            context, self.kube_client, self.k8s_api, cluster
+            context, self.kube_client, self.k8s_api, cluster, self.rust_driver
         )
         self.kube_client.update_cluster(
             "magnum-system",
@@ -472,15 +545,22 @@ class BaseDriver(driver.Driver):
         This method is called asynchonously by the Magnum API, therefore it will not be
         blocking the Magnum API.
         """
-        utils.validate_nodegroup(nodegroup)
+        utils.validate_nodegroup(cluster, nodegroup)
         utils.ensure_worker_server_group(
-            ctx=context, cluster=cluster, node_group=nodegroup
+            ctx=context,
+            cluster=cluster,
+            node_group=nodegroup,
+            rust_driver=self.rust_driver,
         )
 
         cluster_resource = objects.Cluster.for_magnum_cluster(self.k8s_api, cluster)
         cluster_resource.obj["spec"]["topology"]["workers"][
             "machineDeployments"
-        ].append(resources.mutate_machine_deployment(context, cluster, nodegroup))
+        ].append(
+            resources.mutate_machine_deployment(
+                context, cluster, nodegroup, self.rust_driver
+            )
+        )
 
         utils.kube_apply_patch(cluster_resource)
 
@@ -523,7 +603,10 @@ class BaseDriver(driver.Driver):
                 and md is None
             ):
                 utils.delete_worker_server_group(
-                    ctx=context, cluster=cluster, node_group=node_group
+                    ctx=context,
+                    cluster=cluster,
+                    node_group=node_group,
+                    rust_driver=self.rust_driver,
                 )
                 node_group.status = fields.ClusterStatus.DELETE_COMPLETE
                 node_group.save()
@@ -620,7 +703,7 @@ class BaseDriver(driver.Driver):
         cluster: magnum_objects.Cluster,
         nodegroup: magnum_objects.NodeGroup,
     ):
-        utils.validate_nodegroup(nodegroup)
+        utils.validate_nodegroup(cluster, nodegroup)
 
         cluster_resource = objects.Cluster.for_magnum_cluster(self.k8s_api, cluster)
 
@@ -636,7 +719,10 @@ class BaseDriver(driver.Driver):
             ] = nodegroup.node_count
         else:
             utils.ensure_worker_server_group(
-                ctx=context, cluster=cluster, node_group=nodegroup
+                ctx=context,
+                cluster=cluster,
+                node_group=nodegroup,
+                rust_driver=self.rust_driver,
             )
 
             # Migrate failureDomain fields to fix Cluster API v1.10+ validation issues
@@ -654,6 +740,7 @@ class BaseDriver(driver.Driver):
                 context,
                 cluster,
                 nodegroup,
+                self.rust_driver,
                 cluster_resource.get_machine_deployment_spec(nodegroup.name),
             )
 
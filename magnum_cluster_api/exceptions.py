@@ -71,3 +71,79 @@ class MachineDeploymentNotFound(exception.ObjectNotFound):
 
 class InvalidOctaviaLoadBalancerAlgorithm(exception.Invalid):
     message = _("Invalid value for octavia_lb_algorithm: %(octavia_lb_algorithm)s.")
+
+
+class InvalidServerTag(exception.Invalid):
+    message = _("Invalid server tag %(tag)s: %(reason)s.")
+
+
+class InvalidNodePortAllowedCidr(exception.Invalid):
+    message = _("Invalid CIDR %(cidr)s in nodeport_allowed_cidrs: %(reason)s.")
+
+
+class InvalidAdditionalVolumeName(exception.Invalid):
+    message = _("Additional volume name %(name)s is reserved or duplicated.")
+
+
+class InvalidMachineHealthCheck(exception.Invalid):
+    message = _("Invalid value for %(label)s: %(value)s.")
+
+
+class InvalidRolloutStrategy(exception.Invalid):
+    message = _("Invalid value for %(label)s: %(value)s.")
+
+
+class InvalidExtraArgs(exception.Invalid):
+    message = _("Invalid extra argument %(flag)s for %(label)s: %(reason)s.")
+
+
+class InvalidEtcdConfig(exception.Invalid):
+    message = _("Invalid value for %(label)s: %(value)s.")
+
+
+class InvalidEtcdBackup(exception.Invalid):
+    message = _("Invalid etcd backup configuration: %(reason)s.")
+
+
+class InvalidSwapConfig(exception.Invalid):
+    message = _("Invalid value for %(label)s: %(value)s.")
+
+
+class InvalidBootstrapHooks(exception.Invalid):
+    message = _("Invalid value for %(label)s: %(reason)s.")
+
+
+class InvalidContainerRuntime(exception.Invalid):
+    message = _("Unsupported container runtime %(runtime)s.")
+
+
+class InvalidServerGroupPolicy(exception.Invalid):
+    message = _("Unsupported server group policy %(policy)s.")
+
+
+class InvalidClusterNetwork(exception.Invalid):
+    message = _("Invalid cluster network: %(reason)s.")
+
+
+class InvalidCinderCSIStorageClasses(exception.Invalid):
+    message = _("Invalid Cinder CSI storage classes: %(reason)s.")
+
+
+class InvalidManilaCSIShareProtocol(exception.Invalid):
+    message = _("Unsupported Manila share protocol %(protocol)s.")
+
+
+class ManilaCSICephFSNotEnabled(exception.Invalid):
+    message = _("The CEPHFS share protocol is not enabled by the operator.")
+
+
+class InvalidCalicoTag(exception.Invalid):
+    message = _("Invalid Calico version: %(reason)s.")
+
+
+class InvalidCiliumConfig(exception.Invalid):
+    message = _("Invalid value for %(label)s: %(value)s.")
+
+
+class KubeProxyReplacementRequiresCilium(exception.Invalid):
+    message = _("The kube-proxy replacement is only supported with Cilium.")
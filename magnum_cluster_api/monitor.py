@@ -49,4 +49,12 @@ class Monitor(monitors.MonitorBase):
         rust_monitor = tpool.Proxy(RustMonitor(self.cluster))
         self.data = rust_monitor.poll_health_status()
 
+        # NOTE: The floating IP of the bastion host is only known once it has
+        #       been created, so it is surfaced in the health status reason of
+        #       the cluster where users can find it.
+        if utils.get_cluster_label_as_bool(self.cluster, "bastion_enabled", False):
+            bastion_floating_ip = rust_monitor.get_bastion_floating_ip()
+            if bastion_floating_ip:
+                self.data["health_status_reason"]["bastion"] = bastion_floating_ip
+
         self.poll_nodegroup_replicas()
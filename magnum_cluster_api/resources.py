@@ -16,6 +16,8 @@ import abc
 import glob
 import math
 import os
+import secrets
+import shlex
 import types
 import typing
 
@@ -110,27 +112,38 @@ class ClusterAutoscalerHelmRelease:
 
 class ClusterServerGroups:
     def __init__(
-        self, context: context.RequestContext, cluster: magnum_objects.Cluster
+        self,
+        context: context.RequestContext,
+        cluster: magnum_objects.Cluster,
+        rust_driver: magnum_cluster_api.Driver,
     ) -> None:
         self.cluster = cluster
         self.context = context
+        self.rust_driver = rust_driver
         self.osc = clients.get_openstack_api(self.context)
 
     def apply(self):
         # Create a server group for controlplane
-        utils.ensure_controlplane_server_group(ctx=self.context, cluster=self.cluster)
+        utils.ensure_controlplane_server_group(
+            ctx=self.context, cluster=self.cluster, rust_driver=self.rust_driver
+        )
 
         # Create a server group per a nodegroup
         for ng in self.cluster.nodegroups:
             if ng.role == "master":
                 continue
             utils.ensure_worker_server_group(
-                ctx=self.context, cluster=self.cluster, node_group=ng
+                ctx=self.context,
+                cluster=self.cluster,
+                node_group=ng,
+                rust_driver=self.rust_driver,
             )
 
     def delete(self):
         # delete controlplane server group
-        utils.delete_controlplane_server_group(ctx=self.context, cluster=self.cluster)
+        utils.delete_controlplane_server_group(
+            ctx=self.context, cluster=self.cluster, rust_driver=self.rust_driver
+        )
 
         # Create worker server groups
         for ng in self.cluster.nodegroups:
@@ -138,7 +151,10 @@ class ClusterServerGroups:
                 continue
 
             utils.delete_worker_server_group(
-                ctx=self.context, cluster=self.cluster, node_group=ng
+                ctx=self.context,
+                cluster=self.cluster,
+                node_group=ng,
+                rust_driver=self.rust_driver,
             )
 
 
@@ -261,15 +277,48 @@ class CloudProviderClusterResourcesSecret(ClusterBase):
             },
         }
 
+        data = {
+            **data,
+            **magnum_cluster_api.Driver.get_runtime_classes_cluster_resource_secret_data(
+                self.cluster
+            ),
+        }
+
+        # NOTE: Cilium needs the address of the API server when it replaces
+        #       kube-proxy, so it is only deployed once the control plane
+        #       endpoint is known, which is then reconciled by this secret.
+        if (
+            utils.get_cilium_kube_proxy_replacement(self.cluster)
+            and self.cluster.api_address
+        ):
+            data = {
+                **data,
+                **magnum_cluster_api.Driver.get_cilium_cluster_resource_secret_data(
+                    self.cluster
+                ),
+            }
+
         osc = clients.get_openstack_api(self.context)
         if cinder.is_enabled(self.cluster):
-            volume_types = osc.list_volume_types()
-            default_volume_type = osc.get_default_volume_type()
             data = {
                 **data,
                 **magnum_cluster_api.Driver.get_cinder_csi_cluster_resource_secret_data(
-                    self.cluster
+                    self.cluster,
+                    storage_classes=utils.get_cinder_csi_storage_classes(self.cluster),
                 ),
+            }
+
+        # NOTE: The storage classes for every volume type are only generated when
+        #       neither the `cinder_csi_storage_classes` label nor the operator
+        #       default is set, otherwise the configured ones are rendered by the
+        #       Cinder CSI addon.
+        if cinder.is_enabled(
+            self.cluster
+        ) and not utils.get_cinder_csi_storage_classes(self.cluster):
+            volume_types = osc.list_volume_types()
+            default_volume_type = osc.get_default_volume_type()
+            data = {
+                **data,
                 **{
                     f"storageclass-block-{vt.name}.yaml": yaml.dump(
                         {
@@ -300,12 +349,12 @@ class CloudProviderClusterResourcesSecret(ClusterBase):
             }
 
         if manila.is_enabled(self.cluster):
-            share_types = osc.manila().share_types.list()
             share_network_id = self.cluster.labels.get("manila_csi_share_network_id")
             data = {
                 **data,
                 **magnum_cluster_api.Driver.get_manila_csi_cluster_resource_secret_data(
-                    self.cluster
+                    self.cluster,
+                    cephfs_enabled=CONF.manila_csi.cephfs_enabled,
                 ),
                 **{
                     "manila-csi-secret.yaml": yaml.dump(
@@ -325,8 +374,15 @@ class CloudProviderClusterResourcesSecret(ClusterBase):
                     )
                 },
             }
-            # NOTE: We only create StorageClasses if share_network_id specified.
-            if share_network_id:
+            # NOTE: We only create StorageClasses if share_network_id specified,
+            #       unless a share type is set in which case the addon renders
+            #       them for every selected share protocol.
+            if (
+                share_network_id
+                and not self.cluster.labels.get("manila_csi_share_type")
+                and "NFS" in utils.get_manila_csi_share_protocols(self.cluster)
+            ):
+                share_types = osc.manila().share_types.list()
                 data = {
                     **data,
                     **{
@@ -433,20 +489,9 @@ class LegacyClusterResourcesSecret(ClusterBase):
             },
         }
 
-        if self.cluster.cluster_template.network_driver == "calico":
-            calico_version = self.cluster.labels.get("calico_tag", CALICO_TAG)
-            data = {
-                **data,
-                **{
-                    "calico.yml": image_utils.update_manifest_images(
-                        self.cluster.uuid,
-                        os.path.join(manifests_path, f"calico/{calico_version}.yaml"),
-                        repository=repository,
-                    )
-                },
-            }
-
-        if manila.is_enabled(self.cluster):
+        if manila.is_enabled(self.cluster) and (
+            "NFS" in utils.get_manila_csi_share_protocols(self.cluster)
+        ):
             data = {
                 **data,
                 **{
@@ -493,11 +538,40 @@ class LegacyClusterResourcesSecret(ClusterBase):
                 },
             }
 
+        if utils.get_cilium_encryption(self.cluster) == "ipsec":
+            data = {
+                **data,
+                **{
+                    "cilium-ipsec-keys.yaml": self.get_cilium_ipsec_keys(),
+                },
+            }
+
         return {
             "type": "addons.cluster.x-k8s.io/resource-set",
             "stringData": data,
         }
 
+    def get_cilium_ipsec_keys(self) -> str:
+        # NOTE: The key is generated only once and kept in this secret, since
+        #       it is only applied once and the nodes must share the same key.
+        secret = self.get_or_none()
+        if secret and "cilium-ipsec-keys.yaml" in secret.obj.get("data", {}):
+            return base64.decode_as_text(secret.obj["data"]["cilium-ipsec-keys.yaml"])
+
+        return yaml.dump(
+            {
+                "apiVersion": pykube.Secret.version,
+                "kind": pykube.Secret.kind,
+                "metadata": {
+                    "name": "cilium-ipsec-keys",
+                    "namespace": "kube-system",
+                },
+                "stringData": {
+                    "keys": f"3+ rfc4106(gcm(aes)) {secrets.token_hex(20)} 128",
+                },
+            }
+        )
+
     def get_or_none(self) -> objects.Cluster:
         return pykube.Secret.objects(
             self.pykube_api, namespace="magnum-system"
@@ -702,6 +776,7 @@ def mutate_machine_deployment(
     context: context.RequestContext,
     cluster: magnum_objects.Cluster,
     node_group: magnum_objects.NodeGroup,
+    rust_driver: magnum_cluster_api.Driver,
     machine_deployment: dict = None,
 ):
     """
@@ -717,6 +792,9 @@ def mutate_machine_deployment(
 
     auto_scaling_enabled = utils.get_auto_scaling_enabled(cluster)
 
+    # NOTE: Node group labels take precedence over the cluster labels.
+    node_group_labels = {**cluster.labels, **node_group.labels}
+
     machine_deployment.setdefault(
         "metadata",
         {
@@ -729,6 +807,10 @@ def mutate_machine_deployment(
     machine_deployment["metadata"]["labels"] = {
         f"node-role.kubernetes.io/{node_group.role}": "",
         "node.cluster.x-k8s.io/nodegroup": node_group.name,
+        **{
+            f"runtime.node.cluster.x-k8s.io/{runtime}": "true"
+            for runtime in utils.get_container_runtimes(node_group_labels)
+        },
     }
 
     # Lookup the node group resources
@@ -767,9 +849,17 @@ def mutate_machine_deployment(
         machine_deployment["metadata"]["annotations"] = {}
 
     # Fixes
-    machine_deployment["nodeVolumeDetachTimeout"] = (
-        CLUSTER_CLASS_NODE_VOLUME_DETACH_TIMEOUT
+    machine_deployment["nodeVolumeDetachTimeout"] = node_group_labels.get(
+        "node_volume_detach_timeout", CLUSTER_CLASS_NODE_VOLUME_DETACH_TIMEOUT
     )
+
+    # Rollout strategy
+    strategy = utils.get_machine_deployment_strategy(node_group_labels)
+    if strategy:
+        machine_deployment["strategy"] = strategy
+    else:
+        machine_deployment.pop("strategy", None)
+
     # Anything beyond this point will *NOT* be changed in the machine deployment
     # for update operations (i.e. if the machine deployment already exists).
     if machine_deployment.get("name") == node_group.name:
@@ -793,7 +883,7 @@ def mutate_machine_deployment(
             "class": "default-worker",
             "name": node_group.name,
             "failureDomain": node_group.labels.get("availability_zone"),
-            "machineHealthCheck": {"enable": utils.get_auto_healing_enabled(cluster)},
+            "machineHealthCheck": utils.get_machine_health_check(node_group_labels),
             "variables": {
                 "overrides": [
                     {
@@ -825,15 +915,49 @@ def mutate_machine_deployment(
                         "name": "imageUUID",
                         "value": image.get("id"),
                     },
+                    {
+                        "name": "imageFilter",
+                        "value": {
+                            "name": node_group.labels.get("image_filter_name", ""),
+                            "tags": utils.get_node_group_label_as_list(
+                                node_group, "image_filter_tags", ""
+                            ),
+                        },
+                    },
                     {
                         "name": "hardwareDiskBus",
                         "value": image.get("hw_disk_bus") or "",
                     },
+                    {
+                        "name": "additionalVolumes",
+                        "value": utils.get_additional_volumes(
+                            node_group.labels, "worker"
+                        ),
+                    },
+                    {
+                        "name": "nodeGroupName",
+                        "value": node_group.name,
+                    },
+                    {
+                        "name": "bootstrapHooks",
+                        "value": utils.get_bootstrap_hooks(node_group_labels),
+                    },
+                    {
+                        "name": "containerRuntimes",
+                        "value": utils.get_container_runtimes(node_group_labels),
+                    },
+                    {
+                        "name": "additionalNetworks",
+                        "value": utils.get_additional_networks(node_group_labels),
+                    },
                     # NOTE(oleks): Override using MachineDeployment-level variables for node groups
                     {
                         "name": "serverGroupId",
                         "value": utils.ensure_worker_server_group(
-                            ctx=context, cluster=cluster, node_group=node_group
+                            ctx=context,
+                            cluster=cluster,
+                            node_group=node_group,
+                            rust_driver=rust_driver,
                         ),
                     },
                     {
@@ -906,19 +1030,85 @@ def migrate_cluster_failure_domain(
 
 
 def generate_machine_deployments_for_cluster(
-    context: context.RequestContext, cluster: magnum_objects.Cluster
+    context: context.RequestContext,
+    cluster: magnum_objects.Cluster,
+    rust_driver: magnum_cluster_api.Driver,
 ) -> list:
     machine_deployments = []
     for ng in cluster.nodegroups:
         if ng.role == "master" or ng.status.startswith("DELETE"):
             continue
 
-        machine_deployment = mutate_machine_deployment(context, cluster, ng)
+        machine_deployment = mutate_machine_deployment(
+            context, cluster, ng, rust_driver
+        )
         machine_deployments.append(machine_deployment)
 
     return machine_deployments
 
 
+class EtcdBackupSecret(ClusterBase):
+    def __init__(
+        self,
+        api: magnum_cluster_api.KubeClient,
+        cluster: magnum_objects.Cluster,
+        credential: types.SimpleNamespace = types.SimpleNamespace(
+            access=None, secret=None
+        ),
+    ):
+        super().__init__(api, cluster)
+        self.credential = credential
+
+    @property
+    def api_version(self) -> str:
+        return "v1"
+
+    @property
+    def kind(self) -> str:
+        return "Secret"
+
+    @property
+    def name(self) -> str:
+        return utils.get_etcd_backup_secret_name(self.cluster)
+
+    def get_object(self) -> dict:
+        bucket = utils.get_etcd_backup_bucket(self.cluster)
+
+        # NOTE: The EC2 credential of the cluster can only reach the buckets of
+        #       its project, it is used by the nodes to upload the snapshots and
+        #       by the management cluster to list and restore them.
+        config = {
+            "S3_ENDPOINT": CONF.etcd_backup.endpoint,
+            "S3_BUCKET": bucket,
+            "S3_REGION": CONF.etcd_backup.region,
+            "S3_ACCESS_KEY": self.credential.access,
+            "S3_SECRET_KEY": self.credential.secret,
+        }
+
+        return {
+            "metadata": {
+                "labels": self.labels,
+            },
+            "stringData": {
+                "endpoint": CONF.etcd_backup.endpoint,
+                "bucket": bucket,
+                "region": CONF.etcd_backup.region,
+                "ec2-access-key": self.credential.access,
+                "ec2-secret-key": self.credential.secret,
+                "credentials": "".join(
+                    f"{key}={shlex.quote(value)}\n" for key, value in config.items()
+                ),
+            },
+        }
+
+    def get_or_none(
+        self, pykube_api: pykube.HTTPClient
+    ) -> typing.Optional[pykube.Secret]:
+        return pykube.Secret.objects(pykube_api, namespace=self.namespace).get_or_none(
+            name=self.name
+        )
+
+
 class Cluster(ClusterBase):
     def __init__(
         self,
@@ -970,11 +1160,53 @@ class Cluster(ClusterBase):
         This matches the behavior of the Heat templates where NodeRestriction
         is always prepended to any user-provided admission plugins.
         """
-        user_plugins = self.cluster.labels.get("admission_control_list", "")
-        if user_plugins:
-            return f"NodeRestriction,{user_plugins}"
-        else:
-            return "NodeRestriction"
+        plugins = ["NodeRestriction"]
+        plugins += utils.get_cluster_label_as_list(
+            self.cluster, "admission_control_list", ""
+        )
+
+        # NOTE: The limits are useless unless the plugin is enabled, so
+        #       we make sure it is when any limits are configured.
+        if self._get_event_rate_limits() and "EventRateLimit" not in plugins:
+            plugins.append("EventRateLimit")
+
+        return ",".join(plugins)
+
+    def _get_event_rate_limits(self) -> list:
+        return utils.get_cluster_label_as_json(self.cluster, "event_rate_limits", [])
+
+    def _get_pod_security(self) -> dict:
+        levels = {
+            level: self.cluster.labels.get(f"pod_security_{level}", "")
+            for level in ("enforce", "audit", "warn")
+        }
+
+        return {
+            "enabled": any(levels.values()),
+            "enforce": levels["enforce"] or "privileged",
+            "enforceVersion": self.cluster.labels.get(
+                "pod_security_enforce_version", "latest"
+            ),
+            "audit": levels["audit"] or "privileged",
+            "auditVersion": self.cluster.labels.get(
+                "pod_security_audit_version", "latest"
+            ),
+            "warn": levels["warn"] or "privileged",
+            "warnVersion": self.cluster.labels.get(
+                "pod_security_warn_version", "latest"
+            ),
+            "exemptions": {
+                "namespaces": utils.get_cluster_label_as_list(
+                    self.cluster, "pod_security_exempt_namespaces", ""
+                ),
+                "usernames": utils.get_cluster_label_as_list(
+                    self.cluster, "pod_security_exempt_usernames", ""
+                ),
+                "runtimeClasses": utils.get_cluster_label_as_list(
+                    self.cluster, "pod_security_exempt_runtime_classes", ""
+                ),
+            },
+        }
 
     def get_object(self) -> dict:
         osc = clients.get_openstack_api(self.context)
@@ -995,9 +1227,44 @@ class Cluster(ClusterBase):
         control_plane_flavor = utils.lookup_flavor(osc, self.cluster.master_flavor_id)
         worker_flavor = utils.lookup_flavor(osc, self.cluster.flavor_id)
         image = utils.lookup_image(osc, self.cluster.default_ng_master.image_id)
+        bastion_image = utils.lookup_image(
+            osc,
+            self.cluster.labels.get(
+                "bastion_image", self.cluster.default_ng_master.image_id
+            ),
+        )
 
         variables = {
-            "apiServerLoadBalancer": {"enabled": self.cluster.master_lb_enabled},
+            "apiServerLoadBalancer": {
+                "enabled": self.cluster.master_lb_enabled,
+                "allowedCIDRs": utils.get_cluster_label_as_list(
+                    self.cluster, "master_lb_allowed_cidrs", ""
+                ),
+            },
+            # NOTE: The Rust `resolve_immutable_fields` driver sets `managed`
+            #       and keeps the spec of an existing bastion, since CAPO only
+            #       allows removing or changing it once it has been disabled.
+            "bastion": {
+                "enabled": utils.get_cluster_label_as_bool(
+                    self.cluster, "bastion_enabled", False
+                ),
+                "managed": False,
+                "flavor": self.cluster.labels.get(
+                    "bastion_flavor", control_plane_flavor.name
+                ),
+                "imageUUID": bastion_image.get("id"),
+                "sshKeyName": self.cluster.labels.get(
+                    "bastion_ssh_key", self.cluster.keypair or ""
+                ),
+                "availabilityZone": self.cluster.labels.get(
+                    "bastion_availability_zone",
+                    self.cluster.labels.get("availability_zone", ""),
+                ),
+                "floatingIP": self.cluster.labels.get("bastion_floating_ip", ""),
+                "securityGroups": utils.get_cluster_label_as_list(
+                    self.cluster, "bastion_security_groups", ""
+                ),
+            },
         }
         variables = self.rust_driver.resolve_immutable_fields(
             self.cluster.stack_id, dict(self.cluster.labels), variables
@@ -1028,18 +1295,29 @@ class Cluster(ClusterBase):
                     "version": utils.get_kube_tag(self.cluster),
                     "controlPlane": {
                         "metadata": {
+                            # NOTE: This stops the control plane from managing
+                            #       kube-proxy on upgrades when it is replaced.
+                            "annotations": (
+                                {"controlplane.cluster.x-k8s.io/skip-kube-proxy": ""}
+                                if utils.get_cilium_kube_proxy_replacement(self.cluster)
+                                else {}
+                            ),
                             "labels": {
                                 "node-role.kubernetes.io/master": "",
-                            }
+                            },
                         },
                         "replicas": self.cluster.master_count,
-                        "machineHealthCheck": {
-                            "enable": utils.get_auto_healing_enabled(self.cluster)
-                        },
+                        "machineHealthCheck": utils.get_machine_health_check(
+                            self.cluster.labels
+                        ),
+                        "nodeVolumeDetachTimeout": self.cluster.labels.get(
+                            "node_volume_detach_timeout",
+                            CLUSTER_CLASS_NODE_VOLUME_DETACH_TIMEOUT,
+                        ),
                     },
                     "workers": {
                         "machineDeployments": generate_machine_deployments_for_cluster(
-                            self.context, self.cluster
+                            self.context, self.cluster, self.rust_driver
                         ),
                     },
                     "variables": [
@@ -1098,6 +1376,31 @@ class Cluster(ClusterBase):
                                 "maxSize": self.cluster.labels.get(
                                     "audit_log_max_size", "100"
                                 ),
+                                "policy": self.cluster.labels.get(
+                                    "audit_log_policy", ""
+                                ),
+                                "webhook": {
+                                    "enabled": bool(
+                                        self.cluster.labels.get(
+                                            "audit_webhook_kubeconfig"
+                                        )
+                                    ),
+                                    "kubeconfig": self.cluster.labels.get(
+                                        "audit_webhook_kubeconfig", ""
+                                    ),
+                                    "mode": self.cluster.labels.get(
+                                        "audit_webhook_mode", "batch"
+                                    ),
+                                    "batchBufferSize": self.cluster.labels.get(
+                                        "audit_webhook_batch_buffer_size", "10000"
+                                    ),
+                                    "batchMaxSize": self.cluster.labels.get(
+                                        "audit_webhook_batch_max_size", "400"
+                                    ),
+                                    "batchMaxWait": self.cluster.labels.get(
+                                        "audit_webhook_batch_max_wait", "30s"
+                                    ),
+                                },
                             },
                         },
                         {
@@ -1149,6 +1452,48 @@ class Cluster(ClusterBase):
                             "name": "controlPlaneFlavor",
                             "value": control_plane_flavor.name,
                         },
+                        {
+                            "name": "bastion",
+                            "value": variables["bastion"],
+                        },
+                        {
+                            "name": "controlPlaneRolloutStrategy",
+                            "value": {
+                                "maxSurge": utils.get_control_plane_max_surge(
+                                    self.cluster
+                                ),
+                            },
+                        },
+                        {
+                            "name": "extraArgs",
+                            "value": utils.get_extra_args(self.cluster),
+                        },
+                        {
+                            "name": "etcdConfig",
+                            "value": utils.get_etcd_config(self.cluster),
+                        },
+                        {
+                            "name": "etcdBackup",
+                            "value": utils.get_etcd_backup(self.cluster),
+                        },
+                        {
+                            "name": "swap",
+                            "value": utils.get_swap(self.cluster),
+                        },
+                        {
+                            "name": "bootstrapHooks",
+                            "value": utils.get_bootstrap_hooks(self.cluster.labels),
+                        },
+                        {
+                            "name": "containerRuntimes",
+                            "value": utils.get_container_runtimes(self.cluster.labels),
+                        },
+                        {
+                            "name": "kubeProxyReplacement",
+                            "value": utils.get_cilium_kube_proxy_replacement(
+                                self.cluster
+                            ),
+                        },
                         {
                             "name": "disableAPIServerFloatingIP",
                             "value": variables.get(
@@ -1221,6 +1566,17 @@ class Cluster(ClusterBase):
                             "name": "imageUUID",
                             "value": image.get("id"),
                         },
+                        {
+                            "name": "imageFilter",
+                            "value": {
+                                "name": self.cluster.labels.get(
+                                    "image_filter_name", ""
+                                ),
+                                "tags": utils.get_cluster_label_as_list(
+                                    self.cluster, "image_filter_tags", ""
+                                ),
+                            },
+                        },
                         {
                             "name": "kubeletTLSCipherSuites",
                             "value": self.cluster.labels.get(
@@ -1294,6 +1650,12 @@ class Cluster(ClusterBase):
                             "name": "availabilityZone",
                             "value": self.cluster.labels.get("availability_zone", ""),
                         },
+                        {
+                            "name": "additionalVolumes",
+                            "value": utils.get_additional_volumes(
+                                self.cluster.labels, "control-plane"
+                            ),
+                        },
                         {
                             "name": "enableKeystoneAuth",
                             "value": utils.get_cluster_label_as_bool(
@@ -1311,7 +1673,9 @@ class Cluster(ClusterBase):
                         {
                             "name": "serverGroupId",
                             "value": utils.ensure_controlplane_server_group(
-                                ctx=self.context, cluster=self.cluster
+                                ctx=self.context,
+                                cluster=self.cluster,
+                                rust_driver=self.rust_driver,
                             ),
                         },
                         # NOTE(oleks): Set cluster-level variable using cluster label for controlplane.
@@ -1326,6 +1690,65 @@ class Cluster(ClusterBase):
                             "name": "admissionControlList",
                             "value": self._get_admission_control_list(),
                         },
+                        {
+                            "name": "clusterUUID",
+                            "value": self.cluster.uuid,
+                        },
+                        {
+                            "name": "nodeGroupName",
+                            "value": self.cluster.default_ng_master.name,
+                        },
+                        {
+                            "name": "additionalNetworks",
+                            "value": utils.get_additional_networks(
+                                self.cluster.labels
+                            ),
+                        },
+                        {
+                            "name": "serverTags",
+                            "value": utils.get_server_tags(self.cluster),
+                        },
+                        {
+                            "name": "serverMetadata",
+                            "value": [
+                                {"key": key, "value": str(value)}
+                                for key, value in utils.get_cluster_label_as_json(
+                                    self.cluster, "server_metadata", {}
+                                ).items()
+                            ],
+                        },
+                        {
+                            "name": "podSecurity",
+                            "value": self._get_pod_security(),
+                        },
+                        {
+                            "name": "eventRateLimits",
+                            "value": self._get_event_rate_limits(),
+                        },
+                        {
+                            "name": "nodePortAllowedCIDRs",
+                            "value": utils.get_nodeport_allowed_cidrs(self.cluster),
+                        },
+                        {
+                            "name": "allNodesSecurityGroupRules",
+                            "value": utils.get_cluster_label_as_json(
+                                self.cluster, "all_nodes_security_group_rules", []
+                            ),
+                        },
+                        {
+                            "name": "controlPlaneNodesSecurityGroupRules",
+                            "value": utils.get_cluster_label_as_json(
+                                self.cluster,
+                                "control_plane_nodes_security_group_rules",
+                                [],
+                            ),
+                        },
+                        {
+                            "name": "workerNodesSecurityGroupRules",
+                            "value": utils.get_cluster_label_as_json(
+                                self.cluster, "worker_nodes_security_group_rules", []
+                            ),
+                        },
                     ],
                 },
             },
@@ -1342,13 +1765,14 @@ def apply_cluster_from_magnum_cluster(
     api: magnum_cluster_api.KubeClient,
     pykube_api: pykube.HTTPClient,
     cluster: magnum_objects.Cluster,
+    rust_driver: magnum_cluster_api.Driver,
     skip_auto_scaling_release: bool = False,
 ) -> None:
     """
     Create a ClusterAPI cluster given a Magnum Cluster object.
     """
 
-    ClusterServerGroups(context, cluster).apply()
+    ClusterServerGroups(context, cluster, rust_driver).apply()
     LegacyClusterResourcesSecret(context, api, pykube_api, cluster).apply()
     CloudProviderClusterResourcesSecret(context, api, pykube_api, cluster).apply()
 
@@ -12,8 +12,11 @@
 # License for the specific language governing permissions and limitations
 # under the License.
 
+import hashlib
 import types
 
+from oslo_serialization import jsonutils  # type: ignore
+
 from magnum_cluster_api import clients
 
 
@@ -120,6 +123,61 @@ def test_delete_application_credential_sdk_missing(mocker):
     identity.delete_application_credential.assert_not_called()
 
 
+def test_create_ec2_credential_legacy(mocker):
+    credential = types.SimpleNamespace(access="access", secret="secret")
+    manager = types.SimpleNamespace(create=mocker.Mock(return_value=credential))
+    identity = types.SimpleNamespace(ec2=manager)
+    osc = make_openstack_clients(
+        mocker, keystone=types.SimpleNamespace(client=identity)
+    )
+
+    result = osc.create_ec2_credential(user_id="user-id", project_id="project-id")
+
+    assert result == types.SimpleNamespace(access="access", secret="secret")
+    manager.create.assert_called_once_with("user-id", "project-id")
+
+
+def test_create_ec2_credential_sdk(mocker):
+    identity = types.SimpleNamespace(create_credential=mocker.Mock())
+    osc = make_openstack_clients(
+        mocker, keystone=types.SimpleNamespace(client=identity)
+    )
+
+    result = osc.create_ec2_credential(user_id="user-id", project_id="project-id")
+
+    identity.create_credential.assert_called_once_with(
+        type="ec2",
+        user_id="user-id",
+        project_id="project-id",
+        blob=jsonutils.dumps({"access": result.access, "secret": result.secret}),
+    )
+
+
+def test_delete_ec2_credential_legacy(mocker):
+    manager = types.SimpleNamespace(delete=mocker.Mock())
+    identity = types.SimpleNamespace(ec2=manager)
+    osc = make_openstack_clients(
+        mocker, keystone=types.SimpleNamespace(client=identity)
+    )
+
+    osc.delete_ec2_credential(user_id="user-id", access="access")
+
+    manager.delete.assert_called_once_with("user-id", "access")
+
+
+def test_delete_ec2_credential_sdk(mocker):
+    identity = types.SimpleNamespace(delete_credential=mocker.Mock())
+    osc = make_openstack_clients(
+        mocker, keystone=types.SimpleNamespace(client=identity)
+    )
+
+    osc.delete_ec2_credential(user_id="user-id", access="access")
+
+    identity.delete_credential.assert_called_once_with(
+        hashlib.sha256(b"access").hexdigest(), ignore_missing=True
+    )
+
+
 def test_is_service_enabled_legacy(mocker):
     manager = types.SimpleNamespace(
         list=mocker.Mock(return_value=[types.SimpleNamespace(enabled=True)])
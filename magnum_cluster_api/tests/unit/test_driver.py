@@ -368,6 +368,7 @@ class TestDriver:
                         context,
                         self.cluster,
                         self.node_group,
+                        ubuntu_driver.rust_driver,
                     ),
                 ],
             )
@@ -388,6 +389,7 @@ class TestDriver:
                         context,
                         self.cluster,
                         self.node_group,
+                        ubuntu_driver.rust_driver,
                         {
                             "name": self.node_group.name,
                         },
@@ -433,6 +435,7 @@ class TestDriver:
                         context,
                         self.cluster,
                         self.node_group,
+                        ubuntu_driver.rust_driver,
                         {
                             "name": self.node_group.name,
                         },
@@ -0,0 +1,59 @@
+# Copyright (c) 2024 VEXXHOST, Inc.
+#
+# Licensed under the Apache License, Version 2.0 (the "License"); you may
+# not use this file except in compliance with the License. You may obtain
+# a copy of the License at
+#
+#      http://www.apache.org/licenses/LICENSE-2.0
+#
+# Unless required by applicable law or agreed to in writing, software
+# distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+# WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+# License for the specific language governing permissions and limitations
+# under the License.
+
+import pytest
+from magnum.tests.unit.objects import utils as magnum_test_utils  # type: ignore
+
+from magnum_cluster_api import monitor
+
+
+class TestPollHealthStatus:
+    @pytest.fixture(autouse=True)
+    def setup(self, mocker):
+        mocker.patch("magnum_cluster_api.monitor.tpool.Proxy", side_effect=lambda o: o)
+        mocker.patch("magnum_cluster_api.monitor.clients.get_pykube_api")
+
+        self.rust_monitor = mocker.patch(
+            "magnum_cluster_api.monitor.RustMonitor"
+        ).return_value
+        self.rust_monitor.poll_health_status.return_value = {
+            "health_status": "HEALTHY",
+            "health_status_reason": {"api": "ok"},
+        }
+        self.rust_monitor.get_bastion_floating_ip.return_value = "203.0.113.10"
+
+    def test_bastion_floating_ip(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"bastion_enabled": "true"}
+        )
+
+        cluster_monitor = monitor.Monitor(context, cluster)
+        cluster_monitor.poll_health_status()
+
+        assert cluster_monitor.data == {
+            "health_status": "HEALTHY",
+            "health_status_reason": {"api": "ok", "bastion": "203.0.113.10"},
+        }
+
+    def test_bastion_disabled(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        cluster_monitor = monitor.Monitor(context, cluster)
+        cluster_monitor.poll_health_status()
+
+        assert cluster_monitor.data == {
+            "health_status": "HEALTHY",
+            "health_status_reason": {"api": "ok"},
+        }
+        self.rust_monitor.get_bastion_floating_ip.assert_not_called()
@@ -12,6 +12,8 @@
 # License for the specific language governing permissions and limitations
 # under the License.
 
+import types
+
 import pytest
 from magnum.objects import fields
 from magnum.tests.unit.objects import utils
@@ -77,6 +79,7 @@ def test_generate_machine_deployments_for_cluster_with_deleting_node_group(
     mds = resources.generate_machine_deployments_for_cluster(
         context,
         cluster,
+        mocker.Mock(),
     )
 
     assert len(mds) == 2
@@ -92,6 +95,34 @@ def test_generate_machine_deployments_for_cluster_with_deleting_node_group(
     [True, False, None],
     ids=lambda x: f"auto_healing_enabled={x}",
 )
+def test_etcd_backup_secret_uses_cluster_credential(context, mocker):
+    mocker.patch(
+        "magnum_cluster_api.resources.CONF.etcd_backup.endpoint",
+        "https://s3.example.com",
+    )
+    mocker.patch("magnum_cluster_api.resources.CONF.etcd_backup.bucket", "etcd")
+    mocker.patch("magnum_cluster_api.resources.CONF.etcd_backup.region", "us-east-1")
+    cluster = utils.get_test_cluster(context, labels={})
+
+    secret = resources.EtcdBackupSecret(
+        mocker.Mock(),
+        cluster,
+        types.SimpleNamespace(access="access", secret="secret"),
+    ).get_object()
+
+    data = secret["stringData"]
+    assert data["bucket"] == f"etcd-{cluster.project_id}"
+    assert data["ec2-access-key"] == "access"
+    assert data["ec2-secret-key"] == "secret"
+    assert data["credentials"] == (
+        "S3_ENDPOINT=https://s3.example.com\n"
+        f"S3_BUCKET=etcd-{cluster.project_id}\n"
+        "S3_REGION=us-east-1\n"
+        "S3_ACCESS_KEY=access\n"
+        "S3_SECRET_KEY=secret\n"
+    )
+
+
 class TestExistingMutateMachineDeployment:
     @pytest.fixture(autouse=True)
     def setup(self, auto_scaling_enabled, auto_healing_enabled, context, mocker):
@@ -131,11 +162,12 @@ class TestExistingMutateMachineDeployment:
             == resources.CLUSTER_CLASS_NODE_VOLUME_DETACH_TIMEOUT
         )
 
-    def test_mutate_machine_deployment(self, context, auto_scaling_enabled):
+    def test_mutate_machine_deployment(self, context, auto_scaling_enabled, mocker):
         md = resources.mutate_machine_deployment(
             context,
             self.cluster,
             self.node_group,
+            mocker.Mock(),
             {
                 "name": self.node_group.name,
             },
@@ -566,3 +566,902 @@ def test_wait_for_sdk_loadbalancers_deleted_times_out(mocker):
         utils._wait_for_sdk_loadbalancers_deleted(octavia_client, {"lb-id"})
 
     sleep.assert_called_once_with(1)
+
+
+class TestGetClusterLabelAsList:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_cluster_label_as_list(cluster, "foo", "0.0.0.0/0") == [
+            "0.0.0.0/0"
+        ]
+
+    def test_multiple_values(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"foo": "10.0.0.0/8, 192.168.0.0/16"}
+        )
+
+        assert utils.get_cluster_label_as_list(cluster, "foo", "0.0.0.0/0") == [
+            "10.0.0.0/8",
+            "192.168.0.0/16",
+        ]
+
+    def test_empty_value(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={"foo": ""})
+
+        assert utils.get_cluster_label_as_list(cluster, "foo", "0.0.0.0/0") == []
+
+
+class TestGetClusterLabelAsJson:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_cluster_label_as_json(cluster, "foo", []) == []
+
+    def test_value(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context,
+            labels={"foo": '[{"name": "ssh", "protocol": "tcp", "portRangeMin": 22}]'},
+        )
+
+        assert utils.get_cluster_label_as_json(cluster, "foo", []) == [
+            {"name": "ssh", "protocol": "tcp", "portRangeMin": 22}
+        ]
+
+
+class TestGetServerTags:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_server_tags(cluster) == []
+
+    def test_tags(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"server_tags": "billing:team-a, env=prod"}
+        )
+
+        assert utils.get_server_tags(cluster) == ["billing:team-a", "env=prod"]
+
+    def test_too_long(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"server_tags": "a" * 61}
+        )
+
+        with pytest.raises(exceptions.InvalidServerTag):
+            utils.get_server_tags(cluster)
+
+    @pytest.mark.parametrize("tag", ["team/a", 'team"a', "team\\a"])
+    def test_invalid_characters(self, context, tag):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"server_tags": tag}
+        )
+
+        with pytest.raises(exceptions.InvalidServerTag):
+            utils.get_server_tags(cluster)
+
+
+class TestGetNodePortAllowedCidrs:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_nodeport_allowed_cidrs(cluster) == ["0.0.0.0/0"]
+
+    def test_cidrs(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"nodeport_allowed_cidrs": "10.0.0.0/8, 192.168.0.0/16"}
+        )
+
+        assert utils.get_nodeport_allowed_cidrs(cluster) == [
+            "10.0.0.0/8",
+            "192.168.0.0/16",
+        ]
+
+    @pytest.mark.parametrize("cidr", ["fd00::/64", "10.0.0.1", "10.0.0.0/33"])
+    def test_invalid(self, context, cidr):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"nodeport_allowed_cidrs": cidr}
+        )
+
+        with pytest.raises(exceptions.InvalidNodePortAllowedCidr):
+            utils.get_nodeport_allowed_cidrs(cluster)
+
+
+class TestEnsureServerGroupWithOrc:
+    def test_controlplane(self, context, mocker):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"server_group_orc_enabled": "true"}
+        )
+        rust_driver = mocker.Mock()
+        rust_driver.apply_server_group.return_value = "server-group-id"
+        osc = mocker.patch("magnum_cluster_api.clients.get_openstack_api")
+
+        assert (
+            utils.ensure_controlplane_server_group(
+                ctx=context, cluster=cluster, rust_driver=rust_driver
+            )
+            == "server-group-id"
+        )
+        rust_driver.apply_server_group.assert_called_once_with(cluster)
+        osc.assert_not_called()
+
+    def test_worker(self, context, mocker):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"server_group_orc_enabled": "true"}
+        )
+        node_group = magnum_test_utils.get_test_nodegroup(context)
+        rust_driver = mocker.Mock()
+        rust_driver.apply_server_group.return_value = "server-group-id"
+
+        assert (
+            utils.ensure_worker_server_group(
+                ctx=context,
+                cluster=cluster,
+                node_group=node_group,
+                rust_driver=rust_driver,
+            )
+            == "server-group-id"
+        )
+        rust_driver.apply_server_group.assert_called_once_with(cluster, node_group)
+
+    def test_delete_worker(self, context, mocker):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"server_group_orc_enabled": "true"}
+        )
+        node_group = magnum_test_utils.get_test_nodegroup(context)
+        rust_driver = mocker.Mock()
+
+        utils.delete_worker_server_group(
+            ctx=context, cluster=cluster, node_group=node_group, rust_driver=rust_driver
+        )
+
+        rust_driver.delete_server_group.assert_called_once_with(cluster, node_group)
+
+
+class TestValidateServerGroupPolicies:
+    def test_default(self):
+        utils.validate_server_group_policies({})
+
+    def test_valid(self):
+        utils.validate_server_group_policies(
+            {"server_group_policies": "anti-affinity,soft-anti-affinity"}
+        )
+
+    def test_invalid(self):
+        with pytest.raises(exceptions.InvalidServerGroupPolicy):
+            utils.validate_server_group_policies(
+                {"server_group_policies": "anti-afinity"}
+            )
+
+
+class TestGetAdditionalVolumes:
+    VOLUMES = [
+        {"name": "data", "size": 10, "mountPath": "/data"},
+        {"name": "logs", "size": 5, "mountPath": "/var/log", "role": "all"},
+        {"name": "audit", "size": 1, "mountPath": "/audit", "role": "control-plane"},
+    ]
+
+    def test_default(self):
+        assert utils.get_additional_volumes({}, "worker") == []
+
+    def test_empty(self):
+        assert utils.get_additional_volumes({"additional_volumes": ""}, "worker") == []
+
+    def test_worker(self):
+        labels = {"additional_volumes": jsonutils.dumps(self.VOLUMES)}
+
+        assert utils.get_additional_volumes(labels, "worker") == [
+            {"name": "data", "size": 10, "mountPath": "/data"},
+            {"name": "logs", "size": 5, "mountPath": "/var/log"},
+        ]
+
+    def test_control_plane(self):
+        labels = {"additional_volumes": jsonutils.dumps(self.VOLUMES)}
+
+        assert utils.get_additional_volumes(labels, "control-plane") == [
+            {"name": "logs", "size": 5, "mountPath": "/var/log"},
+            {"name": "audit", "size": 1, "mountPath": "/audit"},
+        ]
+
+    @pytest.mark.parametrize("name", ["root", "etcd", "docker"])
+    def test_reserved_name(self, name):
+        labels = {
+            "additional_volumes": jsonutils.dumps(
+                [{"name": name, "size": 10, "mountPath": "/data"}]
+            )
+        }
+
+        with pytest.raises(exceptions.InvalidAdditionalVolumeName):
+            utils.get_additional_volumes(labels, "worker")
+
+    def test_duplicated_name(self):
+        labels = {
+            "additional_volumes": jsonutils.dumps(
+                [
+                    {"name": "data", "size": 10, "mountPath": "/data"},
+                    {"name": "data", "size": 5, "mountPath": "/data2", "role": "all"},
+                ]
+            )
+        }
+
+        with pytest.raises(exceptions.InvalidAdditionalVolumeName):
+            utils.get_additional_volumes(labels, "worker")
+
+    def test_duplicated_name_for_other_role(self):
+        labels = {
+            "additional_volumes": jsonutils.dumps(
+                [
+                    {"name": "data", "size": 10, "mountPath": "/data"},
+                    {
+                        "name": "data",
+                        "size": 5,
+                        "mountPath": "/data2",
+                        "role": "control-plane",
+                    },
+                ]
+            )
+        }
+
+        assert utils.get_additional_volumes(labels, "worker") == [
+            {"name": "data", "size": 10, "mountPath": "/data"}
+        ]
+
+
+class TestGetMachineHealthCheck:
+    def test_default(self):
+        assert utils.get_machine_health_check({}) == {"enable": True}
+
+    def test_disabled(self):
+        assert utils.get_machine_health_check(
+            {"auto_healing_enabled": "false", "auto_healing_max_unhealthy": "1"}
+        ) == {"enable": False}
+
+    def test_overrides(self):
+        assert utils.get_machine_health_check(
+            {
+                "auto_healing_max_unhealthy": "2",
+                "auto_healing_unhealthy_range": "[1-2]",
+                "auto_healing_unhealthy_timeout": "15m",
+                "auto_healing_node_startup_timeout": "30m",
+            }
+        ) == {
+            "enable": True,
+            "maxUnhealthy": 2,
+            "unhealthyRange": "[1-2]",
+            "unhealthyConditions": [
+                {"type": "Ready", "status": "False", "timeout": "15m"},
+                {"type": "Ready", "status": "Unknown", "timeout": "15m"},
+            ],
+            "nodeStartupTimeout": "30m",
+        }
+
+    def test_unhealthy_conditions(self):
+        conditions = [{"type": "DiskPressure", "status": "True", "timeout": "10m"}]
+
+        assert utils.get_machine_health_check(
+            {"auto_healing_unhealthy_conditions": jsonutils.dumps(conditions)}
+        ) == {
+            "enable": True,
+            "maxUnhealthy": "80%",
+            "unhealthyConditions": conditions,
+        }
+
+    def test_remediation_disabled(self):
+        assert utils.get_machine_health_check(
+            {
+                "auto_healing_remediation_enabled": "false",
+                "auto_healing_unhealthy_range": "[1-2]",
+            }
+        ) == {
+            "enable": True,
+            "maxUnhealthy": 0,
+            "unhealthyConditions": [
+                {"type": "Ready", "status": "False", "timeout": "5m0s"},
+                {"type": "Ready", "status": "Unknown", "timeout": "5m0s"},
+            ],
+        }
+
+    @pytest.mark.parametrize(
+        "labels",
+        [
+            {"auto_healing_max_unhealthy": "lots"},
+            {"auto_healing_unhealthy_range": "1-2"},
+            {"auto_healing_unhealthy_conditions": '[{"type": "Ready"}]'},
+            {"auto_healing_unhealthy_conditions": "not-json"},
+            {"auto_healing_unhealthy_timeout": "5 minutes"},
+            {"auto_healing_node_startup_timeout": "10"},
+            {
+                "auto_healing_unhealthy_conditions": jsonutils.dumps(
+                    [{"type": "Ready", "status": "False", "timeout": "1d"}]
+                )
+            },
+        ],
+    )
+    def test_invalid(self, labels):
+        with pytest.raises(exceptions.InvalidMachineHealthCheck):
+            utils.get_machine_health_check(labels)
+
+    def test_validate_nodegroup_with_cluster_labels(self, context, mocker):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"auto_healing_unhealthy_timeout": "10m"}
+        )
+        nodegroup = magnum_test_utils.get_test_nodegroup(
+            context, labels={"auto_healing_max_unhealthy": "1"}
+        )
+        get_machine_health_check = mocker.spy(utils, "get_machine_health_check")
+
+        utils.validate_nodegroup(cluster, nodegroup)
+
+        get_machine_health_check.assert_called_once_with(
+            {
+                "auto_healing_unhealthy_timeout": "10m",
+                "auto_healing_max_unhealthy": "1",
+            }
+        )
+
+
+class TestGetMachineDeploymentStrategy:
+    def test_default(self):
+        assert utils.get_machine_deployment_strategy({}) is None
+
+    def test_values(self):
+        assert utils.get_machine_deployment_strategy(
+            {"max_surge": "25%", "max_unavailable": "2", "delete_policy": "oldest"}
+        ) == {
+            "type": "RollingUpdate",
+            "rollingUpdate": {
+                "maxSurge": "25%",
+                "maxUnavailable": 2,
+                "deletePolicy": "Oldest",
+            },
+        }
+
+    @pytest.mark.parametrize(
+        "labels",
+        [
+            {"max_surge": "many"},
+            {"max_unavailable": "-1"},
+            {"max_surge": "0", "max_unavailable": "0"},
+            {"max_surge": "0"},
+            {"delete_policy": "youngest"},
+        ],
+    )
+    def test_invalid(self, labels):
+        with pytest.raises(exceptions.InvalidRolloutStrategy):
+            utils.get_machine_deployment_strategy(labels)
+
+    def test_validate_nodegroup_with_cluster_labels(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"max_surge": "0", "max_unavailable": "1"}
+        )
+        nodegroup = magnum_test_utils.get_test_nodegroup(
+            context, labels={"max_unavailable": "0"}
+        )
+
+        with pytest.raises(exceptions.InvalidRolloutStrategy):
+            utils.validate_nodegroup(cluster, nodegroup)
+
+
+class TestGetControlPlaneMaxSurge:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_control_plane_max_surge(cluster) == 1
+
+    def test_value(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"control_plane_max_surge": "0"}
+        )
+
+        assert utils.get_control_plane_max_surge(cluster) == 0
+
+    def test_invalid(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"control_plane_max_surge": "2"}
+        )
+
+        with pytest.raises(exceptions.InvalidRolloutStrategy):
+            utils.get_control_plane_max_surge(cluster)
+
+
+class TestGetExtraArgs:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_extra_args(cluster) == {
+            "apiServer": {},
+            "controllerManager": {},
+            "scheduler": {},
+            "etcd": {},
+        }
+
+    def test_allowed(self, context, mocker):
+        mocker.patch(
+            "magnum_cluster_api.utils.CONF.extra_args.api_server",
+            ["max-requests-inflight"],
+        )
+        mocker.patch("magnum_cluster_api.utils.CONF.extra_args.etcd", ["*"])
+        cluster = magnum_test_utils.get_test_cluster(
+            context,
+            labels={
+                "api_server_extra_args": jsonutils.dumps(
+                    {"--max-requests-inflight": 800}
+                ),
+                "etcd_extra_args": jsonutils.dumps(
+                    {"quota-backend-bytes": "8589934592", "experimental-x": True}
+                ),
+            },
+        )
+
+        assert utils.get_extra_args(cluster) == {
+            "apiServer": {"max-requests-inflight": "800"},
+            "controllerManager": {},
+            "scheduler": {},
+            "etcd": {"quota-backend-bytes": "8589934592", "experimental-x": "true"},
+        }
+
+    @pytest.mark.parametrize(
+        "value",
+        [
+            "not-json",
+            '["max-requests-inflight"]',
+            '{"max-mutating-requests-inflight": "400"}',
+            '{"profiling": "true"}',
+            '{"oidc-issuer-url": "https://example.com"}',
+            '{"Bad_Flag": "1"}',
+            '{"max-requests-inflight": "8\\"00"}',
+            '{"max-requests-inflight": ["800"]}',
+        ],
+    )
+    def test_invalid(self, context, mocker, value):
+        mocker.patch(
+            "magnum_cluster_api.utils.CONF.extra_args.api_server",
+            ["max-requests-inflight", "profiling", "oidc-issuer-url", "Bad_Flag"],
+        )
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"api_server_extra_args": value}
+        )
+
+        with pytest.raises(exceptions.InvalidExtraArgs):
+            utils.get_extra_args(cluster)
+
+
+class TestGetEtcdConfig:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_etcd_config(cluster) == {
+            "quotaBackendBytes": 0,
+            "snapshotCount": 0,
+            "heartbeatInterval": 0,
+            "electionTimeout": 0,
+            "autoCompactionMode": "",
+            "autoCompactionRetention": "",
+            "defrag": {"enabled": False, "schedule": "daily"},
+        }
+
+    def test_values(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context,
+            labels={
+                "etcd_quota_backend_bytes": "8589934592",
+                "etcd_snapshot_count": "10000",
+                "etcd_heartbeat_interval": "250",
+                "etcd_election_timeout": "2500",
+                "etcd_auto_compaction_mode": "periodic",
+                "etcd_auto_compaction_retention": "8h",
+                "etcd_defrag_enabled": "true",
+                "etcd_defrag_schedule": "Sun *-*-* 03:00:00",
+            },
+        )
+
+        assert utils.get_etcd_config(cluster) == {
+            "quotaBackendBytes": 8589934592,
+            "snapshotCount": 10000,
+            "heartbeatInterval": 250,
+            "electionTimeout": 2500,
+            "autoCompactionMode": "periodic",
+            "autoCompactionRetention": "8h",
+            "defrag": {"enabled": True, "schedule": "Sun *-*-* 03:00:00"},
+        }
+
+    @pytest.mark.parametrize(
+        "labels",
+        [
+            {"etcd_quota_backend_bytes": "17179869184"},
+            {"etcd_snapshot_count": "-1"},
+            {"etcd_heartbeat_interval": "500"},
+            {"etcd_election_timeout": "400"},
+            {"etcd_auto_compaction_mode": "daily"},
+            {"etcd_auto_compaction_retention": "8 hours"},
+            {"etcd_defrag_schedule": "daily\nExecStart=/bin/true"},
+        ],
+    )
+    def test_invalid(self, context, labels):
+        cluster = magnum_test_utils.get_test_cluster(context, labels=labels)
+
+        with pytest.raises(exceptions.InvalidEtcdConfig):
+            utils.get_etcd_config(cluster)
+
+
+class TestGetEtcdBackup:
+    @pytest.fixture(autouse=True)
+    def setup(self, mocker):
+        mocker.patch(
+            "magnum_cluster_api.utils.CONF.etcd_backup.endpoint",
+            "https://s3.example.com",
+        )
+        mocker.patch("magnum_cluster_api.utils.CONF.etcd_backup.bucket", "etcd")
+
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_etcd_backup(cluster) == {
+            "enabled": False,
+            "schedule": "daily",
+            "retention": 7,
+            "prefix": f"{cluster.project_id}/{cluster.uuid}",
+            "restore": "",
+        }
+
+    def test_values(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+        snapshot = f"{cluster.project_id}/{uuidutils.generate_uuid()}/etcd.db"
+        cluster.labels = {
+            "etcd_backup_enabled": "true",
+            "etcd_backup_schedule": "*-*-* 00/6:00:00",
+            "etcd_backup_retention": "28",
+            "etcd_backup_restore_snapshot": snapshot,
+        }
+
+        assert utils.get_etcd_backup(cluster) == {
+            "enabled": True,
+            "schedule": "*-*-* 00/6:00:00",
+            "retention": 28,
+            "prefix": f"{cluster.project_id}/{cluster.uuid}",
+            "restore": snapshot,
+        }
+
+    def test_bucket(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_etcd_backup_bucket(cluster) == f"etcd-{cluster.project_id}"
+
+    def test_not_configured(self, context, mocker):
+        mocker.patch("magnum_cluster_api.utils.CONF.etcd_backup.endpoint", None)
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"etcd_backup_enabled": "true"}
+        )
+
+        with pytest.raises(exceptions.InvalidEtcdBackup):
+            utils.get_etcd_backup(cluster)
+
+    @pytest.mark.parametrize(
+        "labels",
+        [
+            {"etcd_backup_schedule": "daily\nExecStart=/bin/true"},
+            {"etcd_backup_retention": "0"},
+            {"etcd_backup_restore_snapshot": "other-project/cluster/snapshot.db"},
+            {"etcd_backup_restore_snapshot": "../snapshot.db"},
+            {"etcd_backup_restore_snapshot": "fake_project/../snapshot.db"},
+        ],
+    )
+    def test_invalid(self, context, labels):
+        cluster = magnum_test_utils.get_test_cluster(context, labels=labels)
+
+        with pytest.raises(exceptions.InvalidEtcdBackup):
+            utils.get_etcd_backup(cluster)
+
+
+class TestGetSwap:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_swap(cluster) == {"size": 0, "behavior": "NoSwap"}
+
+    def test_values(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context,
+            labels={
+                "kube_tag": "v1.31.4",
+                "swap_size_mb": "4096",
+                "swap_behavior": "LimitedSwap",
+            },
+        )
+
+        assert utils.get_swap(cluster) == {"size": 4096, "behavior": "LimitedSwap"}
+
+    @pytest.mark.parametrize(
+        "labels",
+        [
+            {"kube_tag": "v1.31.4", "swap_size_mb": "-1"},
+            {"kube_tag": "v1.31.4", "swap_size_mb": "1G"},
+            {"kube_tag": "v1.31.4", "swap_behavior": "UnlimitedSwap"},
+            {"kube_tag": "v1.29.9", "swap_size_mb": "4096"},
+        ],
+    )
+    def test_invalid(self, context, labels):
+        cluster = magnum_test_utils.get_test_cluster(context, labels=labels)
+
+        with pytest.raises(exceptions.InvalidSwapConfig):
+            utils.get_swap(cluster)
+
+
+class TestGetBootstrapHooks:
+    def test_default(self):
+        assert utils.get_bootstrap_hooks({}) == {
+            "enabled": False,
+            "preKubeadmScript": "",
+            "postKubeadmScript": "",
+        }
+
+    def test_values(self):
+        hooks = utils.get_bootstrap_hooks(
+            {
+                "pre_kubeadm_commands": '["echo pre"]',
+                "post_kubeadm_commands": '["systemctl enable --now agent"]',
+                "extra_files": jsonutils.dumps(
+                    [
+                        {
+                            "path": "/etc/agent/agent.conf",
+                            "content": "key=value\n",
+                            "permissions": "0600",
+                        }
+                    ]
+                ),
+            }
+        )
+
+        content = base64.encode_as_text("key=value\n")
+
+        assert hooks["enabled"] is True
+        assert base64.decode_as_text(hooks["preKubeadmScript"]) == "\n".join(
+            [
+                "#!/bin/bash",
+                f"echo {content} | base64 -d | "
+                "install -D -m 0600 /dev/stdin /etc/agent/agent.conf",
+                "echo pre",
+                "",
+            ]
+        )
+        assert base64.decode_as_text(hooks["postKubeadmScript"]) == "\n".join(
+            ["#!/bin/bash", "systemctl enable --now agent", ""]
+        )
+
+    def test_only_files(self):
+        hooks = utils.get_bootstrap_hooks(
+            {"extra_files": '[{"path": "/etc/agent.conf", "content": ""}]'}
+        )
+
+        assert hooks["enabled"] is True
+        assert hooks["postKubeadmScript"] == ""
+
+    def test_not_allowed(self):
+        with pytest.raises(exceptions.InvalidBootstrapHooks):
+            utils.validate_bootstrap_hooks({"pre_kubeadm_commands": '["echo pre"]'})
+
+    def test_not_allowed_without_hooks(self):
+        utils.validate_bootstrap_hooks({"pre_kubeadm_commands": "[]"})
+
+    def test_allowed(self, mocker):
+        mocker.patch("magnum_cluster_api.utils.CONF.bootstrap_hooks.enabled", True)
+
+        utils.validate_bootstrap_hooks({"pre_kubeadm_commands": '["echo pre"]'})
+
+    def test_too_large(self, mocker):
+        mocker.patch("magnum_cluster_api.utils.CONF.bootstrap_hooks.max_size", 8)
+
+        with pytest.raises(exceptions.InvalidBootstrapHooks):
+            utils.get_bootstrap_hooks({"post_kubeadm_commands": '["echo post"]'})
+
+    @pytest.mark.parametrize(
+        "labels",
+        [
+            {"pre_kubeadm_commands": "echo pre"},
+            {"pre_kubeadm_commands": '"echo pre"'},
+            {"post_kubeadm_commands": "[1]"},
+            {"extra_files": '[{"path": "agent.conf", "content": ""}]'},
+            {"extra_files": '[{"path": "/etc/agent.conf"}]'},
+            {
+                "extra_files": jsonutils.dumps(
+                    [{"path": "/etc/agent.conf", "content": "", "permissions": "rw"}]
+                )
+            },
+        ],
+    )
+    def test_invalid(self, labels):
+        with pytest.raises(exceptions.InvalidBootstrapHooks):
+            utils.get_bootstrap_hooks(labels)
+
+
+class TestGetContainerRuntimes:
+    def test_default(self):
+        assert utils.get_container_runtimes({}) == []
+
+    def test_values(self):
+        assert utils.get_container_runtimes(
+            {"container_runtimes": "runsc, kata,runsc"}
+        ) == ["runsc", "kata"]
+
+    def test_invalid(self):
+        with pytest.raises(exceptions.InvalidContainerRuntime):
+            utils.get_container_runtimes({"container_runtimes": "runsc,crun"})
+
+
+class TestGetAdditionalNetworks:
+    def test_default(self):
+        assert utils.get_additional_networks({}) == []
+
+    def test_node_group_override(self):
+        labels = {"additional_networks": '[{"networkName": "storage"}]'}
+
+        assert utils.get_additional_networks(labels) == [{"networkName": "storage"}]
+        assert (
+            utils.get_additional_networks({**labels, "additional_networks": "[]"})
+            == []
+        )
+
+
+class TestGetCiliumKubeProxyReplacement:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_cilium_kube_proxy_replacement(cluster) is False
+
+    def test_enabled(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"cilium_kube_proxy_replacement": "true"}
+        )
+
+        assert utils.get_cilium_kube_proxy_replacement(cluster) is True
+
+    def test_invalid(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"cilium_kube_proxy_replacement": "strict"}
+        )
+
+        with pytest.raises(ValueError):
+            utils.get_cilium_kube_proxy_replacement(cluster)
+
+
+class TestGetCiliumEncryption:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_cilium_encryption(cluster) == ""
+
+    @pytest.mark.parametrize("value", ["wireguard", "ipsec"])
+    def test_values(self, context, value):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"cilium_encryption": value}
+        )
+
+        assert utils.get_cilium_encryption(cluster) == value
+
+    def test_invalid(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"cilium_encryption": "macsec"}
+        )
+
+        with pytest.raises(exceptions.InvalidCiliumConfig):
+            utils.get_cilium_encryption(cluster)
+
+
+class TestGetCiliumRoutingMode:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_cilium_routing_mode(cluster) == "tunnel"
+
+    def test_native(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"cilium_routing_mode": "native"}
+        )
+
+        assert utils.get_cilium_routing_mode(cluster) == "native"
+
+    def test_invalid(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"cilium_routing_mode": "geneve"}
+        )
+
+        with pytest.raises(exceptions.InvalidCiliumConfig):
+            utils.get_cilium_routing_mode(cluster)
+
+
+class TestGetCinderCSIStorageClasses:
+    def test_default(self, context, mocker):
+        mocker.patch("magnum_cluster_api.utils.CONF.cinder_csi.storage_classes", None)
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_cinder_csi_storage_classes(cluster) is None
+
+    def test_operator_default(self, context, mocker):
+        mocker.patch(
+            "magnum_cluster_api.utils.CONF.cinder_csi.storage_classes",
+            '[{"name": "ssd", "volume_type": "SSD"}]',
+        )
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert (
+            utils.get_cinder_csi_storage_classes(cluster)
+            == '[{"name": "ssd", "volume_type": "SSD"}]'
+        )
+
+    def test_label(self, context, mocker):
+        mocker.patch(
+            "magnum_cluster_api.utils.CONF.cinder_csi.storage_classes",
+            '[{"name": "ssd", "volume_type": "SSD"}]',
+        )
+        cluster = magnum_test_utils.get_test_cluster(
+            context,
+            labels={
+                "cinder_csi_storage_classes": '[{"name": "hdd", "volume_type": "HDD"}]'
+            },
+        )
+
+        assert (
+            utils.get_cinder_csi_storage_classes(cluster)
+            == '[{"name": "hdd", "volume_type": "HDD"}]'
+        )
+
+
+class TestValidateCinderCSIVolumeTypes:
+    @pytest.fixture
+    def osc(self, mocker):
+        osc = mocker.patch("magnum_cluster_api.clients.get_openstack_api")
+        ssd = mocker.Mock()
+        ssd.name = "SSD"
+        osc.return_value.list_volume_types.return_value = [ssd]
+        return osc
+
+    def test_valid(self, context, osc):
+        utils.validate_cinder_csi_volume_types(
+            context, '[{"name": "ssd", "volume_type": "SSD"}]'
+        )
+
+    def test_missing_volume_type(self, context, osc):
+        with pytest.raises(exceptions.InvalidCinderCSIStorageClasses):
+            utils.validate_cinder_csi_volume_types(
+                context,
+                '[{"name": "ssd", "volume_type": "SSD"}, '
+                '{"name": "hdd", "volume_type": "HDD"}]',
+            )
+
+
+class TestGetManilaCSIShareProtocols:
+    def test_default(self, context):
+        cluster = magnum_test_utils.get_test_cluster(context, labels={})
+
+        assert utils.get_manila_csi_share_protocols(cluster) == ["NFS"]
+
+    def test_multiple(self, context, mocker):
+        mocker.patch("magnum_cluster_api.utils.CONF.manila_csi.cephfs_enabled", True)
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"manila_csi_share_protocols": "cephfs, NFS,CEPHFS"}
+        )
+
+        assert utils.get_manila_csi_share_protocols(cluster) == ["CEPHFS", "NFS"]
+
+    def test_empty_entries(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"manila_csi_share_protocols": "NFS,"}
+        )
+
+        assert utils.get_manila_csi_share_protocols(cluster) == ["NFS"]
+
+    def test_cephfs_not_enabled(self, context, mocker):
+        mocker.patch("magnum_cluster_api.utils.CONF.manila_csi.cephfs_enabled", False)
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"manila_csi_share_protocols": "NFS,CEPHFS"}
+        )
+
+        with pytest.raises(exceptions.ManilaCSICephFSNotEnabled):
+            utils.get_manila_csi_share_protocols(cluster)
+
+    def test_invalid(self, context):
+        cluster = magnum_test_utils.get_test_cluster(
+            context, labels={"manila_csi_share_protocols": "NFS,GLUSTERFS"}
+        )
+
+        with pytest.raises(exceptions.InvalidManilaCSIShareProtocol):
+            utils.get_manila_csi_share_protocols(cluster)
@@ -14,14 +14,17 @@
 
 from __future__ import annotations
 
+import ipaddress
 import json
 import re
+import shlex
 import string
 import textwrap
 import time
 import typing
 
 import pykube  # type: ignore
+import semver
 import shortuuid
 import yaml
 from magnum import objects as magnum_objects  # type: ignore
@@ -37,6 +40,7 @@ from oslo_serialization import base64  # type: ignore
 from oslo_utils import strutils, uuidutils  # type: ignore
 from tenacity import retry, retry_if_exception_type
 
+import magnum_cluster_api
 from magnum_cluster_api import clients
 from magnum_cluster_api import exceptions as mcapi_exceptions
 from magnum_cluster_api import image_utils, images, objects
@@ -50,6 +54,28 @@ AVAILABLE_SERVER_GROUP_POLICIES = [
     "soft-affinity",
     "soft-anti-affinity",
 ]
+DEFAULT_MACHINE_HEALTH_CHECK_MAX_UNHEALTHY = "80%"
+DEFAULT_MACHINE_HEALTH_CHECK_UNHEALTHY_TIMEOUT = "5m0s"
+GO_DURATION_REGEX = r"0|([0-9]+(\.[0-9]+)?(ns|us|µs|ms|s|m|h))+"
+EXTRA_ARGS_COMPONENTS = {
+    "api_server_extra_args": ("apiServer", "api_server"),
+    "controller_manager_extra_args": ("controllerManager", "controller_manager"),
+    "scheduler_extra_args": ("scheduler", "scheduler"),
+    "etcd_extra_args": ("etcd", "etcd"),
+}
+EXTRA_ARGS_FLAG_RE = re.compile(r"[a-z0-9]([a-z0-9-]*[a-z0-9])?")
+SERVER_TAG_MAX_LENGTH = 60
+SERVER_TAG_INVALID_CHARACTERS = '/"\\'
+ETCD_MAX_QUOTA_BACKEND_BYTES = 8 * 1024 * 1024 * 1024
+ETCD_DEFAULT_HEARTBEAT_INTERVAL = 100
+ETCD_DEFAULT_ELECTION_TIMEOUT = 1000
+ETCD_BACKUP_KEY_RE = re.compile(
+    r"[A-Za-z0-9_-][A-Za-z0-9_.-]*(/[A-Za-z0-9_-][A-Za-z0-9_.-]*)*"
+)
+AVAILABLE_CONTAINER_RUNTIMES = ["runsc", "kata"]
+AVAILABLE_CILIUM_ENCRYPTIONS = ["wireguard", "ipsec"]
+AVAILABLE_CILIUM_ROUTING_MODES = ["tunnel", "native"]
+AVAILABLE_MANILA_CSI_SHARE_PROTOCOLS = ["NFS", "CEPHFS"]
 CONF = cfg.CONF
 
 
@@ -198,8 +224,94 @@ def get_auto_scaling_enabled(cluster: magnum_objects.Cluster) -> bool:
     return get_cluster_label_as_bool(cluster, "auto_scaling_enabled", False)
 
 
-def get_auto_healing_enabled(cluster: magnum_objects.Cluster) -> bool:
-    return get_cluster_label_as_bool(cluster, "auto_healing_enabled", True)
+def get_cilium_kube_proxy_replacement(cluster: magnum_objects.Cluster) -> bool:
+    return get_cluster_label_as_bool(cluster, "cilium_kube_proxy_replacement", False)
+
+
+def get_cilium_encryption(cluster: magnum_objects.Cluster) -> str:
+    value = cluster.labels.get("cilium_encryption", "")
+    if value and value not in AVAILABLE_CILIUM_ENCRYPTIONS:
+        raise mcapi_exceptions.InvalidCiliumConfig(
+            label="cilium_encryption", value=value
+        )
+    return value
+
+
+def get_cilium_routing_mode(cluster: magnum_objects.Cluster) -> str:
+    value = cluster.labels.get("cilium_routing_mode", "tunnel")
+    if value not in AVAILABLE_CILIUM_ROUTING_MODES:
+        raise mcapi_exceptions.InvalidCiliumConfig(
+            label="cilium_routing_mode", value=value
+        )
+    return value
+
+
+def get_cinder_csi_storage_classes(
+    cluster: magnum_objects.Cluster,
+) -> typing.Optional[str]:
+    return (
+        cluster.labels.get("cinder_csi_storage_classes")
+        or CONF.cinder_csi.storage_classes
+    )
+
+
+def validate_cinder_csi_volume_types(ctx: context.RequestContext, storage_classes: str):
+    osc = clients.get_openstack_api(ctx)
+    volume_types = {vt.name for vt in osc.list_volume_types()}
+    for storage_class in json.loads(storage_classes):
+        volume_type = storage_class["volume_type"]
+        if volume_type not in volume_types:
+            raise mcapi_exceptions.InvalidCinderCSIStorageClasses(
+                reason=f"volume type {volume_type} does not exist"
+            )
+
+
+def get_manila_csi_share_protocols(
+    cluster: magnum_objects.Cluster,
+) -> typing.List[str]:
+    protocols = []
+    for value in cluster.labels.get("manila_csi_share_protocols", "NFS").split(","):
+        protocol = value.strip().upper()
+        if not protocol:
+            continue
+        if protocol not in AVAILABLE_MANILA_CSI_SHARE_PROTOCOLS:
+            raise mcapi_exceptions.InvalidManilaCSIShareProtocol(protocol=value)
+        if protocol == "CEPHFS" and not CONF.manila_csi.cephfs_enabled:
+            raise mcapi_exceptions.ManilaCSICephFSNotEnabled()
+        if protocol not in protocols:
+            protocols.append(protocol)
+    return protocols
+
+
+def get_server_tags(cluster: magnum_objects.Cluster) -> typing.List[str]:
+    tags = get_cluster_label_as_list(cluster, "server_tags", "")
+    for tag in tags:
+        if len(tag) > SERVER_TAG_MAX_LENGTH:
+            raise mcapi_exceptions.InvalidServerTag(
+                tag=tag,
+                reason="must be at most %d characters" % SERVER_TAG_MAX_LENGTH,
+            )
+        if any(char in SERVER_TAG_INVALID_CHARACTERS for char in tag):
+            raise mcapi_exceptions.InvalidServerTag(
+                tag=tag, reason="must not contain slashes, quotes or backslashes"
+            )
+    return tags
+
+
+def get_nodeport_allowed_cidrs(cluster: magnum_objects.Cluster) -> typing.List[str]:
+    cidrs = get_cluster_label_as_list(cluster, "nodeport_allowed_cidrs", "0.0.0.0/0")
+    for cidr in cidrs:
+        try:
+            network = ipaddress.ip_network(cidr, strict=False)
+        except ValueError:
+            raise mcapi_exceptions.InvalidNodePortAllowedCidr(
+                cidr=cidr, reason="not a valid CIDR"
+            )
+        if network.version != 4 or "/" not in cidr:
+            raise mcapi_exceptions.InvalidNodePortAllowedCidr(
+                cidr=cidr, reason="must be an IPv4 CIDR"
+            )
+    return cidrs
 
 
 def get_cluster_container_infra_prefix(cluster: magnum_objects.Cluster) -> str:
@@ -307,6 +419,531 @@ def get_node_group_label_as_int(
     return strutils.validate_integer(value, key)
 
 
+def get_node_group_label_as_list(
+    node_group: magnum_objects.NodeGroup, key: str, default: str
+) -> list:
+    value = node_group.labels.get(key, default)
+    return [item.strip() for item in value.split(",") if item.strip()]
+
+
+def get_additional_volumes(labels: dict, role: str) -> list:
+    """
+    Return the additional volumes from the `additional_volumes` label which
+    should be attached to the machines of the given role.
+
+    :param labels: The labels of the cluster or node group.
+    :param role: Either "control-plane" or "worker".
+
+    :return: The additional volumes, without their role.
+    """
+    volumes = []
+    names = {"root", "etcd", "docker"}
+    for volume in json.loads(labels.get("additional_volumes") or "[]"):
+        volume = dict(volume)
+        if volume.pop("role", "worker") not in (role, "all"):
+            continue
+        if volume.get("name") in names:
+            raise mcapi_exceptions.InvalidAdditionalVolumeName(
+                name=volume.get("name")
+            )
+        names.add(volume.get("name"))
+        volumes.append(volume)
+    return volumes
+
+
+def get_bootstrap_hooks(labels: dict) -> dict:
+    """
+    Return the `bootstrapHooks` variable for the given cluster or node group
+    labels, where the extra files and commands are rendered into the base64
+    encoded scripts which run before and after kubeadm.
+
+    :param labels: The labels of the cluster or node group.
+
+    :return: The `bootstrapHooks` variable.
+    """
+
+    def _get_list(label: str) -> list:
+        try:
+            value = json.loads(labels.get(label) or "[]")
+        except ValueError:
+            raise mcapi_exceptions.InvalidBootstrapHooks(
+                label=label, reason="invalid JSON"
+            )
+        if not isinstance(value, list):
+            raise mcapi_exceptions.InvalidBootstrapHooks(
+                label=label, reason="expected a list"
+            )
+        return value
+
+    pre_kubeadm_commands = _get_list("pre_kubeadm_commands")
+    post_kubeadm_commands = _get_list("post_kubeadm_commands")
+    extra_files = _get_list("extra_files")
+
+    for label, commands in (
+        ("pre_kubeadm_commands", pre_kubeadm_commands),
+        ("post_kubeadm_commands", post_kubeadm_commands),
+    ):
+        if not all(isinstance(command, str) for command in commands):
+            raise mcapi_exceptions.InvalidBootstrapHooks(
+                label=label, reason="expected a list of strings"
+            )
+
+    size = sum(len(command.encode()) for command in pre_kubeadm_commands)
+    size += sum(len(command.encode()) for command in post_kubeadm_commands)
+
+    # NOTE: The extra files are written by the script before kubeadm since the
+    #       ClusterClass patches can only append a fixed number of files, while
+    #       every node group can have its own list of files.
+    pre_kubeadm_script = []
+    for extra_file in extra_files:
+        if (
+            not isinstance(extra_file, dict)
+            or not isinstance(extra_file.get("path"), str)
+            or not extra_file["path"].startswith("/")
+            or not isinstance(extra_file.get("content"), str)
+        ):
+            raise mcapi_exceptions.InvalidBootstrapHooks(
+                label="extra_files",
+                reason="expected a list of objects with an absolute path and a content",
+            )
+
+        permissions = extra_file.get("permissions", "0644")
+        if not isinstance(permissions, str) or not re.fullmatch(
+            r"0?[0-7]{3}", permissions
+        ):
+            raise mcapi_exceptions.InvalidBootstrapHooks(
+                label="extra_files", reason=f"invalid permissions {permissions}"
+            )
+
+        size += len(extra_file["content"].encode())
+        pre_kubeadm_script.append(
+            f"echo {base64.encode_as_text(extra_file['content'])} | base64 -d | "
+            f"install -D -m {permissions} /dev/stdin {shlex.quote(extra_file['path'])}"
+        )
+    pre_kubeadm_script.extend(pre_kubeadm_commands)
+
+    if size > CONF.bootstrap_hooks.max_size:
+        raise mcapi_exceptions.InvalidBootstrapHooks(
+            label="pre_kubeadm_commands, post_kubeadm_commands and extra_files",
+            reason=f"larger than {CONF.bootstrap_hooks.max_size} bytes",
+        )
+
+    def _encode_script(lines: list) -> str:
+        if not lines:
+            return ""
+        return base64.encode_as_text("\n".join(["#!/bin/bash", *lines, ""]))
+
+    return {
+        "enabled": bool(pre_kubeadm_script or post_kubeadm_commands),
+        "preKubeadmScript": _encode_script(pre_kubeadm_script),
+        "postKubeadmScript": _encode_script(post_kubeadm_commands),
+    }
+
+
+def validate_bootstrap_hooks(labels: dict):
+    """
+    Validate the commands and files of the given cluster or node group labels,
+    which can only be set if the operator allows them.
+
+    :param labels: The labels of the cluster or node group.
+    """
+    if get_bootstrap_hooks(labels)["enabled"] and not CONF.bootstrap_hooks.enabled:
+        raise mcapi_exceptions.InvalidBootstrapHooks(
+            label="pre_kubeadm_commands, post_kubeadm_commands and extra_files",
+            reason="not allowed by the operator",
+        )
+
+
+def get_container_runtimes(labels: dict) -> list:
+    """
+    Return the extra containerd runtime handlers from the comma-separated
+    `container_runtimes` label.
+
+    :param labels: The labels of the cluster or node group.
+
+    :return: The runtime handlers, without duplicates.
+    """
+    runtimes = []
+    for runtime in (labels.get("container_runtimes") or "").split(","):
+        runtime = runtime.strip()
+        if not runtime or runtime in runtimes:
+            continue
+        if runtime not in AVAILABLE_CONTAINER_RUNTIMES:
+            raise mcapi_exceptions.InvalidContainerRuntime(runtime=runtime)
+        runtimes.append(runtime)
+    return runtimes
+
+
+def get_additional_networks(labels: dict) -> list:
+    """
+    Return the secondary ports from the JSON-encoded `additional_networks`
+    label.
+
+    :param labels: The labels of the cluster or node group.
+
+    :return: The secondary ports.
+    """
+    value = labels.get("additional_networks")
+    if not value:
+        return []
+    return json.loads(value)
+
+
+def get_machine_health_check(labels: dict) -> dict:
+    """
+    Return the `machineHealthCheck` topology override for the given cluster or
+    node group labels.
+
+    Any override replaces the one from the ClusterClass entirely, so the
+    defaults from the ClusterClass are kept unless one of the `auto_healing_*`
+    labels is set.
+
+    :param labels: The labels of the cluster or node group.
+
+    :return: The `machineHealthCheck` for the control plane or machine deployment.
+    """
+    enabled = strutils.bool_from_string(
+        labels.get("auto_healing_enabled", True), strict=True
+    )
+    if not enabled:
+        return {"enable": False}
+
+    remediation_enabled = strutils.bool_from_string(
+        labels.get("auto_healing_remediation_enabled", True), strict=True
+    )
+    max_unhealthy = labels.get("auto_healing_max_unhealthy")
+    unhealthy_range = labels.get("auto_healing_unhealthy_range")
+    unhealthy_timeout = labels.get("auto_healing_unhealthy_timeout")
+    unhealthy_conditions = labels.get("auto_healing_unhealthy_conditions")
+    node_startup_timeout = labels.get("auto_healing_node_startup_timeout")
+
+    for label, value in (
+        ("auto_healing_unhealthy_timeout", unhealthy_timeout),
+        ("auto_healing_node_startup_timeout", node_startup_timeout),
+    ):
+        if value and not re.fullmatch(GO_DURATION_REGEX, value):
+            raise mcapi_exceptions.InvalidMachineHealthCheck(label=label, value=value)
+
+    if remediation_enabled and not any(
+        (
+            max_unhealthy,
+            unhealthy_range,
+            unhealthy_timeout,
+            unhealthy_conditions,
+            node_startup_timeout,
+        )
+    ):
+        return {"enable": True}
+
+    machine_health_check = {
+        "enable": True,
+        "maxUnhealthy": DEFAULT_MACHINE_HEALTH_CHECK_MAX_UNHEALTHY,
+        "unhealthyConditions": [
+            {
+                "type": "Ready",
+                "status": status,
+                "timeout": unhealthy_timeout
+                or DEFAULT_MACHINE_HEALTH_CHECK_UNHEALTHY_TIMEOUT,
+            }
+            for status in ("False", "Unknown")
+        ],
+    }
+
+    if max_unhealthy:
+        if re.fullmatch(r"[0-9]+", max_unhealthy):
+            machine_health_check["maxUnhealthy"] = int(max_unhealthy)
+        elif re.fullmatch(r"[0-9]+%", max_unhealthy):
+            machine_health_check["maxUnhealthy"] = max_unhealthy
+        else:
+            raise mcapi_exceptions.InvalidMachineHealthCheck(
+                label="auto_healing_max_unhealthy", value=max_unhealthy
+            )
+
+    if unhealthy_range:
+        if not re.fullmatch(r"\[[0-9]+-[0-9]+\]", unhealthy_range):
+            raise mcapi_exceptions.InvalidMachineHealthCheck(
+                label="auto_healing_unhealthy_range", value=unhealthy_range
+            )
+        machine_health_check["unhealthyRange"] = unhealthy_range
+
+    if unhealthy_conditions:
+        try:
+            conditions = json.loads(unhealthy_conditions)
+        except ValueError:
+            conditions = None
+        if not isinstance(conditions, list) or not all(
+            isinstance(condition, dict)
+            and {"type", "status", "timeout"} <= condition.keys()
+            and isinstance(condition["timeout"], str)
+            and re.fullmatch(GO_DURATION_REGEX, condition["timeout"])
+            for condition in conditions
+        ):
+            raise mcapi_exceptions.InvalidMachineHealthCheck(
+                label="auto_healing_unhealthy_conditions", value=unhealthy_conditions
+            )
+        machine_health_check["unhealthyConditions"] = conditions
+
+    if node_startup_timeout:
+        machine_health_check["nodeStartupTimeout"] = node_startup_timeout
+
+    # NOTE: Machines are still checked and marked as unhealthy, but no more
+    #       than zero unhealthy machines are allowed to be remediated.
+    if not remediation_enabled:
+        machine_health_check.pop("unhealthyRange", None)
+        machine_health_check["maxUnhealthy"] = 0
+
+    return machine_health_check
+
+
+def _get_int_or_percent(labels: dict, key: str) -> typing.Union[int, str, None]:
+    value = labels.get(key)
+    if not value:
+        return None
+    if re.fullmatch(r"[0-9]+", value):
+        return int(value)
+    if re.fullmatch(r"[0-9]+%", value):
+        return value
+    raise mcapi_exceptions.InvalidRolloutStrategy(label=key, value=value)
+
+
+def get_machine_deployment_strategy(labels: dict) -> typing.Optional[dict]:
+    """
+    Return the rollout `strategy` of a machine deployment from the `max_surge`,
+    `max_unavailable` and `delete_policy` labels of the node group.
+
+    :param labels: The labels of the node group.
+
+    :return: The strategy or `None` to use the Cluster API defaults.
+    """
+    rolling_update = {}
+
+    max_surge = _get_int_or_percent(labels, "max_surge")
+    if max_surge is not None:
+        rolling_update["maxSurge"] = max_surge
+
+    max_unavailable = _get_int_or_percent(labels, "max_unavailable")
+    if max_unavailable is not None:
+        rolling_update["maxUnavailable"] = max_unavailable
+
+    # NOTE: Cluster API defaults `maxUnavailable` to zero, so it is invalid to
+    #       only set `max_surge` to zero.
+    if max_surge in (0, "0%") and max_unavailable in (None, 0, "0%"):
+        raise mcapi_exceptions.InvalidRolloutStrategy(
+            label="max_surge", value=labels.get("max_surge")
+        )
+
+    delete_policy = labels.get("delete_policy")
+    if delete_policy:
+        if delete_policy.lower() not in ("random", "newest", "oldest"):
+            raise mcapi_exceptions.InvalidRolloutStrategy(
+                label="delete_policy", value=delete_policy
+            )
+        rolling_update["deletePolicy"] = delete_policy.capitalize()
+
+    if not rolling_update:
+        return None
+
+    return {"type": "RollingUpdate", "rollingUpdate": rolling_update}
+
+
+def get_control_plane_max_surge(cluster: magnum_objects.Cluster) -> int:
+    max_surge = get_cluster_label_as_int(cluster, "control_plane_max_surge", 1)
+    if max_surge not in (0, 1):
+        raise mcapi_exceptions.InvalidRolloutStrategy(
+            label="control_plane_max_surge", value=max_surge
+        )
+    return max_surge
+
+
+def get_extra_args(cluster: magnum_objects.Cluster) -> dict:
+    extra_args = {}
+
+    for label, (component, opt) in EXTRA_ARGS_COMPONENTS.items():
+        try:
+            flags = get_cluster_label_as_json(cluster, label, {})
+        except ValueError:
+            raise mcapi_exceptions.InvalidExtraArgs(
+                flag="", label=label, reason="value must be a JSON object"
+            )
+        if not isinstance(flags, dict):
+            raise mcapi_exceptions.InvalidExtraArgs(
+                flag="", label=label, reason="value must be a JSON object"
+            )
+
+        allowed_flags = getattr(CONF.extra_args, opt)
+        owned_flags = magnum_cluster_api.EXTRA_ARGS_OWNED_FLAGS[component]
+
+        args = {}
+        for flag, value in flags.items():
+            flag = flag.removeprefix("--")
+            if not EXTRA_ARGS_FLAG_RE.fullmatch(flag):
+                raise mcapi_exceptions.InvalidExtraArgs(
+                    flag=flag, label=label, reason="invalid flag name"
+                )
+            if flag in owned_flags:
+                raise mcapi_exceptions.InvalidExtraArgs(
+                    flag=flag, label=label, reason="flag is managed by the driver"
+                )
+            if "*" not in allowed_flags and flag not in allowed_flags:
+                raise mcapi_exceptions.InvalidExtraArgs(
+                    flag=flag, label=label, reason="flag is not allowed"
+                )
+            if isinstance(value, bool):
+                value = str(value).lower()
+            if not isinstance(value, (str, int, float)):
+                raise mcapi_exceptions.InvalidExtraArgs(
+                    flag=flag, label=label, reason="value must be a string"
+                )
+            value = str(value)
+            if '"' in value or "\\" in value or "\n" in value:
+                raise mcapi_exceptions.InvalidExtraArgs(
+                    flag=flag, label=label, reason="value contains invalid characters"
+                )
+            args[flag] = value
+
+        extra_args[component] = args
+
+    return extra_args
+
+
+def get_etcd_config(cluster: magnum_objects.Cluster) -> dict:
+    def _get_int(label: str, maximum: typing.Optional[int] = None) -> int:
+        try:
+            return strutils.validate_integer(
+                cluster.labels.get(label, 0), label, min_value=0, max_value=maximum
+            )
+        except ValueError:
+            raise mcapi_exceptions.InvalidEtcdConfig(
+                label=label, value=cluster.labels.get(label)
+            )
+
+    quota_backend_bytes = _get_int(
+        "etcd_quota_backend_bytes", ETCD_MAX_QUOTA_BACKEND_BYTES
+    )
+    snapshot_count = _get_int("etcd_snapshot_count")
+    heartbeat_interval = _get_int("etcd_heartbeat_interval")
+    election_timeout = _get_int("etcd_election_timeout")
+
+    # NOTE: etcd refuses to start unless the election timeout is at least five
+    #       times the heartbeat interval.
+    if (election_timeout or ETCD_DEFAULT_ELECTION_TIMEOUT) < 5 * (
+        heartbeat_interval or ETCD_DEFAULT_HEARTBEAT_INTERVAL
+    ):
+        raise mcapi_exceptions.InvalidEtcdConfig(
+            label="etcd_election_timeout", value=election_timeout
+        )
+
+    auto_compaction_mode = cluster.labels.get("etcd_auto_compaction_mode", "")
+    if auto_compaction_mode not in ("", "periodic", "revision"):
+        raise mcapi_exceptions.InvalidEtcdConfig(
+            label="etcd_auto_compaction_mode", value=auto_compaction_mode
+        )
+
+    auto_compaction_retention = cluster.labels.get("etcd_auto_compaction_retention", "")
+    if not re.fullmatch(r"[0-9a-z.]*", auto_compaction_retention):
+        raise mcapi_exceptions.InvalidEtcdConfig(
+            label="etcd_auto_compaction_retention", value=auto_compaction_retention
+        )
+
+    defrag_schedule = cluster.labels.get("etcd_defrag_schedule", "daily")
+    if not re.fullmatch(r"[A-Za-z0-9*:,./~ -]+", defrag_schedule):
+        raise mcapi_exceptions.InvalidEtcdConfig(
+            label="etcd_defrag_schedule", value=defrag_schedule
+        )
+
+    return {
+        "quotaBackendBytes": quota_backend_bytes,
+        "snapshotCount": snapshot_count,
+        "heartbeatInterval": heartbeat_interval,
+        "electionTimeout": election_timeout,
+        "autoCompactionMode": auto_compaction_mode,
+        "autoCompactionRetention": auto_compaction_retention,
+        "defrag": {
+            "enabled": get_cluster_label_as_bool(cluster, "etcd_defrag_enabled", False),
+            "schedule": defrag_schedule,
+        },
+    }
+
+
+def is_etcd_backup_configured() -> bool:
+    return all(
+        [
+            CONF.etcd_backup.endpoint,
+            CONF.etcd_backup.bucket,
+        ]
+    )
+
+
+def get_etcd_backup_secret_name(cluster: magnum_objects.Cluster) -> str:
+    return f"{cluster.stack_id}-etcd-backup"
+
+
+def get_etcd_backup_bucket(cluster: magnum_objects.Cluster) -> str:
+    return f"{CONF.etcd_backup.bucket}-{cluster.project_id}"
+
+
+def get_etcd_backup(cluster: magnum_objects.Cluster) -> dict:
+    enabled = get_cluster_label_as_bool(cluster, "etcd_backup_enabled", False)
+    restore = cluster.labels.get("etcd_backup_restore_snapshot", "")
+
+    if (enabled or restore) and not is_etcd_backup_configured():
+        raise mcapi_exceptions.InvalidEtcdBackup(
+            reason="object storage for etcd snapshots is not configured"
+        )
+
+    schedule = cluster.labels.get("etcd_backup_schedule", "daily")
+    if not re.fullmatch(r"[A-Za-z0-9*:,./~ -]+", schedule):
+        raise mcapi_exceptions.InvalidEtcdBackup(reason=f"invalid schedule {schedule}")
+
+    retention = get_cluster_label_as_int(cluster, "etcd_backup_retention", 7)
+    if retention < 1:
+        raise mcapi_exceptions.InvalidEtcdBackup(
+            reason="at least one snapshot must be kept"
+        )
+
+    # NOTE: Snapshots are stored by project, so a cluster can only be restored
+    #       from the snapshot of a cluster in the same project.
+    if restore and (
+        not ETCD_BACKUP_KEY_RE.fullmatch(restore)
+        or not restore.startswith(f"{cluster.project_id}/")
+    ):
+        raise mcapi_exceptions.InvalidEtcdBackup(reason=f"invalid snapshot {restore}")
+
+    return {
+        "enabled": enabled,
+        "schedule": schedule,
+        "retention": retention,
+        "prefix": f"{cluster.project_id}/{cluster.uuid}",
+        "restore": restore,
+    }
+
+
+def get_swap(cluster: magnum_objects.Cluster) -> dict:
+    try:
+        size = strutils.validate_integer(
+            cluster.labels.get("swap_size_mb", 0), "swap_size_mb", min_value=0
+        )
+    except ValueError:
+        raise mcapi_exceptions.InvalidSwapConfig(
+            label="swap_size_mb", value=cluster.labels.get("swap_size_mb")
+        )
+
+    behavior = cluster.labels.get("swap_behavior", "NoSwap")
+    if behavior not in ("NoSwap", "LimitedSwap"):
+        raise mcapi_exceptions.InvalidSwapConfig(label="swap_behavior", value=behavior)
+
+    # NOTE: The kubelet only accepts the `NoSwap` behavior and configures the
+    #       limits of `LimitedSwap` properly starting with Kubernetes 1.30.
+    kube_tag = get_kube_tag(cluster)
+    if size and semver.VersionInfo.parse(kube_tag[1:]).compare("1.30.0") < 0:
+        raise mcapi_exceptions.InvalidSwapConfig(label="kube_tag", value=kube_tag)
+
+    return {
+        "size": size,
+        "behavior": behavior,
+    }
+
+
 def get_cluster_label_as_int(
     cluster: magnum_objects.Cluster, key: str, default: int
 ) -> int:
@@ -321,6 +958,22 @@ def get_cluster_label_as_bool(
     return strutils.bool_from_string(value, strict=True)
 
 
+def get_cluster_label_as_list(
+    cluster: magnum_objects.Cluster, key: str, default: str
+) -> list:
+    value = cluster.labels.get(key, default)
+    return [item.strip() for item in value.split(",") if item.strip()]
+
+
+def get_cluster_label_as_json(
+    cluster: magnum_objects.Cluster, key: str, default: typing.Any
+) -> typing.Any:
+    value = cluster.labels.get(key)
+    if not value:
+        return default
+    return json.loads(value)
+
+
 def delete_loadbalancers(ctx, cluster):
     # NOTE(mnaser): This code is duplicated from magnum.common.octavia
     #               since the original code is very Heat-specific.
@@ -467,6 +1120,81 @@ def validate_cluster(ctx: context.RequestContext, cluster: magnum_objects.Cluste
     if (cluster.master_count % 2) == 0:
         raise mcapi_exceptions.ClusterMasterCountEven
 
+    # Check cluster network
+    try:
+        magnum_cluster_api.Driver.validate_cluster_network(cluster)
+    except RuntimeError as e:
+        raise mcapi_exceptions.InvalidClusterNetwork(reason=str(e))
+
+    # Check kube-proxy replacement
+    if (
+        get_cilium_kube_proxy_replacement(cluster)
+        and cluster.cluster_template.network_driver != "cilium"
+    ):
+        raise mcapi_exceptions.KubeProxyReplacementRequiresCilium()
+
+    # Check Calico version
+    try:
+        magnum_cluster_api.Driver.validate_calico_tag(cluster)
+    except RuntimeError as e:
+        raise mcapi_exceptions.InvalidCalicoTag(reason=str(e))
+
+    # Check Cilium configuration
+    get_cilium_encryption(cluster)
+    get_cilium_routing_mode(cluster)
+
+    # Check Cinder CSI storage classes
+    storage_classes = get_cinder_csi_storage_classes(cluster)
+    try:
+        magnum_cluster_api.Driver.validate_cinder_csi_storage_classes(
+            cluster, storage_classes=storage_classes
+        )
+    except RuntimeError as e:
+        raise mcapi_exceptions.InvalidCinderCSIStorageClasses(reason=str(e))
+    if storage_classes and get_cluster_label_as_bool(
+        cluster, "cinder_csi_enabled", True
+    ):
+        validate_cinder_csi_volume_types(ctx, storage_classes)
+
+    # Check Manila CSI share protocols
+    get_manila_csi_share_protocols(cluster)
+
+    # Check server tags
+    get_server_tags(cluster)
+
+    # Check NodePort allowed CIDRs
+    get_nodeport_allowed_cidrs(cluster)
+
+    # Check additional volumes
+    get_additional_volumes(cluster.labels, "control-plane")
+    get_additional_volumes(cluster.labels, "worker")
+
+    # Check machine health check
+    get_machine_health_check(cluster.labels)
+
+    # Check rollout strategy
+    get_control_plane_max_surge(cluster)
+    get_machine_deployment_strategy(cluster.labels)
+
+    # Check extra arguments
+    get_extra_args(cluster)
+
+    # Check etcd configuration
+    get_etcd_config(cluster)
+    get_etcd_backup(cluster)
+
+    # Check swap configuration
+    get_swap(cluster)
+
+    # Check bootstrap hooks
+    validate_bootstrap_hooks(cluster.labels)
+
+    # Check container runtimes
+    get_container_runtimes(cluster.labels)
+
+    # Check server group policies
+    validate_server_group_policies(cluster.labels)
+
     # Check if fixed_network exists
     if cluster.fixed_network:
         if uuidutils.is_uuid_like(cluster.fixed_network):
@@ -501,8 +1229,19 @@ def validate_nodegroup_name(nodegroup: magnum_objects.NodeGroup):
         raise mcapi_exceptions.MachineInvalidName(name=nodegroup.name)
 
 
-def validate_nodegroup(nodegroup: magnum_objects.NodeGroup):
+def validate_nodegroup(
+    cluster: magnum_objects.Cluster, nodegroup: magnum_objects.NodeGroup
+):
+    # NOTE: Node group labels take precedence over the cluster labels.
+    node_group_labels = {**cluster.labels, **nodegroup.labels}
+
     validate_nodegroup_name(nodegroup)
+    get_additional_volumes(nodegroup.labels, "worker")
+    get_machine_health_check(node_group_labels)
+    get_machine_deployment_strategy(node_group_labels)
+    validate_bootstrap_hooks(nodegroup.labels)
+    get_container_runtimes(nodegroup.labels)
+    validate_server_group_policies(nodegroup.labels)
 
 
 def get_operating_system(cluster: magnum_objects.Cluster):
@@ -604,6 +1343,18 @@ def get_server_group_id(
     return None
 
 
+def validate_server_group_policies(labels: dict):
+    """
+    Validate the comma-separated `server_group_policies` of the given cluster
+    or node group labels, which are all expected to be supported by Nova.
+
+    :param labels: The labels of the cluster or node group.
+    """
+    for policy in (labels.get("server_group_policies") or "").split(","):
+        if policy and policy not in AVAILABLE_SERVER_GROUP_POLICIES:
+            raise mcapi_exceptions.InvalidServerGroupPolicy(policy=policy)
+
+
 def _get_node_group_server_group_policies(
     node_group: magnum_objects.NodeGroup,
     cluster: magnum_objects.Cluster,
@@ -643,10 +1394,18 @@ def is_controlplane_different_failure_domain(
     return get_cluster_label_as_bool(cluster, "different_failure_domain", False)
 
 
+def is_server_group_orc_enabled(cluster: magnum_objects.Cluster) -> bool:
+    return get_cluster_label_as_bool(cluster, "server_group_orc_enabled", False)
+
+
 def ensure_controlplane_server_group(
     ctx: context.RequestContext,
     cluster: magnum_objects.Cluster,
+    rust_driver: magnum_cluster_api.Driver,
 ):
+    if is_server_group_orc_enabled(cluster):
+        return rust_driver.apply_server_group(cluster)
+
     return _ensure_server_group(
         name=cluster.stack_id,
         ctx=ctx,
@@ -659,7 +1418,11 @@ def ensure_worker_server_group(
     ctx: context.RequestContext,
     cluster: magnum_objects.Cluster,
     node_group: magnum_objects.NodeGroup,
+    rust_driver: magnum_cluster_api.Driver,
 ):
+    if is_server_group_orc_enabled(cluster):
+        return rust_driver.apply_server_group(cluster, node_group)
+
     return _ensure_server_group(
         name=f"{cluster.stack_id}-{node_group.name}",
         ctx=ctx,
@@ -671,7 +1434,12 @@ def ensure_worker_server_group(
 def delete_controlplane_server_group(
     ctx: context.RequestContext,
     cluster: magnum_objects.Cluster,
+    rust_driver: magnum_cluster_api.Driver,
 ):
+    if is_server_group_orc_enabled(cluster):
+        rust_driver.delete_server_group(cluster)
+        return
+
     _delete_server_group(
         name=cluster.stack_id,
         ctx=ctx,
@@ -683,7 +1451,12 @@ def delete_worker_server_group(
     ctx: context.RequestContext,
     cluster: magnum_objects.Cluster,
     node_group: magnum_objects.NodeGroup,
+    rust_driver: magnum_cluster_api.Driver,
 ):
+    if is_server_group_orc_enabled(cluster):
+        rust_driver.delete_server_group(cluster, node_group)
+        return
+
     _delete_server_group(
         name=f"{cluster.stack_id}-{node_group.name}",
         ctx=ctx,
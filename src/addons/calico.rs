@@ -0,0 +1,281 @@
+use crate::{
+    addons::ClusterAddon,
+    magnum::{self, ClusterError},
+};
+use include_dir::{include_dir, Dir};
+use maplit::btreemap;
+use pyo3::{exceptions::PyRuntimeError, PyErr};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Manifests for every supported Calico version, named after the `calico_tag`.
+static MANIFESTS: Dir = include_dir!("magnum_cluster_api/manifests/calico");
+
+#[derive(Debug, Error)]
+pub enum CalicoError {
+    #[error("unsupported calico version: {0}")]
+    UnsupportedVersion(String),
+}
+
+impl From<CalicoError> for PyErr {
+    fn from(err: CalicoError) -> PyErr {
+        PyErr::new::<PyRuntimeError, _>(err.to_string())
+    }
+}
+
+/// Returns the manifest which is shipped for the given Calico version.
+pub fn manifest(tag: &str) -> Result<&'static str, CalicoError> {
+    MANIFESTS
+        .get_file(format!("{}.yaml", tag))
+        .and_then(|file| file.contents_utf8())
+        .ok_or_else(|| CalicoError::UnsupportedVersion(tag.to_owned()))
+}
+
+fn get_mirrored_image_name(image: &str, registry: &Option<String>) -> String {
+    match registry {
+        Some(ref registry) => image.replace(
+            "quay.io/calico/",
+            &format!("{}/calico/", registry.trim_end_matches('/')),
+        ),
+        None => image.to_owned(),
+    }
+}
+
+pub struct Addon {
+    cluster: magnum::Cluster,
+}
+
+impl Addon {
+    /// Points the images of the workloads to the registry of the cluster and
+    /// creates the default IP pool of `calico-node` from the `calico_ipv4pool`.
+    fn patch(&self, document: &mut Value) {
+        if !matches!(
+            document.get("kind").and_then(Value::as_str),
+            Some("DaemonSet" | "Deployment" | "StatefulSet")
+        ) {
+            return;
+        }
+
+        let Some(spec) = document
+            .get_mut("spec")
+            .and_then(|spec| spec.get_mut("template"))
+            .and_then(|template| template.get_mut("spec"))
+        else {
+            return;
+        };
+
+        for key in ["initContainers", "containers"] {
+            let Some(containers) = spec.get_mut(key).and_then(Value::as_sequence_mut) else {
+                continue;
+            };
+
+            for container in containers {
+                let Some(container) = container.as_mapping_mut() else {
+                    continue;
+                };
+
+                if let Some(image) = container.get("image").and_then(Value::as_str) {
+                    let image =
+                        get_mirrored_image_name(image, &self.cluster.labels.container_infra_prefix);
+                    container.insert("image".into(), image.into());
+                }
+
+                if key == "containers"
+                    && container.get("name").and_then(Value::as_str) == Some("calico-node")
+                {
+                    self.set_ipv4_pool(container);
+                }
+            }
+        }
+    }
+
+    fn set_ipv4_pool(&self, container: &mut Mapping) {
+        let env = container
+            .entry("env".into())
+            .or_insert_with(|| Value::Sequence(vec![]));
+        let Some(env) = env.as_sequence_mut() else {
+            return;
+        };
+
+        env.retain(|var| var.get("name").and_then(Value::as_str) != Some("CALICO_IPV4POOL_CIDR"));
+
+        let mut var = Mapping::new();
+        var.insert("name".into(), "CALICO_IPV4POOL_CIDR".into());
+        var.insert(
+            "value".into(),
+            self.cluster.labels.calico_ipv4pool.clone().into(),
+        );
+        env.push(Value::Mapping(var));
+    }
+}
+
+impl ClusterAddon for Addon {
+    fn new(cluster: magnum::Cluster) -> Self {
+        Self { cluster }
+    }
+
+    fn enabled(&self) -> bool {
+        self.cluster.cluster_template.network_driver == "calico"
+    }
+
+    fn secret_name(&self) -> Result<String, ClusterError> {
+        Ok(format!("{}-calico", self.cluster.stack_id()?))
+    }
+
+    fn manifests(&self) -> Result<BTreeMap<String, String>, helm::HelmTemplateError> {
+        let manifest = manifest(&self.cluster.labels.calico_tag).expect("failed to get manifest");
+
+        let documents = serde_yaml::Deserializer::from_str(manifest)
+            .map(|document| {
+                let mut document = Value::deserialize(document)?;
+                self.patch(&mut document);
+
+                serde_yaml::to_string(&document)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(btreemap! {
+            "calico.yml".to_owned() => documents.join("---\n"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn cluster(labels: magnum::ClusterLabels) -> magnum::Cluster {
+        magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels,
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "calico".to_string(),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn rendered_documents(cluster: magnum::Cluster) -> Vec<Value> {
+        let addon = Addon::new(cluster);
+        let manifests = addon.manifests().expect("failed to get manifests");
+
+        serde_yaml::Deserializer::from_str(&manifests["calico.yml"])
+            .map(|document| Value::deserialize(document).expect("failed to parse document"))
+            .collect()
+    }
+
+    fn images(documents: &[Value]) -> Vec<String> {
+        documents
+            .iter()
+            .filter_map(|document| document["spec"]["template"]["spec"].as_mapping())
+            .flat_map(|spec| {
+                ["initContainers", "containers"]
+                    .into_iter()
+                    .filter_map(|key| spec.get(key).and_then(Value::as_sequence))
+                    .flatten()
+            })
+            .map(|container| container["image"].as_str().unwrap().to_owned())
+            .collect()
+    }
+
+    fn calico_node_env(documents: &[Value], name: &str) -> Vec<String> {
+        documents
+            .iter()
+            .filter(|document| document["kind"] == "DaemonSet")
+            .flat_map(|document| {
+                document["spec"]["template"]["spec"]["containers"]
+                    .as_sequence()
+                    .unwrap()
+            })
+            .filter(|container| container["name"] == "calico-node")
+            .flat_map(|container| container["env"].as_sequence().unwrap())
+            .filter(|var| var["name"] == name)
+            .map(|var| var["value"].as_str().unwrap().to_owned())
+            .collect()
+    }
+
+    #[rstest]
+    #[case("calico", true)]
+    #[case("cilium", false)]
+    fn test_enabled(#[case] network_driver: &str, #[case] expected: bool) {
+        let mut cluster = cluster(magnum::ClusterLabels::builder().build());
+        cluster.cluster_template.network_driver = network_driver.to_string();
+
+        assert_eq!(Addon::new(cluster).enabled(), expected);
+    }
+
+    #[test]
+    fn test_manifest_for_every_version() {
+        for file in MANIFESTS.files() {
+            let tag = file.path().file_stem().unwrap().to_str().unwrap();
+
+            assert!(manifest(tag).is_ok(), "missing manifest for {}", tag);
+        }
+    }
+
+    #[test]
+    fn test_manifest_for_unsupported_version() {
+        let err = manifest("v1.0.0").expect_err("version should not be supported");
+
+        assert_eq!(err.to_string(), "unsupported calico version: v1.0.0");
+    }
+
+    #[test]
+    fn test_get_manifests_for_calico_tag() {
+        let documents = rendered_documents(cluster(
+            magnum::ClusterLabels::builder()
+                .calico_tag("v3.29.3".to_string())
+                .build(),
+        ));
+
+        assert_eq!(
+            images(&documents),
+            vec![
+                "quay.io/calico/cni:v3.29.3",
+                "quay.io/calico/cni:v3.29.3",
+                "quay.io/calico/node:v3.29.3",
+                "quay.io/calico/node:v3.29.3",
+                "quay.io/calico/kube-controllers:v3.29.3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_manifests_with_container_infra_prefix() {
+        let documents = rendered_documents(cluster(
+            magnum::ClusterLabels::builder()
+                .container_infra_prefix(Some("registry.example.com/".to_string()))
+                .build(),
+        ));
+
+        assert_eq!(
+            images(&documents),
+            vec![
+                "registry.example.com/calico/cni:v3.32.1",
+                "registry.example.com/calico/cni:v3.32.1",
+                "registry.example.com/calico/node:v3.32.1",
+                "registry.example.com/calico/node:v3.32.1",
+                "registry.example.com/calico/kube-controllers:v3.32.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_manifests_with_ipv4_pool() {
+        let documents = rendered_documents(cluster(
+            magnum::ClusterLabels::builder()
+                .calico_ipv4pool("10.210.0.0/16".to_string())
+                .build(),
+        ));
+
+        assert_eq!(
+            calico_node_env(&documents, "CALICO_IPV4POOL_CIDR"),
+            vec!["10.210.0.0/16"]
+        );
+    }
+}
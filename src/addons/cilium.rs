@@ -8,6 +8,17 @@ use maplit::btreemap;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+/// Metrics which are exported by Hubble when its metrics are enabled.
+const HUBBLE_METRICS: &[&str] = &[
+    "dns",
+    "drop",
+    "tcp",
+    "flow",
+    "port-distribution",
+    "icmp",
+    "httpV2",
+];
+
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 pub struct CiliumValues {
     image: ImageDetails,
@@ -17,6 +28,24 @@ pub struct CiliumValues {
     ipam: CiliumIPAMValues,
     #[serde(rename = "k8s")]
     kubernetes: CiliumKubernetesValues,
+    #[serde(
+        rename = "kubeProxyReplacement",
+        skip_serializing_if = "Option::is_none"
+    )]
+    kube_proxy_replacement: Option<bool>,
+    #[serde(rename = "k8sServiceHost")]
+    k8s_service_host: String,
+    #[serde(rename = "k8sServicePort")]
+    k8s_service_port: String,
+    encryption: CiliumEncryptionValues,
+    #[serde(rename = "bandwidthManager")]
+    bandwidth_manager: CiliumBandwidthManagerValues,
+    #[serde(rename = "routingMode")]
+    routing_mode: CiliumRoutingMode,
+    #[serde(rename = "ipv4NativeRoutingCIDR")]
+    ipv4_native_routing_cidr: String,
+    #[serde(rename = "autoDirectNodeRoutes")]
+    auto_direct_node_routes: bool,
     envoy: CiliumEnvoyValues,
     #[serde(rename = "sessionAffinity")]
     session_affinity: Option<bool>,
@@ -75,6 +104,42 @@ impl TryFrom<magnum::Cluster> for CiliumValues {
 
     fn try_from(cluster: magnum::Cluster) -> Result<Self, ClusterAddonValuesError> {
         let values = Self::defaults()?;
+        let kube_proxy_replacement = cluster.labels.is_cilium_kube_proxy_replacement_enabled();
+
+        // NOTE: Without kube-proxy, Cilium can not reach the API server through
+        //       its service so it must use the control plane endpoint instead.
+        let (k8s_service_host, k8s_service_port) = match cluster.api_server_endpoint() {
+            Some(endpoint) if kube_proxy_replacement => endpoint,
+            _ => (values.k8s_service_host, values.k8s_service_port),
+        };
+
+        let encryption = match cluster.labels.cilium_encryption.as_deref() {
+            Some("ipsec") => CiliumEncryptionValues {
+                enabled: true,
+                encryption_type: CiliumEncryptionType::Ipsec,
+            },
+            Some("wireguard") => CiliumEncryptionValues {
+                enabled: true,
+                encryption_type: CiliumEncryptionType::Wireguard,
+            },
+            _ => values.encryption,
+        };
+
+        // NOTE: With native routing, the pod traffic is not encapsulated so the
+        //       nodes must be able to route the pod network between each other.
+        let (routing_mode, ipv4_native_routing_cidr, auto_direct_node_routes) =
+            match cluster.labels.cilium_routing_mode.as_str() {
+                "native" => (
+                    CiliumRoutingMode::Native,
+                    cluster.labels.cilium_ipv4pool.clone(),
+                    true,
+                ),
+                _ => (
+                    CiliumRoutingMode::Tunnel,
+                    values.ipv4_native_routing_cidr,
+                    values.auto_direct_node_routes,
+                ),
+            };
 
         Ok(Self {
             image: values
@@ -91,6 +156,27 @@ impl TryFrom<magnum::Cluster> for CiliumValues {
             },
             hubble: CiliumHubbleValues {
                 enabled: values.hubble.enabled,
+                peer_service: CiliumHubblePeerServiceValues {
+                    cluster_domain: cluster.labels.dns_cluster_domain.clone(),
+                },
+                metrics: CiliumHubbleMetricsValues {
+                    enabled: if cluster.labels.is_cilium_hubble_metrics_enabled() {
+                        Some(
+                            HUBBLE_METRICS
+                                .iter()
+                                .map(|metric| metric.to_string())
+                                .collect(),
+                        )
+                    } else {
+                        values.hubble.metrics.enabled
+                    },
+                    service_monitor: CiliumServiceMonitorValues {
+                        enabled: cluster.labels.is_cilium_hubble_metrics_enabled()
+                            && cluster
+                                .labels
+                                .is_cilium_hubble_metrics_service_monitor_enabled(),
+                    },
+                },
                 relay: CiliumHubbleRelayValues {
                     enabled: if cluster.labels.is_cilium_hubble_ui_enabled() {
                         Some(true)
@@ -137,8 +223,26 @@ impl TryFrom<magnum::Cluster> for CiliumValues {
             //                    `Services should serve endpoints on same port and different protocols`.
             //                    https://github.com/kubernetes/kubernetes/pull/120069#issuecomment-2111252221
             kubernetes: CiliumKubernetesValues {
-                service_proxy_name: Some("cilium".into()),
+                service_proxy_name: if kube_proxy_replacement {
+                    None
+                } else {
+                    Some("cilium".into())
+                },
             },
+            kube_proxy_replacement: if kube_proxy_replacement {
+                Some(true)
+            } else {
+                None
+            },
+            k8s_service_host,
+            k8s_service_port,
+            encryption,
+            bandwidth_manager: CiliumBandwidthManagerValues {
+                enabled: cluster.labels.is_cilium_bandwidth_manager_enabled(),
+            },
+            routing_mode,
+            ipv4_native_routing_cidr,
+            auto_direct_node_routes,
             envoy: CiliumEnvoyValues {
                 image: values
                     .envoy
@@ -224,10 +328,64 @@ struct CiliumCertGenValues {
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 struct CiliumHubbleValues {
     enabled: bool,
+    #[serde(rename = "peerService")]
+    peer_service: CiliumHubblePeerServiceValues,
+    metrics: CiliumHubbleMetricsValues,
     relay: CiliumHubbleRelayValues,
     ui: CiliumHubbleUiValues,
 }
 
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct CiliumHubblePeerServiceValues {
+    #[serde(rename = "clusterDomain")]
+    cluster_domain: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct CiliumHubbleMetricsValues {
+    enabled: Option<Vec<String>>,
+    #[serde(rename = "serviceMonitor")]
+    service_monitor: CiliumServiceMonitorValues,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct CiliumServiceMonitorValues {
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct CiliumEncryptionValues {
+    enabled: bool,
+    #[serde(rename = "type")]
+    encryption_type: CiliumEncryptionType,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+enum CiliumEncryptionType {
+    #[serde(rename = "ipsec")]
+    Ipsec,
+
+    #[serde(rename = "wireguard")]
+    Wireguard,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct CiliumBandwidthManagerValues {
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+enum CiliumRoutingMode {
+    #[serde(rename = "")]
+    Default,
+
+    #[serde(rename = "tunnel")]
+    Tunnel,
+
+    #[serde(rename = "native")]
+    Native,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 struct CiliumIPAMValues {
     operator: CiliumIPAMOperatorValues,
@@ -635,6 +793,72 @@ mod tests {
         assert_eq!(values.hubble.ui.enabled, Some(true));
     }
 
+    #[test]
+    fn test_cilium_values_cluster_domain() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .dns_cluster_domain("k8s.example.com".to_owned())
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let values: CiliumValues = cluster.try_into().expect("failed to create values");
+
+        assert_eq!(values.hubble.peer_service.cluster_domain, "k8s.example.com");
+    }
+
+    #[test]
+    fn test_cilium_values_without_kube_proxy_replacement() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder().build(),
+            stack_id: "kube-abcde".to_string().into(),
+            api_address: Some("https://10.0.0.10:6443".to_string()),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let values: CiliumValues = cluster.try_into().expect("failed to create values");
+
+        assert_eq!(values.kube_proxy_replacement, None);
+        assert_eq!(values.k8s_service_host, "");
+        assert_eq!(values.k8s_service_port, "");
+        assert_eq!(
+            values.kubernetes.service_proxy_name,
+            Some("cilium".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cilium_values_with_kube_proxy_replacement() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .cilium_kube_proxy_replacement("true".to_owned())
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            api_address: Some("https://10.0.0.10:6443".to_string()),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let values: CiliumValues = cluster.try_into().expect("failed to create values");
+
+        assert_eq!(values.kube_proxy_replacement, Some(true));
+        assert_eq!(values.k8s_service_host, "10.0.0.10");
+        assert_eq!(values.k8s_service_port, "6443");
+        assert_eq!(values.kubernetes.service_proxy_name, None);
+    }
+
     #[test]
     fn test_get_manifests_with_hubble_ui_enabled() {
         let cluster = magnum::Cluster {
@@ -735,4 +959,224 @@ mod tests {
             }
         }
     }
+
+    fn rendered_documents(cluster: magnum::Cluster) -> Vec<serde_yaml::Value> {
+        let addon = Addon::new(cluster);
+        let manifests = addon.manifests().expect("failed to get manifests");
+        let cilium_yaml = manifests.get("cilium.yaml").expect("cilium.yaml not found");
+
+        serde_yaml::Deserializer::from_str(cilium_yaml)
+            .map(serde_yaml::Value::deserialize)
+            .collect::<Result<_, _>>()
+            .expect("failed to parse rendered documents")
+    }
+
+    fn find_document<'a>(
+        docs: &'a [serde_yaml::Value],
+        kind: &str,
+        name: &str,
+    ) -> Option<&'a serde_yaml::Value> {
+        docs.iter().find(|doc| {
+            doc.get("kind").and_then(|v| v.as_str()) == Some(kind)
+                && doc
+                    .get("metadata")
+                    .and_then(|m| m.get("name"))
+                    .and_then(|n| n.as_str())
+                    == Some(name)
+        })
+    }
+
+    fn cilium_config(docs: &[serde_yaml::Value]) -> &serde_yaml::Value {
+        find_document(docs, "ConfigMap", "cilium-config")
+            .and_then(|doc| doc.get("data"))
+            .expect("cilium-config ConfigMap not found")
+    }
+
+    #[test]
+    fn test_cilium_values_network_options_by_default() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder().build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let values: CiliumValues = cluster.try_into().expect("failed to create values");
+
+        assert!(!values.encryption.enabled);
+        assert_eq!(values.hubble.metrics.enabled, None);
+        assert!(!values.hubble.metrics.service_monitor.enabled);
+        assert!(!values.bandwidth_manager.enabled);
+        assert_eq!(values.routing_mode, CiliumRoutingMode::Tunnel);
+        assert_eq!(values.ipv4_native_routing_cidr, "");
+        assert!(!values.auto_direct_node_routes);
+    }
+
+    #[test]
+    fn test_cilium_values_with_encryption() {
+        for (label, expected) in [
+            ("wireguard", CiliumEncryptionType::Wireguard),
+            ("ipsec", CiliumEncryptionType::Ipsec),
+        ] {
+            let cluster = magnum::Cluster {
+                uuid: "sample-uuid".to_string(),
+                labels: magnum::ClusterLabels::builder()
+                    .cilium_encryption(Some(label.to_owned()))
+                    .build(),
+                stack_id: "kube-abcde".to_string().into(),
+                cluster_template: magnum::ClusterTemplate {
+                    network_driver: "cilium".to_string(),
+                },
+                ..Default::default()
+            };
+
+            let values: CiliumValues = cluster.try_into().expect("failed to create values");
+
+            assert_eq!(
+                values.encryption,
+                CiliumEncryptionValues {
+                    enabled: true,
+                    encryption_type: expected,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_cilium_values_with_native_routing() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .cilium_routing_mode("native".to_owned())
+                .cilium_ipv4pool("10.200.0.0/16".to_owned())
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let values: CiliumValues = cluster.try_into().expect("failed to create values");
+
+        assert_eq!(values.routing_mode, CiliumRoutingMode::Native);
+        assert_eq!(values.ipv4_native_routing_cidr, "10.200.0.0/16");
+        assert!(values.auto_direct_node_routes);
+    }
+
+    #[test]
+    fn test_get_manifests_with_wireguard_encryption() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .cilium_encryption(Some("wireguard".to_owned()))
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let docs = rendered_documents(cluster);
+        let config = cilium_config(&docs);
+
+        assert_eq!(config["enable-wireguard"], "true");
+        assert!(config.get("enable-ipsec").is_none());
+    }
+
+    #[test]
+    fn test_get_manifests_with_ipsec_encryption() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .cilium_encryption(Some("ipsec".to_owned()))
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let docs = rendered_documents(cluster);
+        let config = cilium_config(&docs);
+
+        assert_eq!(config["enable-ipsec"], "true");
+        assert!(config.get("enable-wireguard").is_none());
+    }
+
+    #[test]
+    fn test_get_manifests_with_hubble_metrics() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .cilium_hubble_metrics_enabled("true".to_owned())
+                .cilium_hubble_metrics_service_monitor_enabled("true".to_owned())
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let docs = rendered_documents(cluster);
+        let config = cilium_config(&docs);
+
+        assert_eq!(config["hubble-metrics"], HUBBLE_METRICS.join(" "));
+        assert!(find_document(&docs, "Service", "hubble-metrics").is_some());
+        assert!(find_document(&docs, "ServiceMonitor", "hubble").is_some());
+    }
+
+    #[test]
+    fn test_get_manifests_without_hubble_metrics() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .cilium_hubble_metrics_service_monitor_enabled("true".to_owned())
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let docs = rendered_documents(cluster);
+        let config = cilium_config(&docs);
+
+        assert!(config.get("hubble-metrics").is_none());
+        assert!(find_document(&docs, "ServiceMonitor", "hubble").is_none());
+    }
+
+    #[test]
+    fn test_get_manifests_with_bandwidth_manager_and_native_routing() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .cilium_bandwidth_manager_enabled("true".to_owned())
+                .cilium_routing_mode("native".to_owned())
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let docs = rendered_documents(cluster.clone());
+        let config = cilium_config(&docs);
+
+        assert_eq!(config["enable-bandwidth-manager"], "true");
+        assert_eq!(config["routing-mode"], "native");
+        assert_eq!(config["auto-direct-node-routes"], "true");
+        assert_eq!(
+            config["ipv4-native-routing-cidr"],
+            cluster.labels.cilium_ipv4pool.as_str()
+        );
+    }
 }
@@ -1,16 +1,27 @@
 use crate::{
     addons::{
-        csi::CSIComponent, ClusterAddon, ClusterAddonValues, ClusterAddonValuesError, ImageDetails,
+        csi::{
+            parse_storage_classes, CSIComponent, ReclaimPolicy, StorageClassesError,
+            DEFAULT_STORAGE_CLASS_ANNOTATION,
+        },
+        ClusterAddon, ClusterAddonValues, ClusterAddonValuesError, ImageDetails,
     },
     magnum::{self, ClusterError},
 };
 use docker_image::DockerImage;
 use include_dir::include_dir;
-use k8s_openapi::api::core::v1::{Toleration, Volume, VolumeMount};
+use k8s_openapi::api::{
+    core::v1::{Toleration, Volume, VolumeMount},
+    storage::v1::StorageClass,
+};
+use kube::api::ObjectMeta;
 use maplit::btreemap;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::BTreeMap;
 
+const PROVISIONER: &str = "cinder.csi.openstack.org";
+
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 pub struct CSIValues {
     csi: CSIComponents,
@@ -65,6 +76,90 @@ pub struct CSIStorageClass {
     enabled: bool,
 }
 
+/// A `StorageClass` for a Cinder volume type, which is configured by the
+/// `cinder_csi_storage_classes` label.
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CinderStorageClass {
+    name: String,
+    volume_type: String,
+    #[serde(default)]
+    availability: Option<String>,
+    #[serde(default)]
+    reclaim_policy: ReclaimPolicy,
+    #[serde(default = "default_allow_volume_expansion")]
+    allow_volume_expansion: bool,
+    #[serde(default)]
+    fs_type: Option<String>,
+    #[serde(default)]
+    default: bool,
+    #[serde(default)]
+    snapshot_class: bool,
+}
+
+fn default_allow_volume_expansion() -> bool {
+    true
+}
+
+impl CinderStorageClass {
+    fn metadata(&self, annotation: &str) -> ObjectMeta {
+        ObjectMeta {
+            name: Some(self.name.clone()),
+            annotations: self.default.then(|| {
+                btreemap! {
+                    annotation.to_string() => "true".to_string(),
+                }
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn storage_class(&self) -> StorageClass {
+        let mut parameters = btreemap! {
+            "type".to_string() => self.volume_type.clone(),
+        };
+        if let Some(availability) = &self.availability {
+            parameters.insert("availability".to_string(), availability.clone());
+        }
+        if let Some(fs_type) = &self.fs_type {
+            parameters.insert("csi.storage.k8s.io/fstype".to_string(), fs_type.clone());
+        }
+
+        StorageClass {
+            metadata: self.metadata(DEFAULT_STORAGE_CLASS_ANNOTATION),
+            provisioner: PROVISIONER.to_string(),
+            parameters: Some(parameters),
+            reclaim_policy: Some(self.reclaim_policy.as_str().to_string()),
+            allow_volume_expansion: Some(self.allow_volume_expansion),
+            volume_binding_mode: Some("Immediate".to_string()),
+            ..Default::default()
+        }
+    }
+
+    // NOTE: There are no typed resources for the snapshot CRDs, which must be
+    //       installed in the cluster along with the snapshot controller.
+    fn volume_snapshot_class(&self) -> serde_json::Value {
+        json!({
+            "apiVersion": "snapshot.storage.k8s.io/v1",
+            "kind": "VolumeSnapshotClass",
+            "metadata": self.metadata("snapshot.storage.kubernetes.io/is-default-class"),
+            "driver": PROVISIONER,
+            "deletionPolicy": self.reclaim_policy.as_str(),
+        })
+    }
+}
+
+/// Returns the storage classes of the `cinder_csi_storage_classes` label.
+pub fn storage_classes(
+    labels: &magnum::ClusterLabels,
+) -> Result<Vec<CinderStorageClass>, StorageClassesError> {
+    parse_storage_classes(
+        "cinder_csi_storage_classes",
+        labels.cinder_csi_storage_classes.as_deref(),
+        |storage_class: &CinderStorageClass| (storage_class.name.as_str(), storage_class.default),
+    )
+}
+
 impl ClusterAddonValues for CSIValues {
     fn defaults() -> Result<Self, ClusterAddonValuesError> {
         let file = include_str!(concat!(
@@ -211,14 +306,34 @@ impl ClusterAddon for Addon {
     fn manifests(&self) -> Result<BTreeMap<String, String>, helm::HelmTemplateError> {
         let values = &CSIValues::try_from(self.cluster.clone()).expect("failed to create values");
 
-        Ok(btreemap! {
+        let mut manifests = btreemap! {
             "cinder-csi.yaml".to_owned() => helm::template_using_include_dir(
                 include_dir!("magnum_cluster_api/charts/openstack-cinder-csi"),
                 "cinder-csi",
                 "kube-system",
                 values,
             )?,
-        })
+        };
+
+        for storage_class in
+            storage_classes(&self.cluster.labels).expect("failed to parse storage classes")
+        {
+            manifests.insert(
+                format!("storageclass-{}.yaml", storage_class.name),
+                serde_yaml::to_string(&storage_class.storage_class())
+                    .expect("failed to serialize storage class"),
+            );
+
+            if storage_class.snapshot_class {
+                manifests.insert(
+                    format!("volumesnapshotclass-{}.yaml", storage_class.name),
+                    serde_yaml::to_string(&storage_class.volume_snapshot_class())
+                        .expect("failed to serialize volume snapshot class"),
+                );
+            }
+        }
+
+        Ok(manifests)
     }
 }
 
@@ -226,6 +341,7 @@ impl ClusterAddon for Addon {
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
+    use rstest::rstest;
 
     #[test]
     fn test_cinder_csi_values_for_cluster_without_custom_registry() {
@@ -446,4 +562,162 @@ mod tests {
         let addon = Addon::new(cluster.clone());
         addon.manifests().expect("failed to get manifests");
     }
+
+    #[test]
+    fn test_storage_classes_without_label() {
+        let labels = magnum::ClusterLabels::builder().build();
+
+        let storage_classes = storage_classes(&labels).expect("failed to parse storage classes");
+
+        assert_eq!(storage_classes, vec![]);
+    }
+
+    #[test]
+    fn test_storage_classes() {
+        let labels = magnum::ClusterLabels::builder()
+            .cinder_csi_storage_classes(Some(
+                json!([
+                    {
+                        "name": "ssd",
+                        "volume_type": "SSD",
+                        "availability": "az1",
+                        "reclaim_policy": "Retain",
+                        "allow_volume_expansion": false,
+                        "fs_type": "xfs",
+                        "default": true,
+                        "snapshot_class": true,
+                    },
+                    {
+                        "name": "hdd",
+                        "volume_type": "HDD",
+                    },
+                ])
+                .to_string(),
+            ))
+            .build();
+
+        let storage_classes = storage_classes(&labels).expect("failed to parse storage classes");
+
+        assert_eq!(
+            storage_classes[0].storage_class(),
+            StorageClass {
+                metadata: ObjectMeta {
+                    name: Some("ssd".to_string()),
+                    annotations: Some(btreemap! {
+                        DEFAULT_STORAGE_CLASS_ANNOTATION.to_string() => "true".to_string(),
+                    }),
+                    ..Default::default()
+                },
+                provisioner: "cinder.csi.openstack.org".to_string(),
+                parameters: Some(btreemap! {
+                    "type".to_string() => "SSD".to_string(),
+                    "availability".to_string() => "az1".to_string(),
+                    "csi.storage.k8s.io/fstype".to_string() => "xfs".to_string(),
+                }),
+                reclaim_policy: Some("Retain".to_string()),
+                allow_volume_expansion: Some(false),
+                volume_binding_mode: Some("Immediate".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            storage_classes[0].volume_snapshot_class(),
+            json!({
+                "apiVersion": "snapshot.storage.k8s.io/v1",
+                "kind": "VolumeSnapshotClass",
+                "metadata": {
+                    "name": "ssd",
+                    "annotations": {
+                        "snapshot.storage.kubernetes.io/is-default-class": "true",
+                    },
+                },
+                "driver": "cinder.csi.openstack.org",
+                "deletionPolicy": "Retain",
+            })
+        );
+        assert_eq!(
+            storage_classes[1].storage_class(),
+            StorageClass {
+                metadata: ObjectMeta {
+                    name: Some("hdd".to_string()),
+                    ..Default::default()
+                },
+                provisioner: "cinder.csi.openstack.org".to_string(),
+                parameters: Some(btreemap! {
+                    "type".to_string() => "HDD".to_string(),
+                }),
+                reclaim_policy: Some("Delete".to_string()),
+                allow_volume_expansion: Some(true),
+                volume_binding_mode: Some("Immediate".to_string()),
+                ..Default::default()
+            }
+        );
+        assert!(!storage_classes[1].snapshot_class);
+    }
+
+    #[rstest]
+    #[case("{}", "invalid value for cinder_csi_storage_classes")]
+    #[case(
+        r#"[{"name": "ssd", "volume_type": "SSD", "zone": "az1"}]"#,
+        "invalid value for cinder_csi_storage_classes"
+    )]
+    #[case(
+        r#"[{"name": "SSD", "volume_type": "SSD"}]"#,
+        "invalid storage class name in cinder_csi_storage_classes: SSD"
+    )]
+    #[case(
+        r#"[{"name": "ssd", "volume_type": "SSD"}, {"name": "ssd", "volume_type": "NVMe"}]"#,
+        "duplicate storage class name in cinder_csi_storage_classes: ssd"
+    )]
+    #[case(
+        r#"[{"name": "ssd", "volume_type": "SSD", "default": true}, {"name": "hdd", "volume_type": "HDD", "default": true}]"#,
+        "more than one default storage class in cinder_csi_storage_classes"
+    )]
+    fn test_storage_classes_invalid(#[case] value: &str, #[case] expected: &str) {
+        let labels = magnum::ClusterLabels::builder()
+            .cinder_csi_storage_classes(Some(value.to_string()))
+            .build();
+
+        let err = storage_classes(&labels).expect_err("expected an error");
+
+        assert!(
+            err.to_string().starts_with(expected),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_get_manifests_with_storage_classes() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .cinder_csi_storage_classes(Some(
+                    json!([
+                        {"name": "ssd", "volume_type": "SSD", "snapshot_class": true},
+                        {"name": "hdd", "volume_type": "HDD"},
+                    ])
+                    .to_string(),
+                ))
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let addon = Addon::new(cluster.clone());
+        let manifests = addon.manifests().expect("failed to get manifests");
+
+        assert_eq!(
+            manifests.keys().collect::<Vec<_>>(),
+            vec![
+                "cinder-csi.yaml",
+                "storageclass-hdd.yaml",
+                "storageclass-ssd.yaml",
+                "volumesnapshotclass-ssd.yaml",
+            ]
+        );
+    }
 }
@@ -1,7 +1,89 @@
-use crate::addons::ImageDetails;
+use crate::{addons::ImageDetails, validation::is_valid_domain};
+use pyo3::{exceptions::PyRuntimeError, PyErr};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+/// Annotation which marks the default `StorageClass` of the cluster.
+pub const DEFAULT_STORAGE_CLASS_ANNOTATION: &str = "storageclass.kubernetes.io/is-default-class";
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 pub struct CSIComponent {
     pub image: ImageDetails,
 }
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum ReclaimPolicy {
+    #[default]
+    Delete,
+    Retain,
+}
+
+impl ReclaimPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReclaimPolicy::Delete => "Delete",
+            ReclaimPolicy::Retain => "Retain",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StorageClassesError {
+    #[error("invalid value for {0}: {1}")]
+    Json(&'static str, serde_json::Error),
+
+    #[error("invalid storage class name in {0}: {1}")]
+    InvalidName(&'static str, String),
+
+    #[error("duplicate storage class name in {0}: {1}")]
+    DuplicateName(&'static str, String),
+
+    #[error("more than one default storage class in {0}")]
+    MultipleDefaults(&'static str),
+}
+
+impl From<StorageClassesError> for PyErr {
+    fn from(err: StorageClassesError) -> PyErr {
+        PyErr::new::<PyRuntimeError, _>(err.to_string())
+    }
+}
+
+/// Parses the storage classes of a structured label, which must have unique
+/// and valid names with at most one of them being the default one.
+pub fn parse_storage_classes<T, F>(
+    label: &'static str,
+    value: Option<&str>,
+    name_and_default: F,
+) -> Result<Vec<T>, StorageClassesError>
+where
+    T: for<'de> Deserialize<'de>,
+    F: Fn(&T) -> (&str, bool),
+{
+    let value = match value.map(str::trim) {
+        Some(value) if !value.is_empty() => value,
+        _ => return Ok(vec![]),
+    };
+
+    let storage_classes: Vec<T> =
+        serde_json::from_str(value).map_err(|e| StorageClassesError::Json(label, e))?;
+
+    let mut names = BTreeSet::new();
+    let mut has_default = false;
+
+    for storage_class in &storage_classes {
+        let (name, default) = name_and_default(storage_class);
+
+        if !is_valid_domain(name) {
+            return Err(StorageClassesError::InvalidName(label, name.to_string()));
+        }
+        if !names.insert(name) {
+            return Err(StorageClassesError::DuplicateName(label, name.to_string()));
+        }
+        if default && std::mem::replace(&mut has_default, true) {
+            return Err(StorageClassesError::MultipleDefaults(label));
+        }
+    }
+
+    Ok(storage_classes)
+}
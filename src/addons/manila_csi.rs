@@ -4,18 +4,167 @@ use crate::{
 };
 use docker_image::DockerImage;
 use include_dir::include_dir;
-use k8s_openapi::api::core::v1::Toleration;
+use k8s_openapi::api::{core::v1::Toleration, storage::v1::StorageClass};
+use kube::api::ObjectMeta;
 use maplit::btreemap;
+use pyo3::{exceptions::PyRuntimeError, PyErr};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Name of the secret with the credentials of the Manila CSI driver, which is
+/// created in the `kube-system` namespace.
+const SECRET_NAME: &str = "csi-manila-secrets";
+
+#[derive(Debug, Error)]
+pub enum ShareProtocolError {
+    #[error("the CEPHFS share protocol is not enabled by the operator")]
+    CephFsNotEnabled,
+}
+
+impl From<ShareProtocolError> for PyErr {
+    fn from(err: ShareProtocolError) -> PyErr {
+        PyErr::new::<PyRuntimeError, _>(err.to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ShareProtocol {
+    #[serde(rename = "NFS")]
+    Nfs,
+
+    #[serde(rename = "CEPHFS")]
+    CephFs,
+}
+
+impl ShareProtocol {
+    /// Returns the share protocols of the `manila_csi_share_protocols` label,
+    /// in their order and without duplicates.
+    pub fn from_labels(labels: &magnum::ClusterLabels) -> Vec<ShareProtocol> {
+        let mut protocols = Vec::new();
+
+        for protocol in labels.manila_csi_share_protocols.split(',') {
+            let protocol = match protocol.trim().to_ascii_uppercase().as_str() {
+                "NFS" => ShareProtocol::Nfs,
+                "CEPHFS" => ShareProtocol::CephFs,
+                _ => continue,
+            };
+
+            if !protocols.contains(&protocol) {
+                protocols.push(protocol);
+            }
+        }
+
+        protocols
+    }
+
+    /// Returns the share protocols of the `manila_csi_share_protocols` label,
+    /// refusing CephFS unless the operator has confirmed that the CephFS node
+    /// plugin of ceph-csi is installed, since it is not deployed with the
+    /// cluster.
+    pub fn from_labels_checked(
+        labels: &magnum::ClusterLabels,
+        cephfs_enabled: bool,
+    ) -> Result<Vec<ShareProtocol>, ShareProtocolError> {
+        let protocols = Self::from_labels(labels);
+        if !cephfs_enabled && protocols.contains(&ShareProtocol::CephFs) {
+            return Err(ShareProtocolError::CephFsNotEnabled);
+        }
+
+        Ok(protocols)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ShareProtocol::Nfs => "nfs",
+            ShareProtocol::CephFs => "cephfs",
+        }
+    }
+
+    fn provisioner(&self) -> String {
+        format!("{}.manila.csi.openstack.org", self.name())
+    }
+
+    /// Returns the endpoint of the node plugin which the Manila CSI driver
+    /// forwards the node requests of the protocol to.
+    fn node_plugin_endpoint(&self) -> CSIShareProtocolEndpoint {
+        CSIShareProtocolEndpoint {
+            dir: match self {
+                ShareProtocol::Nfs => "/var/lib/kubelet/plugins/csi-nfsplugin",
+                ShareProtocol::CephFs => "/var/lib/kubelet/plugins/cephfs.csi.ceph.com",
+            }
+            .to_string(),
+            sock_file: "csi.sock".to_string(),
+        }
+    }
+
+    fn storage_class(&self, share_type: &str, share_network_id: Option<&str>) -> StorageClass {
+        let mut parameters = btreemap! {
+            "type".to_string() => share_type.to_string(),
+        };
+        if let Some(share_network_id) = share_network_id {
+            parameters.insert("shareNetworkID".to_string(), share_network_id.to_string());
+        }
+        for secret in [
+            "provisioner",
+            "controller-expand",
+            "node-stage",
+            "node-publish",
+        ] {
+            parameters.insert(
+                format!("csi.storage.k8s.io/{}-secret-name", secret),
+                SECRET_NAME.to_string(),
+            );
+            parameters.insert(
+                format!("csi.storage.k8s.io/{}-secret-namespace", secret),
+                "kube-system".to_string(),
+            );
+        }
+
+        StorageClass {
+            metadata: ObjectMeta {
+                name: Some(format!("share-{}", self.name())),
+                ..Default::default()
+            },
+            provisioner: self.provisioner(),
+            parameters: Some(parameters),
+            reclaim_policy: Some("Delete".to_string()),
+            allow_volume_expansion: Some(true),
+            volume_binding_mode: Some("Immediate".to_string()),
+            ..Default::default()
+        }
+    }
+}
 
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 pub struct CSIValues {
+    #[serde(rename = "shareProtocols")]
+    share_protocols: Vec<CSIShareProtocol>,
     csimanila: CSIComponent,
     nodeplugin: CSINodePlugin,
     controllerplugin: CSIControllerPlugin,
 }
 
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct CSIShareProtocol {
+    #[serde(rename = "protocolSelector")]
+    protocol_selector: ShareProtocol,
+
+    #[serde(rename = "fsGroupPolicy", skip_serializing_if = "Option::is_none")]
+    fs_group_policy: Option<String>,
+
+    #[serde(rename = "fwdNodePluginEndpoint")]
+    fwd_node_plugin_endpoint: CSIShareProtocolEndpoint,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct CSIShareProtocolEndpoint {
+    dir: String,
+
+    #[serde(rename = "sockFile")]
+    sock_file: String,
+}
+
 #[derive(Debug, Deserialize, PartialEq, Serialize)]
 pub struct CSINodePlugin {
     registrar: CSIComponent,
@@ -60,6 +209,17 @@ impl TryFrom<magnum::Cluster> for CSIValues {
         let values = Self::defaults()?;
 
         Ok(Self {
+            share_protocols: ShareProtocol::from_labels(&cluster.labels)
+                .into_iter()
+                .map(|protocol| CSIShareProtocol {
+                    protocol_selector: protocol,
+                    fs_group_policy: match protocol {
+                        ShareProtocol::Nfs => Some("None".to_string()),
+                        ShareProtocol::CephFs => None,
+                    },
+                    fwd_node_plugin_endpoint: protocol.node_plugin_endpoint(),
+                })
+                .collect(),
             csimanila: CSIComponent {
                 image: values
                     .csimanila
@@ -139,14 +299,29 @@ impl ClusterAddon for Addon {
     fn manifests(&self) -> Result<BTreeMap<String, String>, helm::HelmTemplateError> {
         let values = &CSIValues::try_from(self.cluster.clone()).expect("failed to create values");
 
-        Ok(btreemap! {
+        let mut manifests = btreemap! {
             "manila-csi.yaml".to_owned() => helm::template_using_include_dir(
                 include_dir!("magnum_cluster_api/charts/openstack-manila-csi"),
                 "manila-csi",
                 "kube-system",
                 values,
             )?,
-        })
+        };
+
+        if let Some(share_type) = &self.cluster.labels.manila_csi_share_type {
+            for protocol in ShareProtocol::from_labels(&self.cluster.labels) {
+                manifests.insert(
+                    format!("storageclass-share-{}.yaml", protocol.name()),
+                    serde_yaml::to_string(&protocol.storage_class(
+                        share_type,
+                        self.cluster.labels.manila_csi_share_network_id.as_deref(),
+                    ))
+                    .expect("failed to serialize storage class"),
+                );
+            }
+        }
+
+        Ok(manifests)
     }
 }
 
@@ -155,6 +330,7 @@ mod tests {
     use super::*;
     use crate::addons::ImageDetails;
     use pretty_assertions::assert_eq;
+    use rstest::rstest;
 
     #[test]
     fn test_manila_csi_values_for_cluster_without_custom_registry() {
@@ -323,4 +499,135 @@ mod tests {
         let addon = Addon::new(cluster.clone());
         addon.manifests().expect("failed to get manifests");
     }
+
+    #[rstest]
+    #[case("NFS", vec![ShareProtocol::Nfs])]
+    #[case("NFS,", vec![ShareProtocol::Nfs])]
+    #[case("cephfs", vec![ShareProtocol::CephFs])]
+    #[case("CEPHFS, nfs,CEPHFS", vec![ShareProtocol::CephFs, ShareProtocol::Nfs])]
+    fn test_share_protocols_from_labels(#[case] value: &str, #[case] expected: Vec<ShareProtocol>) {
+        let labels = magnum::ClusterLabels::builder()
+            .manila_csi_share_protocols(value.to_string())
+            .build();
+
+        assert_eq!(ShareProtocol::from_labels(&labels), expected);
+    }
+
+    #[test]
+    fn test_share_protocols_from_labels_checked() {
+        let labels = magnum::ClusterLabels::builder()
+            .manila_csi_share_protocols("NFS,CEPHFS".to_string())
+            .build();
+
+        assert!(matches!(
+            ShareProtocol::from_labels_checked(&labels, false),
+            Err(ShareProtocolError::CephFsNotEnabled)
+        ));
+        assert_eq!(
+            ShareProtocol::from_labels_checked(&labels, true).expect("cephfs should be enabled"),
+            vec![ShareProtocol::Nfs, ShareProtocol::CephFs]
+        );
+    }
+
+    #[test]
+    fn test_manila_csi_values_share_protocols() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .manila_csi_share_protocols("NFS,CEPHFS".to_string())
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let values: CSIValues = cluster.try_into().expect("failed to create values");
+
+        assert_eq!(
+            values.share_protocols,
+            vec![
+                CSIShareProtocol {
+                    protocol_selector: ShareProtocol::Nfs,
+                    fs_group_policy: Some("None".to_string()),
+                    fwd_node_plugin_endpoint: CSIShareProtocolEndpoint {
+                        dir: "/var/lib/kubelet/plugins/csi-nfsplugin".to_string(),
+                        sock_file: "csi.sock".to_string(),
+                    },
+                },
+                CSIShareProtocol {
+                    protocol_selector: ShareProtocol::CephFs,
+                    fs_group_policy: None,
+                    fwd_node_plugin_endpoint: CSIShareProtocolEndpoint {
+                        dir: "/var/lib/kubelet/plugins/cephfs.csi.ceph.com".to_string(),
+                        sock_file: "csi.sock".to_string(),
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_share_protocol_storage_class() {
+        let storage_class = ShareProtocol::CephFs.storage_class("cephfstype", Some("network-id"));
+
+        assert_eq!(
+            storage_class.metadata.name,
+            Some("share-cephfs".to_string())
+        );
+        assert_eq!(storage_class.provisioner, "cephfs.manila.csi.openstack.org");
+
+        let parameters = storage_class.parameters.expect("parameters should be set");
+        assert_eq!(parameters["type"], "cephfstype");
+        assert_eq!(parameters["shareNetworkID"], "network-id");
+        assert_eq!(
+            parameters["csi.storage.k8s.io/node-stage-secret-name"],
+            "csi-manila-secrets"
+        );
+        assert_eq!(
+            parameters["csi.storage.k8s.io/node-stage-secret-namespace"],
+            "kube-system"
+        );
+    }
+
+    #[test]
+    fn test_share_protocol_storage_class_without_share_network() {
+        let storage_class = ShareProtocol::Nfs.storage_class("default", None);
+
+        assert_eq!(storage_class.metadata.name, Some("share-nfs".to_string()));
+        assert_eq!(storage_class.provisioner, "nfs.manila.csi.openstack.org");
+        assert!(!storage_class
+            .parameters
+            .expect("parameters should be set")
+            .contains_key("shareNetworkID"));
+    }
+
+    #[test]
+    fn test_get_manifests_with_share_type() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder()
+                .manila_csi_share_protocols("NFS,CEPHFS".to_string())
+                .manila_csi_share_type(Some("default".to_string()))
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let addon = Addon::new(cluster.clone());
+        let manifests = addon.manifests().expect("failed to get manifests");
+
+        assert_eq!(
+            manifests.keys().collect::<Vec<_>>(),
+            vec![
+                "manila-csi.yaml",
+                "storageclass-share-cephfs.yaml",
+                "storageclass-share-nfs.yaml",
+            ]
+        );
+    }
 }
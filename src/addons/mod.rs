@@ -4,11 +4,13 @@ use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use thiserror::Error;
 
+pub mod calico;
 pub mod cilium;
 pub mod cinder_csi;
 pub mod cloud_controller_manager;
 pub mod csi;
 pub mod manila_csi;
+pub mod runtime_classes;
 
 #[cfg_attr(test, mockall::automock)]
 pub trait ClusterAddon {
@@ -0,0 +1,118 @@
+use crate::{
+    addons::ClusterAddon,
+    features::container_runtimes::ContainerRuntime,
+    magnum::{self, ClusterError},
+};
+use k8s_openapi::{
+    api::node::v1::{Overhead, RuntimeClass, Scheduling},
+    apimachinery::pkg::api::resource::Quantity,
+};
+use kube::api::ObjectMeta;
+use maplit::btreemap;
+use std::collections::BTreeMap;
+
+/// Returns the `RuntimeClass` for the given runtime, which only schedules the
+/// workloads on the nodes where the runtime handler is configured.
+fn runtime_class(runtime: ContainerRuntime) -> RuntimeClass {
+    RuntimeClass {
+        metadata: ObjectMeta {
+            name: Some(runtime.runtime_class_name().to_string()),
+            ..Default::default()
+        },
+        handler: runtime.handler().to_string(),
+        overhead: match runtime {
+            // NOTE: Every pod of Kata Containers runs in its own virtual machine,
+            //       this is the overhead which is recommended upstream.
+            ContainerRuntime::Kata => Some(Overhead {
+                pod_fixed: Some(btreemap! {
+                    "cpu".to_string() => Quantity("250m".to_string()),
+                    "memory".to_string() => Quantity("160Mi".to_string()),
+                }),
+            }),
+            ContainerRuntime::Runsc => None,
+        },
+        scheduling: Some(Scheduling {
+            node_selector: Some(btreemap! {
+                runtime.node_label() => "true".to_string(),
+            }),
+            ..Default::default()
+        }),
+    }
+}
+
+pub struct Addon {
+    cluster: magnum::Cluster,
+}
+
+impl ClusterAddon for Addon {
+    fn new(cluster: magnum::Cluster) -> Self {
+        Self { cluster }
+    }
+
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn secret_name(&self) -> Result<String, ClusterError> {
+        Ok(format!("{}-runtime-classes", self.cluster.stack_id()?))
+    }
+
+    // NOTE: The classes are created for all of the runtimes, since the runtimes
+    //       are picked by the node groups which can be added after the cluster
+    //       is created.  The pods using a class stay pending until a node group
+    //       with its runtime exists.
+    fn manifests(&self) -> Result<BTreeMap<String, String>, helm::HelmTemplateError> {
+        Ok(ContainerRuntime::ALL
+            .into_iter()
+            .map(|runtime| {
+                (
+                    format!("runtimeclass-{}.yaml", runtime.runtime_class_name()),
+                    serde_yaml::to_string(&runtime_class(runtime))
+                        .expect("failed to serialize runtime class"),
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_get_manifests() {
+        let cluster = magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: magnum::ClusterLabels::builder().build(),
+            stack_id: "kube-abcde".to_string().into(),
+            ..Default::default()
+        };
+
+        let addon = Addon::new(cluster);
+        let manifests = addon.manifests().expect("failed to get manifests");
+
+        assert_eq!(
+            manifests.keys().collect::<Vec<_>>(),
+            vec!["runtimeclass-gvisor.yaml", "runtimeclass-kata.yaml"]
+        );
+
+        let gvisor: RuntimeClass = serde_yaml::from_str(&manifests["runtimeclass-gvisor.yaml"])
+            .expect("failed to parse runtime class");
+        assert_eq!(gvisor.handler, "runsc");
+        assert_eq!(gvisor.overhead, None);
+        assert_eq!(
+            gvisor
+                .scheduling
+                .and_then(|scheduling| scheduling.node_selector),
+            Some(btreemap! {
+                "runtime.node.cluster.x-k8s.io/runsc".to_string() => "true".to_string(),
+            })
+        );
+
+        let kata: RuntimeClass = serde_yaml::from_str(&manifests["runtimeclass-kata.yaml"])
+            .expect("failed to parse runtime class");
+        assert_eq!(kata.handler, "kata");
+        assert!(kata.overhead.is_some());
+    }
+}
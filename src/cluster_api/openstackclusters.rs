@@ -1102,6 +1102,12 @@ pub struct OpenStackClusterManagedSecurityGroups {
     /// AllowAllInClusterTraffic allows all ingress and egress traffic between cluster nodes when set to true.
     #[serde(rename = "allowAllInClusterTraffic")]
     pub allow_all_in_cluster_traffic: bool,
+    /// controlPlaneNodesSecurityGroupRules defines the rules that should be applied to control plane nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "controlPlaneNodesSecurityGroupRules")]
+    pub control_plane_nodes_security_group_rules: Option<Vec<OpenStackClusterManagedSecurityGroupsControlPlaneNodesSecurityGroupRules>>,
+    /// workerNodesSecurityGroupRules defines the rules that should be applied to worker nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "workerNodesSecurityGroupRules")]
+    pub worker_nodes_security_group_rules: Option<Vec<OpenStackClusterManagedSecurityGroupsWorkerNodesSecurityGroupRules>>,
 }
 
 /// SecurityGroupRuleSpec represent the basic information of the associated OpenStack
@@ -1152,6 +1158,102 @@ pub struct OpenStackClusterManagedSecurityGroupsAllNodesSecurityGroupRules {
     pub remote_managed_groups: Option<Vec<String>>,
 }
 
+/// SecurityGroupRuleSpec represent the basic information of the associated OpenStack
+/// Security Group Role.
+/// For now this is only used for the allNodesSecurityGroupRules but when we add
+/// other security groups, we'll need to add a validation because
+/// Remote* fields are mutually exclusive.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct OpenStackClusterManagedSecurityGroupsControlPlaneNodesSecurityGroupRules {
+    /// description of the security group rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// direction in which the security group rule is applied. The only values
+    /// allowed are "ingress" or "egress". For a compute instance, an ingress
+    /// security group rule is applied to incoming (ingress) traffic for that
+    /// instance. An egress rule is applied to traffic leaving the instance.
+    pub direction: String,
+    /// etherType must be IPv4 or IPv6, and addresses represented in CIDR must match the
+    /// ingress or egress rules.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "etherType")]
+    pub ether_type: Option<String>,
+    /// name of the security group rule.
+    /// It's used to identify the rule so it can be patched and will not be sent to the OpenStack API.
+    pub name: String,
+    /// portRangeMax is a number in the range that is matched by the security group
+    /// rule. The portRangeMin attribute constrains the portRangeMax attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "portRangeMax")]
+    pub port_range_max: Option<i64>,
+    /// portRangeMin is a number in the range that is matched by the security group
+    /// rule. If the protocol is TCP or UDP, this value must be less than or equal
+    /// to the value of the portRangeMax attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "portRangeMin")]
+    pub port_range_min: Option<i64>,
+    /// protocol is the protocol that is matched by the security group rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    /// remoteGroupID is the remote group ID to be associated with this security group rule.
+    /// You can specify either remoteGroupID or remoteIPPrefix or remoteManagedGroups.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "remoteGroupID")]
+    pub remote_group_id: Option<String>,
+    /// remoteIPPrefix is the remote IP prefix to be associated with this security group rule.
+    /// You can specify either remoteGroupID or remoteIPPrefix or remoteManagedGroups.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "remoteIPPrefix")]
+    pub remote_ip_prefix: Option<String>,
+    /// remoteManagedGroups is the remote managed groups to be associated with this security group rule.
+    /// You can specify either remoteGroupID or remoteIPPrefix or remoteManagedGroups.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "remoteManagedGroups")]
+    pub remote_managed_groups: Option<Vec<String>>,
+}
+
+/// SecurityGroupRuleSpec represent the basic information of the associated OpenStack
+/// Security Group Role.
+/// For now this is only used for the allNodesSecurityGroupRules but when we add
+/// other security groups, we'll need to add a validation because
+/// Remote* fields are mutually exclusive.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct OpenStackClusterManagedSecurityGroupsWorkerNodesSecurityGroupRules {
+    /// description of the security group rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// direction in which the security group rule is applied. The only values
+    /// allowed are "ingress" or "egress". For a compute instance, an ingress
+    /// security group rule is applied to incoming (ingress) traffic for that
+    /// instance. An egress rule is applied to traffic leaving the instance.
+    pub direction: String,
+    /// etherType must be IPv4 or IPv6, and addresses represented in CIDR must match the
+    /// ingress or egress rules.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "etherType")]
+    pub ether_type: Option<String>,
+    /// name of the security group rule.
+    /// It's used to identify the rule so it can be patched and will not be sent to the OpenStack API.
+    pub name: String,
+    /// portRangeMax is a number in the range that is matched by the security group
+    /// rule. The portRangeMin attribute constrains the portRangeMax attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "portRangeMax")]
+    pub port_range_max: Option<i64>,
+    /// portRangeMin is a number in the range that is matched by the security group
+    /// rule. If the protocol is TCP or UDP, this value must be less than or equal
+    /// to the value of the portRangeMax attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "portRangeMin")]
+    pub port_range_min: Option<i64>,
+    /// protocol is the protocol that is matched by the security group rule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    /// remoteGroupID is the remote group ID to be associated with this security group rule.
+    /// You can specify either remoteGroupID or remoteIPPrefix or remoteManagedGroups.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "remoteGroupID")]
+    pub remote_group_id: Option<String>,
+    /// remoteIPPrefix is the remote IP prefix to be associated with this security group rule.
+    /// You can specify either remoteGroupID or remoteIPPrefix or remoteManagedGroups.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "remoteIPPrefix")]
+    pub remote_ip_prefix: Option<String>,
+    /// remoteManagedGroups is the remote managed groups to be associated with this security group rule.
+    /// You can specify either remoteGroupID or remoteIPPrefix or remoteManagedGroups.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "remoteManagedGroups")]
+    pub remote_managed_groups: Option<Vec<String>>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
 pub struct OpenStackClusterManagedSubnets {
     /// AllocationPools is an array of AllocationPool objects that will be applied to OpenStack Subnet being created.
@@ -0,0 +1,307 @@
+use crate::{magnum, validation::is_valid_domain};
+use ipnet::{IpNet, Ipv4Net};
+use pyo3::{exceptions::PyRuntimeError, PyErr};
+use std::net::Ipv4Addr;
+use thiserror::Error;
+
+/// Offset of the DNS service in the service range, which is the address that
+/// kubeadm picks for the `kube-dns` service and the `clusterDNS` of kubelet.
+const DNS_SERVICE_IP_OFFSET: u32 = 10;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum Error {
+    #[error("invalid CIDR for {0}: {1}")]
+    InvalidCidr(&'static str, String),
+
+    #[error("{0} must be an IPv4 CIDR: {1}")]
+    UnsupportedFamily(&'static str, String),
+
+    #[error("{0} ({1}) overlaps with {2} ({3})")]
+    Overlap(&'static str, Ipv4Net, &'static str, Ipv4Net),
+
+    #[error("service_cluster_ip_range ({0}) is too small for the DNS service IP")]
+    MissingDnsServiceIp(Ipv4Net),
+
+    #[error("invalid DNS domain for dns_cluster_domain: {0}")]
+    InvalidDomain(String),
+}
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> PyErr {
+        PyErr::new::<PyRuntimeError, _>(err.to_string())
+    }
+}
+
+/// Networks of a cluster, which are picked from its labels and are validated
+/// against each other before any of the resources are created.
+#[derive(Debug, PartialEq)]
+pub struct ClusterNetwork {
+    /// The subnet created for the nodes, which is unknown when the nodes use an
+    /// existing network or subnet.
+    pub node_cidr: Option<Ipv4Net>,
+    pub pod_cidr: Ipv4Net,
+    pub service_cidr: Ipv4Net,
+    pub dns_service_ip: Ipv4Addr,
+    pub domain: String,
+}
+
+fn parse_cidr(label: &'static str, value: &str) -> Result<Ipv4Net, Error> {
+    match value.trim().parse::<IpNet>() {
+        Ok(IpNet::V4(cidr)) => Ok(cidr.trunc()),
+        Ok(IpNet::V6(_)) => Err(Error::UnsupportedFamily(label, value.to_string())),
+        Err(_) => Err(Error::InvalidCidr(label, value.to_string())),
+    }
+}
+
+impl TryFrom<&magnum::Cluster> for ClusterNetwork {
+    type Error = Error;
+
+    fn try_from(cluster: &magnum::Cluster) -> Result<Self, Error> {
+        let labels = &cluster.labels;
+
+        let pod_label = match cluster.cluster_template.network_driver.as_str() {
+            "calico" => ("calico_ipv4pool", &labels.calico_ipv4pool),
+            _ => ("cilium_ipv4pool", &labels.cilium_ipv4pool),
+        };
+
+        // NOTE: The `fixed_subnet_cidr` label is only used to create the subnet
+        //       of the nodes, so it is ignored for an existing network or subnet.
+        let has_fixed_network = [&cluster.fixed_network, &cluster.fixed_subnet]
+            .iter()
+            .any(|value| value.as_deref().is_some_and(|value| !value.is_empty()));
+        let node_cidr = if has_fixed_network {
+            None
+        } else {
+            Some(parse_cidr("fixed_subnet_cidr", &labels.fixed_subnet_cidr)?)
+        };
+        let pod_cidr = parse_cidr(pod_label.0, pod_label.1)?;
+        let service_cidr =
+            parse_cidr("service_cluster_ip_range", &labels.service_cluster_ip_range)?;
+
+        let networks: Vec<_> = node_cidr
+            .map(|cidr| ("fixed_subnet_cidr", cidr))
+            .into_iter()
+            .chain([
+                (pod_label.0, pod_cidr),
+                ("service_cluster_ip_range", service_cidr),
+            ])
+            .collect();
+        for (i, (label, cidr)) in networks.iter().enumerate() {
+            for (other_label, other_cidr) in &networks[i + 1..] {
+                if cidr.contains(other_cidr) || other_cidr.contains(cidr) {
+                    return Err(Error::Overlap(label, *cidr, other_label, *other_cidr));
+                }
+            }
+        }
+
+        let dns_service_ip = u32::from(service_cidr.network())
+            .checked_add(DNS_SERVICE_IP_OFFSET)
+            .map(Ipv4Addr::from)
+            .filter(|ip| service_cidr.contains(ip) && *ip != service_cidr.broadcast())
+            .ok_or(Error::MissingDnsServiceIp(service_cidr))?;
+
+        if !is_valid_domain(&labels.dns_cluster_domain) {
+            return Err(Error::InvalidDomain(labels.dns_cluster_domain.clone()));
+        }
+
+        Ok(ClusterNetwork {
+            node_cidr,
+            pod_cidr,
+            service_cidr,
+            dns_service_ip,
+            domain: labels.dns_cluster_domain.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn cluster(network_driver: &str, labels: magnum::ClusterLabels) -> magnum::Cluster {
+        magnum::Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels,
+            cluster_template: magnum::ClusterTemplate {
+                network_driver: network_driver.to_string(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let network =
+            ClusterNetwork::try_from(&cluster("cilium", magnum::ClusterLabels::builder().build()))
+                .expect("network should be valid");
+
+        assert_eq!(
+            network,
+            ClusterNetwork {
+                node_cidr: Some("10.0.0.0/24".parse().unwrap()),
+                pod_cidr: "10.100.0.0/16".parse().unwrap(),
+                service_cidr: "10.254.0.0/16".parse().unwrap(),
+                dns_service_ip: "10.254.0.10".parse().unwrap(),
+                domain: "cluster.local".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_pod_cidr_for_network_driver() {
+        let labels = || {
+            magnum::ClusterLabels::builder()
+                .cilium_ipv4pool("10.200.0.0/16".into())
+                .calico_ipv4pool("10.210.0.0/16".into())
+                .build()
+        };
+
+        assert_eq!(
+            ClusterNetwork::try_from(&cluster("cilium", labels()))
+                .expect("network should be valid")
+                .pod_cidr,
+            "10.200.0.0/16".parse::<Ipv4Net>().unwrap()
+        );
+        assert_eq!(
+            ClusterNetwork::try_from(&cluster("calico", labels()))
+                .expect("network should be valid")
+                .pod_cidr,
+            "10.210.0.0/16".parse::<Ipv4Net>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_cidr() {
+        let labels = magnum::ClusterLabels::builder()
+            .service_cluster_ip_range("10.254.0.0/33".into())
+            .build();
+
+        assert_eq!(
+            ClusterNetwork::try_from(&cluster("cilium", labels)),
+            Err(Error::InvalidCidr(
+                "service_cluster_ip_range",
+                "10.254.0.0/33".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unsupported_family() {
+        let labels = magnum::ClusterLabels::builder()
+            .cilium_ipv4pool("fd00::/104".into())
+            .build();
+
+        assert_eq!(
+            ClusterNetwork::try_from(&cluster("cilium", labels)),
+            Err(Error::UnsupportedFamily(
+                "cilium_ipv4pool",
+                "fd00::/104".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_overlap() {
+        let labels = magnum::ClusterLabels::builder()
+            .cilium_ipv4pool("10.0.0.0/8".into())
+            .build();
+
+        assert_eq!(
+            ClusterNetwork::try_from(&cluster("cilium", labels)),
+            Err(Error::Overlap(
+                "fixed_subnet_cidr",
+                "10.0.0.0/24".parse().unwrap(),
+                "cilium_ipv4pool",
+                "10.0.0.0/8".parse().unwrap(),
+            ))
+        );
+
+        let labels = magnum::ClusterLabels::builder()
+            .service_cluster_ip_range("10.100.128.0/20".into())
+            .build();
+
+        assert_eq!(
+            ClusterNetwork::try_from(&cluster("cilium", labels)),
+            Err(Error::Overlap(
+                "cilium_ipv4pool",
+                "10.100.0.0/16".parse().unwrap(),
+                "service_cluster_ip_range",
+                "10.100.128.0/20".parse().unwrap(),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_fixed_network() {
+        let labels = || {
+            magnum::ClusterLabels::builder()
+                .fixed_subnet_cidr("10.100.0.0/24".into())
+                .build()
+        };
+
+        assert_eq!(
+            ClusterNetwork::try_from(&cluster("cilium", labels())),
+            Err(Error::Overlap(
+                "fixed_subnet_cidr",
+                "10.100.0.0/24".parse().unwrap(),
+                "cilium_ipv4pool",
+                "10.100.0.0/16".parse().unwrap(),
+            ))
+        );
+
+        for (fixed_network, fixed_subnet) in
+            [(Some("private"), None), (None, Some("private-subnet"))]
+        {
+            let cluster = magnum::Cluster {
+                fixed_network: fixed_network.map(String::from),
+                fixed_subnet: fixed_subnet.map(String::from),
+                ..cluster("cilium", labels())
+            };
+
+            assert_eq!(
+                ClusterNetwork::try_from(&cluster)
+                    .expect("network should be valid")
+                    .node_cidr,
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_missing_dns_service_ip() {
+        let labels = magnum::ClusterLabels::builder()
+            .service_cluster_ip_range("10.254.0.0/29".into())
+            .build();
+
+        assert_eq!(
+            ClusterNetwork::try_from(&cluster("cilium", labels)),
+            Err(Error::MissingDnsServiceIp("10.254.0.0/29".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_domain() {
+        let labels = magnum::ClusterLabels::builder()
+            .dns_cluster_domain("k8s.example.com".into())
+            .build();
+
+        assert_eq!(
+            ClusterNetwork::try_from(&cluster("cilium", labels))
+                .expect("network should be valid")
+                .domain,
+            "k8s.example.com"
+        );
+
+        for domain in ["", "Cluster.Local", "cluster..local", "-cluster.local"] {
+            let labels = magnum::ClusterLabels::builder()
+                .dns_cluster_domain(domain.into())
+                .build();
+
+            assert_eq!(
+                ClusterNetwork::try_from(&cluster("cilium", labels)),
+                Err(Error::InvalidDomain(domain.into()))
+            );
+        }
+    }
+}
@@ -3,9 +3,18 @@ use std::collections::BTreeMap;
 use crate::{
     addons::{self, ClusterAddon},
     clients::kubernetes::{self, ClientHelpers},
-    cluster_api::clusterresourcesets::ClusterResourceSet,
-    features,
+    cluster_api::{
+        clusterresourcesets::ClusterResourceSet, clusters,
+        kubeadmconfigtemplates::KubeadmConfigTemplate, kubeadmcontrolplane::KubeadmControlPlane,
+    },
+    cluster_network::ClusterNetwork,
+    etcd_snapshots::EtcdSnapshotStore,
+    features::{
+        self,
+        ignition::{self, KubeadmCommand},
+    },
     magnum::{self},
+    orc::servergroups::ServerGroup,
     resources::ClusterClassBuilder,
 };
 use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, StatefulSet};
@@ -13,11 +22,16 @@ use k8s_openapi::api::core::v1::{Namespace, Secret};
 use kube::{
     api::{Api, ListParams, ObjectMeta},
     core::Expression,
-    Client,
+    runtime::wait::{await_condition, conditions},
+    Client, Resource, ResourceExt,
 };
 use log::debug;
 use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyType};
 use pyo3_async_runtimes::tokio::get_runtime;
+use std::time::Duration;
+
+/// How long to wait for ORC to create or delete a server group in OpenStack.
+const SERVER_GROUP_TIMEOUT: Duration = Duration::from_secs(300);
 
 create_exception!(magnum_cluster_api, ImmutableFieldError, PyException);
 
@@ -272,13 +286,17 @@ impl Driver {
     // TODO(mnaser): We should move this out of the Python-facing implementation once we have
     //               migrated all the code to Rust.
     #[classmethod]
-    #[pyo3(signature = (cluster))]
+    #[pyo3(signature = (cluster, storage_classes=None))]
     fn get_cinder_csi_cluster_resource_secret_data(
         _cls: &Bound<'_, PyType>,
         cluster: Py<PyAny>,
+        storage_classes: Option<String>,
         py: Python<'_>,
     ) -> PyResult<Option<BTreeMap<String, String>>> {
-        let cluster: magnum::Cluster = cluster.extract(py)?;
+        let mut cluster: magnum::Cluster = cluster.extract(py)?;
+        if storage_classes.is_some() {
+            cluster.labels.cinder_csi_storage_classes = storage_classes;
+        }
 
         let addon = addons::cinder_csi::Addon::new(cluster.clone());
         Ok(cluster.cluster_addon_secret(&addon)?.string_data)
@@ -287,18 +305,101 @@ impl Driver {
     // TODO(mnaser): We should move this out of the Python-facing implementation once we have
     //               migrated all the code to Rust.
     #[classmethod]
-    #[pyo3(signature = (cluster))]
+    #[pyo3(signature = (cluster, cephfs_enabled=false))]
     fn get_manila_csi_cluster_resource_secret_data(
         _cls: &Bound<'_, PyType>,
         cluster: Py<PyAny>,
+        cephfs_enabled: bool,
         py: Python<'_>,
     ) -> PyResult<Option<BTreeMap<String, String>>> {
         let cluster: magnum::Cluster = cluster.extract(py)?;
 
+        addons::manila_csi::ShareProtocol::from_labels_checked(&cluster.labels, cephfs_enabled)?;
+
         let addon = addons::manila_csi::Addon::new(cluster.clone());
         Ok(cluster.cluster_addon_secret(&addon)?.string_data)
     }
 
+    /// Cilium is only deployed through this secret when it replaces
+    /// kube-proxy, since it needs the address of the API server.
+    #[classmethod]
+    #[pyo3(signature = (cluster))]
+    fn get_cilium_cluster_resource_secret_data(
+        _cls: &Bound<'_, PyType>,
+        cluster: Py<PyAny>,
+        py: Python<'_>,
+    ) -> PyResult<Option<BTreeMap<String, String>>> {
+        let cluster: magnum::Cluster = cluster.extract(py)?;
+
+        let addon = addons::cilium::Addon::new(cluster.clone());
+        Ok(cluster.cluster_addon_secret(&addon)?.string_data)
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (cluster))]
+    fn get_runtime_classes_cluster_resource_secret_data(
+        _cls: &Bound<'_, PyType>,
+        cluster: Py<PyAny>,
+        py: Python<'_>,
+    ) -> PyResult<Option<BTreeMap<String, String>>> {
+        let cluster: magnum::Cluster = cluster.extract(py)?;
+
+        let addon = addons::runtime_classes::Addon::new(cluster.clone());
+        Ok(cluster.cluster_addon_secret(&addon)?.string_data)
+    }
+
+    /// Validates the networks of the cluster, which are set by separate labels
+    /// and must not overlap each other.
+    #[classmethod]
+    #[pyo3(signature = (cluster))]
+    fn validate_cluster_network(
+        _cls: &Bound<'_, PyType>,
+        cluster: Py<PyAny>,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        let cluster: magnum::Cluster = cluster.extract(py)?;
+
+        ClusterNetwork::try_from(&cluster)?;
+        Ok(())
+    }
+
+    /// Validates the storage classes which are configured for the Cinder CSI
+    /// driver through the `cinder_csi_storage_classes` label, or the operator
+    /// default which is passed by the caller when the label is unset.
+    #[classmethod]
+    #[pyo3(signature = (cluster, storage_classes=None))]
+    fn validate_cinder_csi_storage_classes(
+        _cls: &Bound<'_, PyType>,
+        cluster: Py<PyAny>,
+        storage_classes: Option<String>,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        let mut cluster: magnum::Cluster = cluster.extract(py)?;
+        if storage_classes.is_some() {
+            cluster.labels.cinder_csi_storage_classes = storage_classes;
+        }
+
+        addons::cinder_csi::storage_classes(&cluster.labels)?;
+        Ok(())
+    }
+
+    /// Validates that a manifest is shipped for the `calico_tag` of clusters
+    /// which use the Calico network driver.
+    #[classmethod]
+    #[pyo3(signature = (cluster))]
+    fn validate_calico_tag(
+        _cls: &Bound<'_, PyType>,
+        cluster: Py<PyAny>,
+        py: Python<'_>,
+    ) -> PyResult<()> {
+        let cluster: magnum::Cluster = cluster.extract(py)?;
+
+        if addons::calico::Addon::new(cluster.clone()).enabled() {
+            addons::calico::manifest(&cluster.labels.calico_tag)?;
+        }
+        Ok(())
+    }
+
     fn create_cluster(&self, py: Python<'_>, cluster: Py<PyAny>) -> PyResult<()> {
         let cluster: magnum::Cluster = cluster.extract(py)?;
 
@@ -327,6 +428,191 @@ impl Driver {
         Ok(())
     }
 
+    /// Applies the server group for the control plane, or for the node group if
+    /// one is given, and returns its ID once it has been created in OpenStack.
+    #[pyo3(signature = (cluster, node_group=None))]
+    fn apply_server_group(
+        &self,
+        py: Python<'_>,
+        cluster: Py<PyAny>,
+        node_group: Option<Py<PyAny>>,
+    ) -> PyResult<String> {
+        let cluster: magnum::Cluster = cluster.extract(py)?;
+        let mut server_group = match node_group {
+            Some(node_group) => cluster.node_group_server_group(&node_group.extract(py)?)?,
+            None => cluster.control_plane_server_group()?,
+        };
+
+        let name = server_group.name_any();
+        let api = Api::<ServerGroup>::namespaced(self.client.clone(), &self.namespace);
+
+        Python::detach(py, || {
+            get_runtime().block_on(async {
+                // NOTE: The server groups of a new cluster are created before
+                //       the Cluster API cluster which owns them, so they only
+                //       get their owner reference once they are applied again.
+                server_group.metadata.owner_references =
+                    Api::<clusters::Cluster>::namespaced(self.client.clone(), &self.namespace)
+                        .get_opt(&cluster.stack_id()?)
+                        .await
+                        .map_err(kubernetes::Error::from)?
+                        .and_then(|owner| owner.owner_ref(&()))
+                        .map(|owner_reference| vec![owner_reference]);
+
+                self.client
+                    .create_or_update_namespaced_resource(&self.namespace, server_group)
+                    .await?;
+
+                let has_id = |server_group: Option<&ServerGroup>| {
+                    server_group
+                        .and_then(|server_group| server_group.status.as_ref())
+                        .and_then(|status| status.id.as_ref())
+                        .is_some()
+                };
+                let server_group =
+                    tokio::time::timeout(SERVER_GROUP_TIMEOUT, await_condition(api, &name, has_id))
+                        .await
+                        .map_err(|_| magnum::ClusterError::ServerGroupTimeout(name.clone()))?
+                        .map_err(magnum::ClusterError::from)?;
+
+                Ok(server_group
+                    .and_then(|server_group| server_group.status)
+                    .and_then(|status| status.id)
+                    .ok_or(magnum::ClusterError::ServerGroupTimeout(name))?)
+            })
+        })
+    }
+
+    /// Deletes the server group for the control plane, or for the node group if
+    /// one is given, and waits until ORC has deleted it from OpenStack.
+    #[pyo3(signature = (cluster, node_group=None))]
+    fn delete_server_group(
+        &self,
+        py: Python<'_>,
+        cluster: Py<PyAny>,
+        node_group: Option<Py<PyAny>>,
+    ) -> PyResult<()> {
+        let cluster: magnum::Cluster = cluster.extract(py)?;
+        let server_group = match node_group {
+            Some(node_group) => cluster.node_group_server_group(&node_group.extract(py)?)?,
+            None => cluster.control_plane_server_group()?,
+        };
+
+        let name = server_group.name_any();
+        let api = Api::<ServerGroup>::namespaced(self.client.clone(), &self.namespace);
+
+        Python::detach(py, || {
+            get_runtime().block_on(async {
+                let Some(uid) = api
+                    .get_opt(&name)
+                    .await
+                    .map_err(kubernetes::Error::from)?
+                    .and_then(|server_group| server_group.uid())
+                else {
+                    return Ok(());
+                };
+
+                self.client.delete_resource(api.clone(), &name).await?;
+
+                // NOTE: ORC only removes its finalizer once the server group
+                //       is deleted from OpenStack, which needs the credentials
+                //       of the cluster to still be valid.
+                tokio::time::timeout(
+                    SERVER_GROUP_TIMEOUT,
+                    await_condition(api, &name, conditions::is_deleted(&uid)),
+                )
+                .await
+                .map_err(|_| magnum::ClusterError::ServerGroupDeleteTimeout(name.clone()))?
+                .map_err(magnum::ClusterError::from)?;
+
+                Ok(())
+            })
+        })
+    }
+
+    /// Checks that the rendered bootstrap configuration of the control plane
+    /// and the node groups translates into a valid Ignition config, which the
+    /// nodes of Flatcar clusters need to boot.
+    #[pyo3(signature = (cluster))]
+    fn validate_ignition(&self, py: Python<'_>, cluster: Py<PyAny>) -> PyResult<()> {
+        let cluster: magnum::Cluster = cluster.extract(py)?;
+        let list_params = ListParams::default().labels(&format!(
+            "cluster.x-k8s.io/cluster-name={}",
+            cluster.stack_id()?
+        ));
+
+        let (control_planes, templates) = Python::detach(py, || {
+            get_runtime().block_on(async {
+                let control_planes =
+                    Api::<KubeadmControlPlane>::namespaced(self.client.clone(), &self.namespace)
+                        .list(&list_params)
+                        .await
+                        .map_err(kubernetes::Error::from)?;
+                let templates =
+                    Api::<KubeadmConfigTemplate>::namespaced(self.client.clone(), &self.namespace)
+                        .list(&list_params)
+                        .await
+                        .map_err(kubernetes::Error::from)?;
+
+                Ok::<_, kubernetes::Error>((control_planes, templates))
+            })
+        })?;
+
+        for control_plane in control_planes {
+            for command in [KubeadmCommand::Init, KubeadmCommand::Join] {
+                ignition::to_ignition(&control_plane.spec.kubeadm_config_spec, command)?;
+            }
+        }
+        for template in templates {
+            if let Some(spec) = template.spec.template.spec {
+                ignition::to_ignition(&spec, KubeadmCommand::Join)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lists the etcd snapshots of the cluster in object storage, from the
+    /// oldest to the newest one.
+    #[pyo3(signature = (cluster))]
+    fn list_etcd_snapshots(&self, py: Python<'_>, cluster: Py<PyAny>) -> PyResult<Py<PyAny>> {
+        let cluster: magnum::Cluster = cluster.extract(py)?;
+
+        let snapshots = Python::detach(py, || {
+            get_runtime().block_on(async {
+                EtcdSnapshotStore::for_cluster(self.client.clone(), &self.namespace, &cluster)
+                    .await?
+                    .list(&cluster)
+                    .await
+            })
+        })?;
+
+        Ok(pythonize::pythonize(py, &snapshots)?.unbind())
+    }
+
+    /// Checks that the cluster can be restored from the etcd snapshot, which is
+    /// restored by the first control plane node when the cluster is created.
+    #[pyo3(signature = (cluster, snapshot))]
+    fn restore_etcd_snapshot(
+        &self,
+        py: Python<'_>,
+        cluster: Py<PyAny>,
+        snapshot: String,
+    ) -> PyResult<Py<PyAny>> {
+        let cluster: magnum::Cluster = cluster.extract(py)?;
+
+        let snapshot = Python::detach(py, || {
+            get_runtime().block_on(async {
+                EtcdSnapshotStore::for_cluster(self.client.clone(), &self.namespace, &cluster)
+                    .await?
+                    .restore_source(&cluster, &snapshot)
+                    .await
+            })
+        })?;
+
+        Ok(pythonize::pythonize(py, &snapshot)?.unbind())
+    }
+
     fn resolve_immutable_fields(
         &self,
         py: Python<'_>,
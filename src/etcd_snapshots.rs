@@ -0,0 +1,287 @@
+use crate::magnum::{self, ClusterError};
+use futures::TryStreamExt;
+use k8s_openapi::api::core::v1::Secret;
+use kube::{Api, Client};
+use object_store::{aws::AmazonS3Builder, path::Path, ObjectStore};
+use pyo3::{exceptions::PyRuntimeError, PyErr};
+use serde::Serialize;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Cluster(#[from] ClusterError),
+
+    #[error(transparent)]
+    Kubernetes(#[from] kube::Error),
+
+    #[error("missing key {1} in etcd backup secret {0}")]
+    MissingSecretKey(String, String),
+
+    #[error(transparent)]
+    ObjectStore(#[from] object_store::Error),
+
+    #[error("etcd snapshot {0} does not belong to the project of the cluster")]
+    SnapshotNotAllowed(String),
+}
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> PyErr {
+        PyErr::new::<PyRuntimeError, _>(err.to_string())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EtcdSnapshot {
+    /// Key of the snapshot in the bucket.
+    pub name: String,
+
+    pub size: u64,
+
+    /// Time of the upload of the snapshot, in RFC 3339 format.
+    pub last_modified: String,
+}
+
+impl From<object_store::ObjectMeta> for EtcdSnapshot {
+    fn from(meta: object_store::ObjectMeta) -> Self {
+        EtcdSnapshot {
+            name: meta.location.to_string(),
+            size: meta.size,
+            last_modified: meta.last_modified.to_rfc3339(),
+        }
+    }
+}
+
+/// Snapshots of etcd which are uploaded to object storage by the control plane
+/// nodes of the clusters.
+pub struct EtcdSnapshotStore {
+    store: Arc<dyn ObjectStore>,
+}
+
+impl EtcdSnapshotStore {
+    pub fn new(store: Arc<dyn ObjectStore>) -> Self {
+        EtcdSnapshotStore { store }
+    }
+
+    /// Builds the store from the secret created by the driver for the cluster,
+    /// which contains the object storage endpoint and credentials.
+    pub async fn for_cluster(
+        client: Client,
+        namespace: &str,
+        cluster: &magnum::Cluster,
+    ) -> Result<Self, Error> {
+        let secret = Api::<Secret>::namespaced(client, namespace)
+            .get(&cluster.etcd_backup_secret_name()?)
+            .await?;
+
+        let store = s3_builder(&secret)?.build()?;
+
+        Ok(EtcdSnapshotStore::new(Arc::new(store)))
+    }
+
+    /// Lists the snapshots of the cluster, from the oldest to the newest one.
+    pub async fn list(&self, cluster: &magnum::Cluster) -> Result<Vec<EtcdSnapshot>, Error> {
+        let prefix = Path::from(cluster.etcd_backup_prefix());
+
+        let mut snapshots: Vec<EtcdSnapshot> = self
+            .store
+            .list(Some(&prefix))
+            .map_ok(EtcdSnapshot::from)
+            .try_collect()
+            .await?;
+        snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(snapshots)
+    }
+
+    /// Returns the snapshot which the cluster is restored from, which can be
+    /// any snapshot taken by a cluster in the same project.
+    pub async fn restore_source(
+        &self,
+        cluster: &magnum::Cluster,
+        name: &str,
+    ) -> Result<EtcdSnapshot, Error> {
+        if !name.starts_with(&format!("{}/", cluster.project_id)) {
+            return Err(Error::SnapshotNotAllowed(name.to_string()));
+        }
+
+        Ok(self.store.head(&Path::from(name)).await?.into())
+    }
+}
+
+/// Returns the builder of the S3 client for the bucket of the project of the
+/// cluster, using the EC2 credential of the cluster.
+fn s3_builder(secret: &Secret) -> Result<AmazonS3Builder, Error> {
+    let secret_name = secret.metadata.name.clone().unwrap_or_default();
+    let data = secret.data.clone().unwrap_or_default();
+
+    let get = |key: &str| -> Result<String, Error> {
+        data.get(key)
+            .map(|value| String::from_utf8_lossy(&value.0).to_string())
+            .ok_or_else(|| Error::MissingSecretKey(secret_name.clone(), key.to_string()))
+    };
+
+    // NOTE: The bucket is created by the first snapshot of the project, so it
+    //       is owned by the project and can not be read with the keys of any
+    //       other user on object storages which separate the tenants, such as
+    //       Ceph RGW with Keystone authentication.
+    let endpoint = get("endpoint")?;
+    Ok(AmazonS3Builder::new()
+        .with_allow_http(endpoint.starts_with("http://"))
+        .with_endpoint(endpoint)
+        .with_bucket_name(get("bucket")?)
+        .with_region(get("region")?)
+        .with_access_key_id(get("ec2-access-key")?)
+        .with_secret_access_key(get("ec2-secret-key")?)
+        .with_virtual_hosted_style_request(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::ByteString;
+    use kube::api::ObjectMeta;
+    use maplit::btreemap;
+    use object_store::{aws::AmazonS3ConfigKey, memory::InMemory, PutPayload};
+    use pretty_assertions::assert_eq;
+
+    async fn store() -> EtcdSnapshotStore {
+        let store = InMemory::new();
+
+        for key in [
+            "project-a/cluster-a/etcd-snapshot-20261019T030000Z.db",
+            "project-a/cluster-a/etcd-snapshot-20261018T030000Z.db",
+            "project-a/cluster-b/etcd-snapshot-20261019T030000Z.db",
+            "project-b/cluster-c/etcd-snapshot-20261019T030000Z.db",
+        ] {
+            store
+                .put(&Path::from(key), PutPayload::from_static(b"snapshot"))
+                .await
+                .expect("put should succeed");
+        }
+
+        EtcdSnapshotStore::new(Arc::new(store))
+    }
+
+    fn cluster() -> magnum::Cluster {
+        magnum::Cluster {
+            uuid: "cluster-a".to_string(),
+            project_id: "project-a".to_string(),
+            stack_id: "kube-abcde".to_string().into(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let snapshots = store()
+            .await
+            .list(&cluster())
+            .await
+            .expect("list should succeed");
+
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|snapshot| (snapshot.name.as_str(), snapshot.size))
+                .collect::<Vec<_>>(),
+            vec![
+                ("project-a/cluster-a/etcd-snapshot-20261018T030000Z.db", 8),
+                ("project-a/cluster-a/etcd-snapshot-20261019T030000Z.db", 8),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restore_source() {
+        let store = store().await;
+
+        let snapshot = store
+            .restore_source(
+                &cluster(),
+                "project-a/cluster-b/etcd-snapshot-20261019T030000Z.db",
+            )
+            .await
+            .expect("snapshot should be found");
+        assert_eq!(
+            snapshot.name,
+            "project-a/cluster-b/etcd-snapshot-20261019T030000Z.db"
+        );
+
+        assert!(matches!(
+            store
+                .restore_source(
+                    &cluster(),
+                    "project-b/cluster-c/etcd-snapshot-20261019T030000Z.db"
+                )
+                .await,
+            Err(Error::SnapshotNotAllowed(_))
+        ));
+        assert!(matches!(
+            store
+                .restore_source(
+                    &cluster(),
+                    "project-a/cluster-a/etcd-snapshot-20261017T030000Z.db"
+                )
+                .await,
+            Err(Error::ObjectStore(object_store::Error::NotFound { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_s3_builder_uses_project_credential() {
+        // NOTE: The secret of a cluster created by another user of the project
+        //       than the one whose cluster created the bucket.
+        let secret = Secret {
+            metadata: ObjectMeta {
+                name: Some("kube-abcde-etcd-backup".into()),
+                ..Default::default()
+            },
+            data: Some(btreemap! {
+                "endpoint".to_string() => ByteString(b"https://s3.example.com".to_vec()),
+                "bucket".to_string() => ByteString(b"etcd-project-a".to_vec()),
+                "region".to_string() => ByteString(b"us-east-1".to_vec()),
+                "ec2-access-key".to_string() => ByteString(b"project-access".to_vec()),
+                "ec2-secret-key".to_string() => ByteString(b"project-secret".to_vec()),
+            }),
+            ..Default::default()
+        };
+
+        let builder = s3_builder(&secret).expect("builder should be created");
+
+        assert_eq!(
+            builder.get_config_value(&AmazonS3ConfigKey::Bucket),
+            Some("etcd-project-a".into())
+        );
+        assert_eq!(
+            builder.get_config_value(&AmazonS3ConfigKey::AccessKeyId),
+            Some("project-access".into())
+        );
+        assert_eq!(
+            builder.get_config_value(&AmazonS3ConfigKey::SecretAccessKey),
+            Some("project-secret".into())
+        );
+    }
+
+    #[test]
+    fn test_s3_builder_missing_credential() {
+        let secret = Secret {
+            metadata: ObjectMeta {
+                name: Some("kube-abcde-etcd-backup".into()),
+                ..Default::default()
+            },
+            data: Some(btreemap! {
+                "endpoint".to_string() => ByteString(b"https://s3.example.com".to_vec()),
+                "bucket".to_string() => ByteString(b"etcd-project-a".to_vec()),
+                "region".to_string() => ByteString(b"us-east-1".to_vec()),
+            }),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            s3_builder(&secret),
+            Err(Error::MissingSecretKey(_, key)) if key == "ec2-access-key"
+        ));
+    }
+}
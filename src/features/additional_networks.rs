@@ -0,0 +1,335 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources,
+            ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass,
+            ClusterClassVariables, ClusterClassVariablesSchema,
+        },
+        openstackmachinetemplates::OpenStackMachineTemplate,
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use cluster_feature_derive::ClusterFeatureValues;
+use indoc::indoc;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+/// A fixed IP requested on a secondary port, the IP address is optional and
+/// will be allocated from the subnet when left empty.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct AdditionalNetworkFixedIP {
+    #[serde(rename = "subnetId")]
+    #[schemars(regex(pattern = r"^[0-9a-fA-F-]{36}$"))]
+    pub subnet_id: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "ipAddress")]
+    #[schemars(regex(pattern = r"^[0-9a-fA-F.:]*$"))]
+    pub ip_address: String,
+}
+
+/// An address pair which is allowed to egress from a secondary port, this is
+/// typically used for virtual IPs managed by keepalived or similar.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct AdditionalNetworkAllowedAddressPair {
+    #[serde(rename = "ipAddress")]
+    #[schemars(regex(pattern = r"^[0-9a-fA-F.:/]+$"))]
+    pub ip_address: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "macAddress")]
+    #[schemars(regex(pattern = r"^([0-9a-fA-F]{2}(:[0-9a-fA-F]{2}){5})?$"))]
+    pub mac_address: String,
+}
+
+/// A secondary port which is attached to every machine after the port on the
+/// cluster network, the network is looked up by ID if set, otherwise by name.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct AdditionalNetwork {
+    #[builder(default)]
+    #[serde(default, rename = "networkId")]
+    #[schemars(regex(pattern = r"^([0-9a-fA-F-]{36})?$"))]
+    pub network_id: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "networkName")]
+    #[schemars(regex(pattern = r#"^[^"\\]*$"#))]
+    pub network_name: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "nameSuffix")]
+    #[schemars(regex(pattern = r"^[a-zA-Z0-9-]*$"))]
+    pub name_suffix: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "fixedIPs")]
+    pub fixed_ips: Vec<AdditionalNetworkFixedIP>,
+
+    #[builder(default)]
+    #[serde(default, rename = "vnicType")]
+    #[schemars(regex(
+        pattern = r"^(|normal|direct|direct-physical|macvtap|baremetal|virtio-forwarder|smart-nic|remote-managed|vdpa)$"
+    ))]
+    pub vnic_type: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "disablePortSecurity")]
+    pub disable_port_security: bool,
+
+    #[builder(default)]
+    #[serde(default, rename = "allowedAddressPairs")]
+    pub allowed_address_pairs: Vec<AdditionalNetworkAllowedAddressPair>,
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "additionalNetworks")]
+    pub additional_networks: Vec<AdditionalNetwork>,
+}
+
+// NOTE: Cluster API Provider OpenStack only creates the ports listed in the
+//       machine spec once any are specified, so the first port is left empty
+//       in order to keep the default port on the cluster network.  The ports
+//       are only rendered when there are additional networks inside the
+//       template rather than with `enabledIf` since node groups override
+//       `additionalNetworks`, see [`ClusterFeaturePatches`].
+const PORTS_TEMPLATE: &str = indoc! {r#"
+    {{- if .additionalNetworks }}
+    - {}
+    {{- range .additionalNetworks }}
+    - network:
+        {{- if .networkId }}
+        id: "{{ .networkId }}"
+        {{- else }}
+        filter:
+          name: "{{ .networkName }}"
+        {{- end }}
+      {{- if .nameSuffix }}
+      nameSuffix: "{{ .nameSuffix }}"
+      {{- end }}
+      {{- if .fixedIPs }}
+      fixedIPs:
+        {{- range .fixedIPs }}
+        - subnet:
+            id: "{{ .subnetId }}"
+          {{- if .ipAddress }}
+          ipAddress: "{{ .ipAddress }}"
+          {{- end }}
+        {{- end }}
+      {{- end }}
+      {{- if .vnicType }}
+      vnicType: "{{ .vnicType }}"
+      {{- end }}
+      {{- if .disablePortSecurity }}
+      disablePortSecurity: true
+      {{- end }}
+      {{- if .allowedAddressPairs }}
+      allowedAddressPairs:
+        {{- range .allowedAddressPairs }}
+        - ipAddress: "{{ .ipAddress }}"
+          {{- if .macAddress }}
+          macAddress: "{{ .macAddress }}"
+          {{- end }}
+        {{- end }}
+      {{- end }}
+    {{- end }}
+    {{- end }}
+"#};
+
+fn json_patches() -> Vec<ClusterClassPatchesDefinitionsJsonPatches> {
+    vec![ClusterClassPatchesDefinitionsJsonPatches {
+        op: "add".into(),
+        path: "/spec/template/spec/ports".into(),
+        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+            template: Some(PORTS_TEMPLATE.into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }]
+}
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        vec![ClusterClassPatches {
+            name: "additionalNetworks".into(),
+            definitions: Some(vec![
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackMachineTemplate::api_resource().api_version,
+                        kind: OpenStackMachineTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            control_plane: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: json_patches(),
+                },
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackMachineTemplate::api_resource().api_version,
+                        kind: OpenStackMachineTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            machine_deployment_class: Some(
+                                ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
+                                    names: Some(vec!["default-worker".to_string()]),
+                                },
+                            ),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: json_patches(),
+                },
+            ]),
+            ..Default::default()
+        }]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cluster_api::openstackmachinetemplates::{
+            OpenStackMachineTemplateTemplateSpecPorts,
+            OpenStackMachineTemplateTemplateSpecPortsAllowedAddressPairs,
+            OpenStackMachineTemplateTemplateSpecPortsFixedIPs,
+            OpenStackMachineTemplateTemplateSpecPortsFixedIPsSubnet,
+            OpenStackMachineTemplateTemplateSpecPortsNetwork,
+            OpenStackMachineTemplateTemplateSpecPortsNetworkFilter,
+        },
+        features::test::TestClusterResources,
+        resources::fixtures::default_values,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_disabled_by_default() {
+        let feature = Feature {};
+
+        let values = default_values();
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources
+                .control_plane_openstack_machine_template
+                .spec
+                .template
+                .spec
+                .ports,
+            None
+        );
+        assert_eq!(
+            resources
+                .worker_openstack_machine_template
+                .spec
+                .template
+                .spec
+                .ports,
+            None
+        );
+    }
+
+    #[test]
+    fn test_apply_patches() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.additional_networks = vec![
+            AdditionalNetwork::builder()
+                .network_id("0c4a1e1f-8b6e-4d5b-9d5a-3f2e1c0b9a8d".into())
+                .name_suffix("storage".into())
+                .fixed_ips(vec![AdditionalNetworkFixedIP::builder()
+                    .subnet_id("5e6f7a8b-9c0d-4e1f-8a2b-3c4d5e6f7a8b".into())
+                    .build()])
+                .vnic_type("direct".into())
+                .disable_port_security(true)
+                .build(),
+            AdditionalNetwork::builder()
+                .network_name("vip-network".into())
+                .allowed_address_pairs(vec![AdditionalNetworkAllowedAddressPair::builder()
+                    .ip_address("192.168.100.10".into())
+                    .build()])
+                .build(),
+        ];
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let expected = Some(vec![
+            OpenStackMachineTemplateTemplateSpecPorts::default(),
+            OpenStackMachineTemplateTemplateSpecPorts {
+                network: Some(OpenStackMachineTemplateTemplateSpecPortsNetwork {
+                    id: Some("0c4a1e1f-8b6e-4d5b-9d5a-3f2e1c0b9a8d".into()),
+                    ..Default::default()
+                }),
+                name_suffix: Some("storage".into()),
+                fixed_i_ps: Some(vec![OpenStackMachineTemplateTemplateSpecPortsFixedIPs {
+                    subnet: Some(OpenStackMachineTemplateTemplateSpecPortsFixedIPsSubnet {
+                        id: Some("5e6f7a8b-9c0d-4e1f-8a2b-3c4d5e6f7a8b".into()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }]),
+                vnic_type: Some("direct".into()),
+                disable_port_security: Some(true),
+                ..Default::default()
+            },
+            OpenStackMachineTemplateTemplateSpecPorts {
+                network: Some(OpenStackMachineTemplateTemplateSpecPortsNetwork {
+                    filter: Some(OpenStackMachineTemplateTemplateSpecPortsNetworkFilter {
+                        name: Some("vip-network".into()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                allowed_address_pairs: Some(vec![
+                    OpenStackMachineTemplateTemplateSpecPortsAllowedAddressPairs {
+                        ip_address: "192.168.100.10".into(),
+                        mac_address: None,
+                    },
+                ]),
+                ..Default::default()
+            },
+        ]);
+
+        assert_eq!(
+            resources
+                .control_plane_openstack_machine_template
+                .spec
+                .template
+                .spec
+                .ports,
+            expected
+        );
+        assert_eq!(
+            resources
+                .worker_openstack_machine_template
+                .spec
+                .template
+                .spec
+                .ports,
+            expected
+        );
+    }
+}
@@ -8,7 +8,10 @@ use crate::{
             ClusterClassPatchesDefinitionsSelectorMatchResources, ClusterClassVariables,
             ClusterClassVariablesSchema,
         },
-        kubeadmcontrolplanetemplates::KubeadmControlPlaneTemplate,
+        kubeadmcontrolplanetemplates::{
+            KubeadmControlPlaneTemplate,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes,
+        },
     },
     features::{
         ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
@@ -16,44 +19,256 @@ use crate::{
     },
 };
 use cluster_feature_derive::ClusterFeatureValues;
+use indoc::indoc;
 use kube::CustomResourceExt;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use typed_builder::TypedBuilder;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PodSecurityLevel {
+    #[default]
+    Privileged,
+    Baseline,
+    Restricted,
+}
+
+/// Users, namespaces and runtime classes which are exempted from the Pod
+/// Security Admission checks.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct PodSecurityExemptions {
+    #[serde(default)]
+    #[schemars(inner(regex(pattern = r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?$")))]
+    #[builder(default)]
+    pub namespaces: Vec<String>,
+
+    #[serde(default)]
+    #[schemars(inner(regex(pattern = r#"^[^"\\]+$"#)))]
+    #[builder(default)]
+    pub usernames: Vec<String>,
+
+    #[serde(default, rename = "runtimeClasses")]
+    #[schemars(inner(regex(pattern = r"^[a-z0-9]([-.a-z0-9]*[a-z0-9])?$")))]
+    #[builder(default)]
+    pub runtime_classes: Vec<String>,
+}
+
+/// Cluster-wide defaults for the `PodSecurity` admission plugin, which apply
+/// to every namespace without `pod-security.kubernetes.io/*` labels.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct PodSecurityConfig {
+    pub enabled: bool,
+
+    #[serde(default)]
+    #[builder(default)]
+    pub enforce: PodSecurityLevel,
+
+    #[serde(default = "default_pod_security_version", rename = "enforceVersion")]
+    #[schemars(regex(pattern = r"^(latest|v1\.[0-9]+)$"))]
+    #[builder(default = default_pod_security_version())]
+    pub enforce_version: String,
+
+    #[serde(default)]
+    #[builder(default)]
+    pub audit: PodSecurityLevel,
+
+    #[serde(default = "default_pod_security_version", rename = "auditVersion")]
+    #[schemars(regex(pattern = r"^(latest|v1\.[0-9]+)$"))]
+    #[builder(default = default_pod_security_version())]
+    pub audit_version: String,
+
+    #[serde(default)]
+    #[builder(default)]
+    pub warn: PodSecurityLevel,
+
+    #[serde(default = "default_pod_security_version", rename = "warnVersion")]
+    #[schemars(regex(pattern = r"^(latest|v1\.[0-9]+)$"))]
+    #[builder(default = default_pod_security_version())]
+    pub warn_version: String,
+
+    #[serde(default)]
+    #[builder(default)]
+    pub exemptions: PodSecurityExemptions,
+}
+
+fn default_pod_security_version() -> String {
+    "latest".into()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum EventRateLimitType {
+    Server,
+    Namespace,
+    User,
+    SourceAndObject,
+}
+
+/// A single limit for the `EventRateLimit` admission plugin, a `cacheSize` of
+/// zero leaves the Kubernetes default in place.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct EventRateLimit {
+    #[serde(rename = "type")]
+    pub limit_type: EventRateLimitType,
+
+    #[schemars(range(min = 1))]
+    pub qps: i64,
+
+    #[schemars(range(min = 1))]
+    pub burst: i64,
+
+    #[serde(default, rename = "cacheSize")]
+    #[builder(default)]
+    pub cache_size: i64,
+}
 
 #[derive(Serialize, Deserialize, ClusterFeatureValues)]
 #[allow(dead_code)]
 pub struct FeatureValues {
     #[serde(rename = "admissionControlList")]
     pub admission_control_list: String,
+
+    #[serde(rename = "podSecurity")]
+    pub pod_security: PodSecurityConfig,
+
+    #[serde(rename = "eventRateLimits")]
+    pub event_rate_limits: Vec<EventRateLimit>,
 }
 
+/// Directory on the control plane hosts which holds the admission plugin
+/// configuration, it is mounted read-only into the API server.
+const ADMISSION_CONFIGURATION_DIR: &str = "/etc/kubernetes/admission";
+
+/// Go template which renders the `kubeadmConfigSpec` file holding the single
+/// `AdmissionConfiguration` shared by every configurable admission plugin.
+const ADMISSION_CONFIGURATION_FILE_TEMPLATE: &str = indoc! {r#"
+    path: /etc/kubernetes/admission/configuration.yaml
+    owner: root:root
+    permissions: "0600"
+    content: |
+      apiVersion: apiserver.config.k8s.io/v1
+      kind: AdmissionConfiguration
+      plugins:
+      {{- if .podSecurity.enabled }}
+        - name: PodSecurity
+          configuration:
+            apiVersion: pod-security.admission.config.k8s.io/v1
+            kind: PodSecurityConfiguration
+            defaults:
+              enforce: "{{ .podSecurity.enforce }}"
+              enforce-version: "{{ .podSecurity.enforceVersion }}"
+              audit: "{{ .podSecurity.audit }}"
+              audit-version: "{{ .podSecurity.auditVersion }}"
+              warn: "{{ .podSecurity.warn }}"
+              warn-version: "{{ .podSecurity.warnVersion }}"
+            exemptions:
+              namespaces:
+              {{- range .podSecurity.exemptions.namespaces }}
+                - "{{ . }}"
+              {{- end }}
+              usernames:
+              {{- range .podSecurity.exemptions.usernames }}
+                - "{{ . }}"
+              {{- end }}
+              runtimeClasses:
+              {{- range .podSecurity.exemptions.runtimeClasses }}
+                - "{{ . }}"
+              {{- end }}
+      {{- end }}
+      {{- if .eventRateLimits }}
+        - name: EventRateLimit
+          configuration:
+            apiVersion: eventratelimit.admission.k8s.io/v1alpha1
+            kind: Configuration
+            limits:
+            {{- range .eventRateLimits }}
+              - type: {{ .type }}
+                qps: {{ .qps }}
+                burst: {{ .burst }}
+                {{- if .cacheSize }}
+                cacheSize: {{ .cacheSize }}
+                {{- end }}
+            {{- end }}
+      {{- end }}
+"#};
+
 pub struct Feature {}
 
 impl ClusterFeaturePatches for Feature {
     fn patches(&self) -> Vec<ClusterClassPatches> {
-        vec![ClusterClassPatches {
-            name: "admissionControlList".into(),
-            enabled_if: Some("{{ if .admissionControlList }}true{{end}}".into()),
-            definitions: Some(vec![ClusterClassPatchesDefinitions {
-                selector: ClusterClassPatchesDefinitionsSelector {
-                    api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
-                    kind: KubeadmControlPlaneTemplate::api_resource().kind,
-                    match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                        control_plane: Some(true),
-                        ..Default::default()
+        vec![
+            ClusterClassPatches {
+                name: "admissionControlList".into(),
+                enabled_if: Some("{{ if .admissionControlList }}true{{end}}".into()),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+                        kind: KubeadmControlPlaneTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            control_plane: Some(true),
+                            ..Default::default()
+                        },
                     },
-                },
-                json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
-                    op: "add".into(),
-                    path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/enable-admission-plugins".into(),
-                    value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                        variable: Some("admissionControlList".into()),
+                    json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/enable-admission-plugins".into(),
+                        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                            variable: Some("admissionControlList".into()),
+                            ..Default::default()
+                        }),
                         ..Default::default()
-                    }),
-                    ..Default::default()
-                }],
-            }]),
-            ..Default::default()
-        }]
+                    }],
+                }]),
+                ..Default::default()
+            },
+            ClusterClassPatches {
+                name: "admissionConfiguration".into(),
+                enabled_if: Some(
+                    "{{ if or .podSecurity.enabled .eventRateLimits }}true{{end}}".into(),
+                ),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+                        kind: KubeadmControlPlaneTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            control_plane: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: vec![
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                            value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                template: Some(ADMISSION_CONFIGURATION_FILE_TEMPLATE.into()),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/admission-control-config-file".into(),
+                            value: Some(format!("{}/configuration.yaml", ADMISSION_CONFIGURATION_DIR).into()),
+                            ..Default::default()
+                        },
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraVolumes/-".into(),
+                            value: Some(json!(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes {
+                                name: "admission-configuration".to_string(),
+                                host_path: ADMISSION_CONFIGURATION_DIR.to_string(),
+                                mount_path: ADMISSION_CONFIGURATION_DIR.to_string(),
+                                read_only: Some(true),
+                                ..Default::default()
+                            })),
+                            ..Default::default()
+                        },
+                    ],
+                }]),
+                ..Default::default()
+            },
+        ]
     }
 }
 
@@ -64,9 +279,46 @@ inventory::submit! {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{features::test::TestClusterResources, resources::fixtures::default_values};
+    use crate::{
+        cluster_api::kubeadmcontrolplanetemplates::KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServer,
+        features::test::TestClusterResources, resources::fixtures::default_values,
+    };
     use pretty_assertions::assert_eq;
 
+    fn admission_configuration(resources: &TestClusterResources) -> Option<serde_yaml::Value> {
+        resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec
+            .files
+            .as_ref()
+            .expect("files should be set")
+            .iter()
+            .find(|f| f.path == "/etc/kubernetes/admission/configuration.yaml")
+            .map(|f| {
+                serde_yaml::from_str(f.content.as_ref().expect("content should be set"))
+                    .expect("content should be valid YAML")
+            })
+    }
+
+    fn api_server(
+        resources: &TestClusterResources,
+    ) -> KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServer
+    {
+        resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec
+            .cluster_configuration
+            .clone()
+            .expect("cluster configuration should be set")
+            .api_server
+            .expect("api server should be set")
+    }
 
     #[test]
     fn test_admission_plugins_patch() {
@@ -114,9 +366,11 @@ mod tests {
         let feature = Feature {};
         let variables = feature.variables();
 
-        assert_eq!(variables.len(), 1);
+        assert_eq!(variables.len(), 3);
         assert_eq!(variables[0].name, "admissionControlList");
         assert_eq!(variables[0].required, true);
+        assert_eq!(variables[1].name, "podSecurity");
+        assert_eq!(variables[2].name, "eventRateLimits");
     }
 
     #[test]
@@ -176,4 +430,195 @@ mod tests {
             Some(&"PodNodeSelector,NodeRestriction,LimitRanger".to_string())
         );
     }
+
+    #[test]
+    fn test_admission_configuration_disabled() {
+        let feature = Feature {};
+        let values = default_values();
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(admission_configuration(&resources), None);
+
+        let api_server = api_server(&resources);
+        assert_eq!(
+            api_server
+                .extra_args
+                .expect("extra args should be set")
+                .get("admission-control-config-file"),
+            None
+        );
+        assert!(!api_server
+            .extra_volumes
+            .unwrap_or_default()
+            .iter()
+            .any(|v| v.name == "admission-configuration"));
+    }
+
+    #[test]
+    fn test_pod_security() {
+        let feature = Feature {};
+        let mut values = default_values();
+        values.pod_security = PodSecurityConfig::builder()
+            .enabled(true)
+            .enforce(PodSecurityLevel::Baseline)
+            .enforce_version("v1.30".into())
+            .audit(PodSecurityLevel::Restricted)
+            .warn(PodSecurityLevel::Restricted)
+            .exemptions(
+                PodSecurityExemptions::builder()
+                    .namespaces(vec!["kube-system".into()])
+                    .usernames(vec!["system:serviceaccount:ci:deployer".into()])
+                    .runtime_classes(vec!["kata".into()])
+                    .build(),
+            )
+            .build();
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let configuration =
+            admission_configuration(&resources).expect("admission configuration should be set");
+
+        assert_eq!(
+            configuration,
+            serde_yaml::from_str::<serde_yaml::Value>(indoc! {r#"
+                apiVersion: apiserver.config.k8s.io/v1
+                kind: AdmissionConfiguration
+                plugins:
+                  - name: PodSecurity
+                    configuration:
+                      apiVersion: pod-security.admission.config.k8s.io/v1
+                      kind: PodSecurityConfiguration
+                      defaults:
+                        enforce: baseline
+                        enforce-version: v1.30
+                        audit: restricted
+                        audit-version: latest
+                        warn: restricted
+                        warn-version: latest
+                      exemptions:
+                        namespaces:
+                          - kube-system
+                        usernames:
+                          - system:serviceaccount:ci:deployer
+                        runtimeClasses:
+                          - kata
+            "#})
+            .unwrap()
+        );
+
+        let api_server = api_server(&resources);
+        assert_eq!(
+            api_server
+                .extra_args
+                .expect("extra args should be set")
+                .get("admission-control-config-file"),
+            Some(&"/etc/kubernetes/admission/configuration.yaml".to_string())
+        );
+        assert!(api_server
+            .extra_volumes
+            .expect("extra volumes should be set")
+            .contains(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes {
+                name: "admission-configuration".to_string(),
+                host_path: "/etc/kubernetes/admission".to_string(),
+                mount_path: "/etc/kubernetes/admission".to_string(),
+                read_only: Some(true),
+                ..Default::default()
+            }));
+    }
+
+    #[test]
+    fn test_event_rate_limit_shares_admission_configuration() {
+        let feature = Feature {};
+        let mut values = default_values();
+        values.pod_security = PodSecurityConfig::builder().enabled(true).build();
+        values.event_rate_limits = vec![
+            EventRateLimit::builder()
+                .limit_type(EventRateLimitType::Server)
+                .qps(50)
+                .burst(100)
+                .build(),
+            EventRateLimit::builder()
+                .limit_type(EventRateLimitType::Namespace)
+                .qps(10)
+                .burst(20)
+                .cache_size(2000)
+                .build(),
+        ];
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let files = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec
+            .files
+            .as_ref()
+            .expect("files should be set");
+        assert_eq!(
+            files
+                .iter()
+                .filter(|f| f.path == "/etc/kubernetes/admission/configuration.yaml")
+                .count(),
+            1
+        );
+
+        let configuration =
+            admission_configuration(&resources).expect("admission configuration should be set");
+        let plugins = configuration["plugins"]
+            .as_sequence()
+            .expect("plugins should be a list");
+
+        assert_eq!(plugins.len(), 2);
+        assert_eq!(plugins[0]["name"], "PodSecurity");
+        assert_eq!(plugins[1]["name"], "EventRateLimit");
+        assert_eq!(
+            plugins[1]["configuration"],
+            serde_yaml::from_str::<serde_yaml::Value>(indoc! {r#"
+                apiVersion: eventratelimit.admission.k8s.io/v1alpha1
+                kind: Configuration
+                limits:
+                  - type: Server
+                    qps: 50
+                    burst: 100
+                  - type: Namespace
+                    qps: 10
+                    burst: 20
+                    cacheSize: 2000
+            "#})
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_event_rate_limit_without_pod_security() {
+        let feature = Feature {};
+        let mut values = default_values();
+        values.event_rate_limits = vec![EventRateLimit::builder()
+            .limit_type(EventRateLimitType::User)
+            .qps(5)
+            .burst(10)
+            .build()];
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let configuration =
+            admission_configuration(&resources).expect("admission configuration should be set");
+        let plugins = configuration["plugins"]
+            .as_sequence()
+            .expect("plugins should be a list");
+
+        assert_eq!(plugins.len(), 1);
+        assert_eq!(plugins[0]["name"], "EventRateLimit");
+    }
 }
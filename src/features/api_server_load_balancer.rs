@@ -37,6 +37,10 @@ pub struct APIServerLoadBalancerConfig {
     #[serde(default, skip_serializing_if = "str::is_empty", rename = "availabilityZone")]
     #[builder(default)]
     pub availability_zone: String,
+
+    #[serde(default, rename = "allowedCIDRs")]
+    #[builder(default)]
+    pub allowed_cidrs: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, ClusterFeatureValues)]
@@ -50,29 +54,62 @@ pub struct Feature {}
 
 impl ClusterFeaturePatches for Feature {
     fn patches(&self) -> Vec<ClusterClassPatches> {
-        vec![ClusterClassPatches {
-            name: "apiServerLoadBalancer".into(),
-            definitions: Some(vec![ClusterClassPatchesDefinitions {
-                selector: ClusterClassPatchesDefinitionsSelector {
-                    api_version: OpenStackClusterTemplate::api_resource().api_version,
-                    kind: OpenStackClusterTemplate::api_resource().kind,
-                    match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                        infrastructure_cluster: Some(true),
+        vec![
+            ClusterClassPatches {
+                name: "apiServerLoadBalancer".into(),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackClusterTemplate::api_resource().api_version,
+                        kind: OpenStackClusterTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            infrastructure_cluster: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/apiServerLoadBalancer".into(),
+                        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                            variable: Some("apiServerLoadBalancer".into()),
+                            ..Default::default()
+                        }),
                         ..Default::default()
+                    }],
+                }]),
+                ..Default::default()
+            },
+            // NOTE: CAPO allows the bastion, router and subnet addresses it
+            //       knows about from the `OpenStackCluster` status, but a managed
+            //       subnet is only known once it has been created, so the node
+            //       CIDR is appended up-front to let the first nodes join.
+            ClusterClassPatches {
+                name: "apiServerLoadBalancerAllowedNodeCidr".into(),
+                enabled_if: Some(
+                    r#"{{ if and .apiServerLoadBalancer.enabled .apiServerLoadBalancer.allowedCIDRs (eq .fixedNetworkId "") }}true{{end}}"#
+                        .into(),
+                ),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackClusterTemplate::api_resource().api_version,
+                        kind: OpenStackClusterTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            infrastructure_cluster: Some(true),
+                            ..Default::default()
+                        },
                     },
-                },
-                json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
-                    op: "add".into(),
-                    path: "/spec/template/spec/apiServerLoadBalancer".into(),
-                    value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                        variable: Some("apiServerLoadBalancer".into()),
+                    json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/apiServerLoadBalancer/allowedCIDRs/-".into(),
+                        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                            variable: Some("nodeCidr".into()),
+                            ..Default::default()
+                        }),
                         ..Default::default()
-                    }),
-                    ..Default::default()
-                }],
-            }]),
-            ..Default::default()
-        }]
+                    }],
+                }]),
+                ..Default::default()
+            },
+        ]
     }
 }
 
@@ -199,4 +236,92 @@ mod tests {
         assert_eq!(api_server_load_balancer.flavor, None);
         assert_eq!(api_server_load_balancer.availability_zone, None);
     }
+
+    #[test]
+    fn test_patches_with_allowed_cidrs() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.api_server_load_balancer = APIServerLoadBalancerConfig::builder()
+            .enabled(true)
+            .allowed_cidrs(vec!["192.168.0.0/24".into(), "203.0.113.10/32".into()])
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let api_server_load_balancer = resources
+            .openstack_cluster_template
+            .spec
+            .template
+            .spec
+            .api_server_load_balancer
+            .expect("apiServerLoadBalancer should be set");
+
+        assert_eq!(
+            api_server_load_balancer.allowed_cid_rs,
+            Some(vec![
+                "192.168.0.0/24".to_string(),
+                "203.0.113.10/32".to_string(),
+                values.node_cidr,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_patches_with_allowed_cidrs_and_existing_network() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.fixed_network_id = "5d8c7a4e-8a70-4f4e-9a3e-3e1a0e6b6b39".into();
+        values.api_server_load_balancer = APIServerLoadBalancerConfig::builder()
+            .enabled(true)
+            .allowed_cidrs(vec!["192.168.0.0/24".into()])
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let api_server_load_balancer = resources
+            .openstack_cluster_template
+            .spec
+            .template
+            .spec
+            .api_server_load_balancer
+            .expect("apiServerLoadBalancer should be set");
+
+        assert_eq!(
+            api_server_load_balancer.allowed_cid_rs,
+            Some(vec!["192.168.0.0/24".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_patches_without_allowed_cidrs() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.api_server_load_balancer = APIServerLoadBalancerConfig::builder()
+            .enabled(true)
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let api_server_load_balancer = resources
+            .openstack_cluster_template
+            .spec
+            .template
+            .spec
+            .api_server_load_balancer
+            .expect("apiServerLoadBalancer should be set");
+
+        assert_eq!(api_server_load_balancer.allowed_cid_rs, Some(vec![]));
+    }
 }
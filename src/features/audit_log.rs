@@ -11,6 +11,8 @@ use crate::{
         kubeadmcontrolplanetemplates::{
             KubeadmControlPlaneTemplate,
             KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding,
         },
     },
     features::{
@@ -25,6 +27,59 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use typed_builder::TypedBuilder;
 
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuditWebhookMode {
+    #[default]
+    Batch,
+    Blocking,
+    BlockingStrict,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct AuditWebhookConfig {
+    pub enabled: bool,
+
+    /// Base64 encoded kubeconfig which points to the remote audit backend.
+    #[serde(default)]
+    #[builder(default)]
+    pub kubeconfig: String,
+
+    #[serde(default)]
+    #[builder(default)]
+    pub mode: AuditWebhookMode,
+
+    #[serde(default = "default_batch_buffer_size", rename = "batchBufferSize")]
+    #[builder(default = default_batch_buffer_size())]
+    pub batch_buffer_size: String,
+
+    #[serde(default = "default_batch_max_size", rename = "batchMaxSize")]
+    #[builder(default = default_batch_max_size())]
+    pub batch_max_size: String,
+
+    #[serde(default = "default_batch_max_wait", rename = "batchMaxWait")]
+    #[builder(default = default_batch_max_wait())]
+    pub batch_max_wait: String,
+}
+
+impl Default for AuditWebhookConfig {
+    fn default() -> Self {
+        AuditWebhookConfig::builder().enabled(false).build()
+    }
+}
+
+fn default_batch_buffer_size() -> String {
+    "10000".into()
+}
+
+fn default_batch_max_size() -> String {
+    "400".into()
+}
+
+fn default_batch_max_wait() -> String {
+    "30s".into()
+}
+
 #[derive(Clone, Serialize, Deserialize, JsonSchema, TypedBuilder)]
 pub struct AuditLogConfig {
     pub enabled: bool,
@@ -37,6 +92,15 @@ pub struct AuditLogConfig {
 
     #[serde(rename = "maxSize")]
     pub max_size: String,
+
+    /// Base64 encoded audit policy which replaces the default one.
+    #[serde(default)]
+    #[builder(default)]
+    pub policy: String,
+
+    #[serde(default)]
+    #[builder(default)]
+    pub webhook: AuditWebhookConfig,
 }
 
 #[derive(Serialize, Deserialize, ClusterFeatureValues)]
@@ -50,20 +114,85 @@ pub struct Feature {}
 
 impl ClusterFeaturePatches for Feature {
     fn patches(&self) -> Vec<ClusterClassPatches> {
+        let selector = ClusterClassPatchesDefinitionsSelector {
+            api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+            kind: KubeadmControlPlaneTemplate::api_resource().kind,
+            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                control_plane: Some(true),
+                ..Default::default()
+            },
+        };
+
         vec![
             ClusterClassPatches {
-                name: "auditLog".into(),
-                enabled_if: Some("{{ if .auditLog.enabled }}true{{end}}".into()),
+                name: "auditPolicy".into(),
+                enabled_if: Some("{{ if or .auditLog.enabled .auditLog.webhook.enabled }}true{{end}}".into()),
                 definitions: Some(vec![
                     ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
-                            kind: KubeadmControlPlaneTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                control_plane: Some(true),
+                        selector: selector.clone(),
+                        json_patches: vec![
+                            ClusterClassPatchesDefinitionsJsonPatches {
+                                op: "add".into(),
+                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/audit-policy-file".into(),
+                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                    template: Some("/etc/kubernetes/audit-policy/{{ if .auditLog.policy }}custom{{ else }}apiserver{{ end }}-audit-policy.yaml".into()),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            },
+                            ClusterClassPatchesDefinitionsJsonPatches {
+                                op: "add".into(),
+                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraVolumes/-".into(),
+                                value: Some(json!(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes {
+                                    name: "audit-policy".to_string(),
+                                    host_path: "/etc/kubernetes/audit-policy".to_string(),
+                                    mount_path: "/etc/kubernetes/audit-policy".to_string(),
+                                    ..Default::default()
+                                })),
                                 ..Default::default()
                             },
-                        },
+                        ],
+                    }
+                ]),
+                ..Default::default()
+            },
+            ClusterClassPatches {
+                name: "auditPolicyCustom".into(),
+                enabled_if: Some("{{ if and (or .auditLog.enabled .auditLog.webhook.enabled) .auditLog.policy }}true{{end}}".into()),
+                definitions: Some(vec![
+                    ClusterClassPatchesDefinitions {
+                        selector: selector.clone(),
+                        json_patches: vec![
+                            ClusterClassPatchesDefinitionsJsonPatches {
+                                op: "add".into(),
+                                path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                    template: Some(
+                                        serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+                                            path: "/etc/kubernetes/audit-policy/custom-audit-policy.yaml".to_string(),
+                                            permissions: Some("0600".to_string()),
+                                            encoding: Some(
+                                                KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding::Base64,
+                                            ),
+                                            content: Some("{{ .auditLog.policy }}".to_string()),
+                                            ..Default::default()
+                                        }).unwrap(),
+                                    ),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            },
+                        ],
+                    }
+                ]),
+                ..Default::default()
+            },
+            ClusterClassPatches {
+                name: "auditLog".into(),
+                enabled_if: Some("{{ if .auditLog.enabled }}true{{end}}".into()),
+                definitions: Some(vec![
+                    ClusterClassPatchesDefinitions {
+                        selector: selector.clone(),
                         json_patches: vec![
                             ClusterClassPatchesDefinitionsJsonPatches {
                                 op: "add".into(),
@@ -100,38 +229,106 @@ impl ClusterFeaturePatches for Feature {
                             },
                             ClusterClassPatchesDefinitionsJsonPatches {
                                 op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/audit-policy-file".into(),
-                                value: Some("/etc/kubernetes/audit-policy/apiserver-audit-policy.yaml".into()),
+                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraVolumes/-".into(),
+                                value: Some(json!(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes {
+                                    name: "audit-logs".to_string(),
+                                    host_path: "/var/log/kubernetes/audit".to_string(),
+                                    mount_path: "/var/log/audit".to_string(),
+                                    ..Default::default()
+                                })),
                                 ..Default::default()
                             },
+                        ],
+                    }
+
+                ]),
+                ..Default::default()
+            },
+            ClusterClassPatches {
+                name: "auditWebhook".into(),
+                enabled_if: Some("{{ if .auditLog.webhook.enabled }}true{{end}}".into()),
+                definitions: Some(vec![
+                    ClusterClassPatchesDefinitions {
+                        selector,
+                        json_patches: vec![
                             ClusterClassPatchesDefinitionsJsonPatches {
                                 op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraVolumes/-".into(),
-                                value: Some(json!(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes {
-                                    name: "audit-policy".to_string(),
-                                    host_path: "/etc/kubernetes/audit-policy".to_string(),
-                                    mount_path: "/etc/kubernetes/audit-policy".to_string(),
+                                path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                    template: Some(
+                                        serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+                                            path: "/etc/kubernetes/audit-webhook/kubeconfig.yaml".to_string(),
+                                            permissions: Some("0600".to_string()),
+                                            encoding: Some(
+                                                KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding::Base64,
+                                            ),
+                                            content: Some("{{ .auditLog.webhook.kubeconfig }}".to_string()),
+                                            ..Default::default()
+                                        }).unwrap(),
+                                    ),
                                     ..Default::default()
-                                })),
+                                }),
+                                ..Default::default()
+                            },
+                            ClusterClassPatchesDefinitionsJsonPatches {
+                                op: "add".into(),
+                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/audit-webhook-config-file".into(),
+                                value: Some("/etc/kubernetes/audit-webhook/kubeconfig.yaml".into()),
+                                ..Default::default()
+                            },
+                            ClusterClassPatchesDefinitionsJsonPatches {
+                                op: "add".into(),
+                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/audit-webhook-mode".into(),
+                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                    variable: Some("auditLog.webhook.mode".into()),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            },
+                            ClusterClassPatchesDefinitionsJsonPatches {
+                                op: "add".into(),
+                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/audit-webhook-batch-buffer-size".into(),
+                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                    variable: Some("auditLog.webhook.batchBufferSize".into()),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            },
+                            ClusterClassPatchesDefinitionsJsonPatches {
+                                op: "add".into(),
+                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/audit-webhook-batch-max-size".into(),
+                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                    variable: Some("auditLog.webhook.batchMaxSize".into()),
+                                    ..Default::default()
+                                }),
+                                ..Default::default()
+                            },
+                            ClusterClassPatchesDefinitionsJsonPatches {
+                                op: "add".into(),
+                                path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs/audit-webhook-batch-max-wait".into(),
+                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                    variable: Some("auditLog.webhook.batchMaxWait".into()),
+                                    ..Default::default()
+                                }),
                                 ..Default::default()
                             },
                             ClusterClassPatchesDefinitionsJsonPatches {
                                 op: "add".into(),
                                 path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraVolumes/-".into(),
                                 value: Some(json!(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes {
-                                    name: "audit-logs".to_string(),
-                                    host_path: "/var/log/kubernetes/audit".to_string(),
-                                    mount_path: "/var/log/audit".to_string(),
+                                    name: "audit-webhook".to_string(),
+                                    host_path: "/etc/kubernetes/audit-webhook".to_string(),
+                                    mount_path: "/etc/kubernetes/audit-webhook".to_string(),
+                                    read_only: Some(true),
                                     ..Default::default()
                                 })),
                                 ..Default::default()
                             },
                         ],
                     }
-
                 ]),
                 ..Default::default()
-            }
+            },
         ]
     }
 }
@@ -241,4 +438,133 @@ mod tests {
             ..Default::default()
         }));
     }
+
+    #[test]
+    fn test_custom_policy() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.audit_log = AuditLogConfig::builder()
+            .enabled(true)
+            .max_age("30".into())
+            .max_backup("10".into())
+            .max_size("100".into())
+            .policy("YXBpVmVyc2lvbjogYXVkaXQuazhzLmlvL3YxCmtpbmQ6IFBvbGljeQo=".into())
+            .build();
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let kubeadm_config_spec = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+
+        let file = kubeadm_config_spec
+            .files
+            .expect("files should be set")
+            .into_iter()
+            .find(|f| f.path == "/etc/kubernetes/audit-policy/custom-audit-policy.yaml")
+            .expect("custom audit policy should be set");
+
+        assert_eq!(file.content, Some(values.audit_log.policy));
+        assert_eq!(
+            file.encoding,
+            Some(KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding::Base64)
+        );
+        assert_eq!(
+            kubeadm_config_spec
+                .cluster_configuration
+                .expect("cluster_configuration should be set")
+                .api_server
+                .expect("api_server should be set")
+                .extra_args
+                .expect("extra_args should be set")
+                .get("audit-policy-file"),
+            Some(&"/etc/kubernetes/audit-policy/custom-audit-policy.yaml".to_string())
+        );
+    }
+
+    #[test]
+    fn test_webhook() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.audit_log = AuditLogConfig::builder()
+            .enabled(false)
+            .max_age("30".into())
+            .max_backup("10".into())
+            .max_size("100".into())
+            .webhook(
+                AuditWebhookConfig::builder()
+                    .enabled(true)
+                    .kubeconfig("a3ViZWNvbmZpZw==".into())
+                    .mode(AuditWebhookMode::BlockingStrict)
+                    .batch_max_wait("5s".into())
+                    .build(),
+            )
+            .build();
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let kubeadm_config_spec = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+
+        let file = kubeadm_config_spec
+            .files
+            .expect("files should be set")
+            .into_iter()
+            .find(|f| f.path == "/etc/kubernetes/audit-webhook/kubeconfig.yaml")
+            .expect("audit webhook kubeconfig should be set");
+
+        assert_eq!(file.content, Some("a3ViZWNvbmZpZw==".to_string()));
+        assert_eq!(file.permissions, Some("0600".to_string()));
+
+        let api_server = kubeadm_config_spec
+            .cluster_configuration
+            .expect("cluster_configuration should be set")
+            .api_server
+            .expect("api_server should be set");
+
+        assert_eq!(
+            &btreemap! {
+                "audit-policy-file".to_string() => "/etc/kubernetes/audit-policy/apiserver-audit-policy.yaml".to_string(),
+                "audit-webhook-batch-buffer-size".to_string() => "10000".to_string(),
+                "audit-webhook-batch-max-size".to_string() => "400".to_string(),
+                "audit-webhook-batch-max-wait".to_string() => "5s".to_string(),
+                "audit-webhook-config-file".to_string() => "/etc/kubernetes/audit-webhook/kubeconfig.yaml".to_string(),
+                "audit-webhook-mode".to_string() => "blocking-strict".to_string(),
+                "profiling".to_string() => "false".to_string(),
+            },
+            &api_server.extra_args.expect("extra_args should be set"),
+        );
+
+        let extra_volumes = api_server
+            .extra_volumes
+            .expect("extra_volumes should be set");
+
+        assert!(extra_volumes.contains(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes {
+            name: "audit-policy".to_string(),
+            host_path: "/etc/kubernetes/audit-policy".to_string(),
+            mount_path: "/etc/kubernetes/audit-policy".to_string(),
+            ..Default::default()
+        }));
+        assert!(extra_volumes.contains(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecClusterConfigurationApiServerExtraVolumes {
+            name: "audit-webhook".to_string(),
+            host_path: "/etc/kubernetes/audit-webhook".to_string(),
+            mount_path: "/etc/kubernetes/audit-webhook".to_string(),
+            read_only: Some(true),
+            ..Default::default()
+        }));
+        assert!(!extra_volumes.iter().any(|v| v.name == "audit-logs"));
+    }
 }
@@ -0,0 +1,249 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources, ClusterClassVariables,
+            ClusterClassVariablesSchema,
+        },
+        openstackclustertemplates::OpenStackClusterTemplate,
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use cluster_feature_derive::ClusterFeatureValues;
+use indoc::indoc;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+/// Configuration of the bastion host which is used to reach the nodes of
+/// clusters without floating IPs.
+///
+/// CAPO refuses to remove the bastion from the cluster spec until it has been
+/// disabled and deleted, so `managed` keeps the bastion rendered with
+/// `enabled: false` once it has been created.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct BastionConfig {
+    #[builder(default)]
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[builder(default)]
+    #[serde(default)]
+    pub managed: bool,
+
+    #[builder(default)]
+    #[serde(default)]
+    #[schemars(regex(pattern = r#"^[^"\\]*$"#))]
+    pub flavor: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "imageUUID")]
+    #[schemars(regex(pattern = r"^([0-9a-fA-F-]{36})?$"))]
+    pub image_uuid: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "sshKeyName")]
+    #[schemars(regex(pattern = r#"^[^"\\]*$"#))]
+    pub ssh_key_name: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "availabilityZone")]
+    #[schemars(regex(pattern = r#"^[^"\\]*$"#))]
+    pub availability_zone: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "floatingIP")]
+    #[schemars(regex(pattern = r"^[0-9a-fA-F.:]*$"))]
+    pub floating_ip: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "securityGroups")]
+    #[schemars(inner(regex(pattern = r#"^[^"\\]+$"#)))]
+    pub security_groups: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "bastion")]
+    pub bastion: BastionConfig,
+}
+
+const BASTION_TEMPLATE: &str = indoc! {r#"
+    enabled: {{ if .bastion.enabled }}true{{ else }}false{{ end }}
+    {{- if .bastion.availabilityZone }}
+    availabilityZone: "{{ .bastion.availabilityZone }}"
+    {{- end }}
+    {{- if .bastion.floatingIP }}
+    floatingIP: "{{ .bastion.floatingIP }}"
+    {{- end }}
+    spec:
+      flavor: "{{ .bastion.flavor }}"
+      image:
+        id: "{{ .bastion.imageUUID }}"
+      {{- if .bastion.sshKeyName }}
+      sshKeyName: "{{ .bastion.sshKeyName }}"
+      {{- end }}
+      {{- if .bastion.securityGroups }}
+      securityGroups:
+        {{- range .bastion.securityGroups }}
+        - filter:
+            name: "{{ . }}"
+        {{- end }}
+      {{- end }}
+"#};
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        vec![ClusterClassPatches {
+            name: "bastion".into(),
+            enabled_if: Some("{{ if or .bastion.enabled .bastion.managed }}true{{end}}".into()),
+            definitions: Some(vec![ClusterClassPatchesDefinitions {
+                selector: ClusterClassPatchesDefinitionsSelector {
+                    api_version: OpenStackClusterTemplate::api_resource().api_version,
+                    kind: OpenStackClusterTemplate::api_resource().kind,
+                    match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                        infrastructure_cluster: Some(true),
+                        ..Default::default()
+                    },
+                },
+                json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                    op: "add".into(),
+                    path: "/spec/template/spec/bastion".into(),
+                    value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                        template: Some(BASTION_TEMPLATE.into()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+            }]),
+            ..Default::default()
+        }]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cluster_api::openstackclustertemplates::{
+            OpenStackClusterTemplateTemplateSpecBastion,
+            OpenStackClusterTemplateTemplateSpecBastionSpec,
+            OpenStackClusterTemplateTemplateSpecBastionSpecImage,
+            OpenStackClusterTemplateTemplateSpecBastionSpecSecurityGroups,
+            OpenStackClusterTemplateTemplateSpecBastionSpecSecurityGroupsFilter,
+        },
+        features::test::TestClusterResources,
+        resources::fixtures::default_values,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_disabled() {
+        let feature = Feature {};
+
+        let values = default_values();
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources.openstack_cluster_template.spec.template.spec.bastion,
+            None
+        );
+    }
+
+    #[test]
+    fn test_enabled() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.bastion = BastionConfig::builder()
+            .enabled(true)
+            .flavor("m1.small".into())
+            .image_uuid("b6a4e1c0-3f2d-4c5b-8a9e-7d6c5b4a3f2e".into())
+            .ssh_key_name("my-key".into())
+            .availability_zone("az1".into())
+            .security_groups(vec!["ssh-from-office".into()])
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources.openstack_cluster_template.spec.template.spec.bastion,
+            Some(OpenStackClusterTemplateTemplateSpecBastion {
+                enabled: Some(true),
+                availability_zone: Some("az1".into()),
+                spec: Some(OpenStackClusterTemplateTemplateSpecBastionSpec {
+                    flavor: Some("m1.small".into()),
+                    image: OpenStackClusterTemplateTemplateSpecBastionSpecImage {
+                        id: Some("b6a4e1c0-3f2d-4c5b-8a9e-7d6c5b4a3f2e".into()),
+                        ..Default::default()
+                    },
+                    ssh_key_name: Some("my-key".into()),
+                    security_groups: Some(vec![
+                        OpenStackClusterTemplateTemplateSpecBastionSpecSecurityGroups {
+                            filter: Some(
+                                OpenStackClusterTemplateTemplateSpecBastionSpecSecurityGroupsFilter {
+                                    name: Some("ssh-from-office".into()),
+                                    ..Default::default()
+                                }
+                            ),
+                            ..Default::default()
+                        }
+                    ]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_disabled_after_being_managed() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.bastion = BastionConfig::builder()
+            .managed(true)
+            .flavor("m1.small".into())
+            .image_uuid("b6a4e1c0-3f2d-4c5b-8a9e-7d6c5b4a3f2e".into())
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let bastion = resources
+            .openstack_cluster_template
+            .spec
+            .template
+            .spec
+            .bastion
+            .expect("bastion should be set");
+
+        assert_eq!(bastion.enabled, Some(false));
+        assert_eq!(
+            bastion.spec.expect("bastion spec should be set").flavor,
+            Some("m1.small".into())
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources,
+            ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass,
+            ClusterClassVariables, ClusterClassVariablesSchema,
+        },
+        kubeadmconfigtemplates::KubeadmConfigTemplate,
+        kubeadmcontrolplanetemplates::{
+            KubeadmControlPlaneTemplate,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding,
+        },
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use cluster_feature_derive::ClusterFeatureValues;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use typed_builder::TypedBuilder;
+
+const PRE_KUBEADM_SCRIPT: &str = "/etc/kubernetes/hooks/pre-kubeadm.sh";
+const POST_KUBEADM_SCRIPT: &str = "/etc/kubernetes/hooks/post-kubeadm.sh";
+
+/// Commands and files of the user for the nodes, which the driver renders into
+/// scripts running before and after kubeadm.  The scripts are base64 encoded
+/// so that any content can be passed through the templates.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct BootstrapHooksConfig {
+    /// Whether any commands or files are set.
+    #[builder(default)]
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Script writing the extra files and running the extra commands before kubeadm.
+    #[builder(default)]
+    #[serde(default, rename = "preKubeadmScript")]
+    #[schemars(regex(pattern = r"^[A-Za-z0-9+/]*={0,2}$"))]
+    pub pre_kubeadm_script: String,
+
+    /// Script running the extra commands after kubeadm.
+    #[builder(default)]
+    #[serde(default, rename = "postKubeadmScript")]
+    #[schemars(regex(pattern = r"^[A-Za-z0-9+/]*={0,2}$"))]
+    pub post_kubeadm_script: String,
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "bootstrapHooks")]
+    pub bootstrap_hooks: BootstrapHooksConfig,
+}
+
+/// Returns `true` for the patches of this feature, which append to the lists of
+/// commands created by the other features and have to be applied after them.
+pub fn is_bootstrap_hooks_patch(patch: &ClusterClassPatches) -> bool {
+    patch.name.starts_with("bootstrapHooks")
+}
+
+/// Returns the patches for the kubeadm config at `path`.  The files have the
+/// same schema in the `KubeadmControlPlaneTemplate` and the
+/// `KubeadmConfigTemplate`, which is why they are rendered as templates.
+fn json_patches(path: &str) -> Vec<ClusterClassPatchesDefinitionsJsonPatches> {
+    [
+        (PRE_KUBEADM_SCRIPT, "{{ .bootstrapHooks.preKubeadmScript }}"),
+        (POST_KUBEADM_SCRIPT, "{{ .bootstrapHooks.postKubeadmScript }}"),
+    ]
+    .iter()
+    .map(|(script, content)| ClusterClassPatchesDefinitionsJsonPatches {
+        op: "add".into(),
+        path: format!("{}/files/-", path),
+        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+            template: Some(
+                serde_yaml::to_string(
+                    &KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+                        path: script.to_string(),
+                        owner: Some("root:root".into()),
+                        permissions: Some("0700".to_string()),
+                        content: Some(content.to_string()),
+                        encoding: Some(
+                            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding::Base64,
+                        ),
+                        ..Default::default()
+                    },
+                )
+                .unwrap(),
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+    .collect()
+}
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        let mut control_plane_patches = json_patches("/spec/template/spec/kubeadmConfigSpec");
+        control_plane_patches.extend([
+            ClusterClassPatchesDefinitionsJsonPatches {
+                op: "add".into(),
+                path: "/spec/template/spec/kubeadmConfigSpec/preKubeadmCommands/-".into(),
+                value: Some(format!("bash {}", PRE_KUBEADM_SCRIPT).into()),
+                ..Default::default()
+            },
+            ClusterClassPatchesDefinitionsJsonPatches {
+                op: "add".into(),
+                path: "/spec/template/spec/kubeadmConfigSpec/postKubeadmCommands/-".into(),
+                value: Some(format!("bash {}", POST_KUBEADM_SCRIPT).into()),
+                ..Default::default()
+            },
+        ]);
+
+        // NOTE: No other feature adds commands after kubeadm on the workers,
+        //       so the list is created here instead of being appended to.
+        let mut worker_patches = json_patches("/spec/template/spec");
+        worker_patches.extend([
+            ClusterClassPatchesDefinitionsJsonPatches {
+                op: "add".into(),
+                path: "/spec/template/spec/preKubeadmCommands/-".into(),
+                value: Some(format!("bash {}", PRE_KUBEADM_SCRIPT).into()),
+                ..Default::default()
+            },
+            ClusterClassPatchesDefinitionsJsonPatches {
+                op: "add".into(),
+                path: "/spec/template/spec/postKubeadmCommands".into(),
+                value: Some(json!([format!("bash {}", POST_KUBEADM_SCRIPT)])),
+                ..Default::default()
+            },
+        ]);
+
+        vec![
+            ClusterClassPatches {
+                name: "bootstrapHooksControlPlane".into(),
+                enabled_if: Some("{{ if .bootstrapHooks.enabled }}true{{end}}".into()),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+                        kind: KubeadmControlPlaneTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            control_plane: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: control_plane_patches,
+                }]),
+                ..Default::default()
+            },
+            // NOTE: The scripts are always added to the workers since the node
+            //       groups override `bootstrapHooks`, see [`ClusterFeaturePatches`]
+            //       for why, and they are empty for the node groups without hooks.
+            ClusterClassPatches {
+                name: "bootstrapHooksWorker".into(),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: KubeadmConfigTemplate::api_resource().api_version,
+                        kind: KubeadmConfigTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            machine_deployment_class: Some(
+                                ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
+                                    names: Some(vec!["default-worker".to_string()]),
+                                },
+                            ),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: worker_patches,
+                }]),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{features::test::TestClusterResources, resources::fixtures::default_values};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_disabled() {
+        let feature = Feature {};
+
+        let values = default_values();
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(
+            &crate::features::containerd_config::Feature {}.patches(),
+            &values,
+        );
+        let kubeadm_control_plane_template = resources.kubeadm_control_plane_template.clone();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources.kubeadm_control_plane_template,
+            kubeadm_control_plane_template
+        );
+
+        let spec = resources
+            .kubeadm_config_template
+            .spec
+            .template
+            .spec
+            .expect("spec should be set");
+
+        let files = spec.files.expect("files should be set");
+        for script in [PRE_KUBEADM_SCRIPT, POST_KUBEADM_SCRIPT] {
+            let file = files
+                .iter()
+                .find(|file| file.path == script)
+                .expect("script should be added");
+            assert_eq!(file.content, Some("".into()));
+        }
+        assert_eq!(
+            spec.post_kubeadm_commands,
+            Some(vec!["bash /etc/kubernetes/hooks/post-kubeadm.sh".into()])
+        );
+    }
+
+    #[test]
+    fn test_enabled() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.bootstrap_hooks = BootstrapHooksConfig::builder()
+            .enabled(true)
+            .pre_kubeadm_script("ZWNobyBwcmUK".into())
+            .post_kubeadm_script("ZWNobyBwb3N0Cg==".into())
+            .build();
+
+        // NOTE: The commands before kubeadm are created by the `containerdConfig`
+        //       patch on the workers, which runs before this one.
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(
+            &crate::features::containerd_config::Feature {}.patches(),
+            &values,
+        );
+        resources.apply_patches(&feature.patches(), &values);
+
+        let kubeadm_config_spec = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+
+        let files = kubeadm_config_spec.files.expect("files should be set");
+        let pre_kubeadm_script = files
+            .iter()
+            .find(|file| file.path == PRE_KUBEADM_SCRIPT)
+            .expect("pre kubeadm script should be added");
+        assert_eq!(pre_kubeadm_script.content, Some("ZWNobyBwcmUK".into()));
+        assert_eq!(pre_kubeadm_script.permissions, Some("0700".into()));
+        let post_kubeadm_script = files
+            .iter()
+            .find(|file| file.path == POST_KUBEADM_SCRIPT)
+            .expect("post kubeadm script should be added");
+        assert_eq!(post_kubeadm_script.content, Some("ZWNobyBwb3N0Cg==".into()));
+
+        assert_eq!(
+            kubeadm_config_spec
+                .pre_kubeadm_commands
+                .and_then(|commands| commands.last().cloned()),
+            Some("bash /etc/kubernetes/hooks/pre-kubeadm.sh".into())
+        );
+        assert_eq!(
+            kubeadm_config_spec.post_kubeadm_commands,
+            Some(vec![
+                "echo PLACEHOLDER".into(),
+                "bash /etc/kubernetes/hooks/post-kubeadm.sh".into(),
+            ])
+        );
+
+        let spec = resources
+            .kubeadm_config_template
+            .spec
+            .template
+            .spec
+            .expect("spec should be set");
+
+        let files = spec.files.expect("files should be set");
+        assert!(files.iter().any(|file| file.path == PRE_KUBEADM_SCRIPT));
+        assert!(files.iter().any(|file| file.path == POST_KUBEADM_SCRIPT));
+        assert_eq!(
+            spec.pre_kubeadm_commands,
+            Some(vec![
+                "systemctl daemon-reload".into(),
+                "systemctl restart containerd".into(),
+                "bash /etc/kubernetes/hooks/pre-kubeadm.sh".into(),
+            ])
+        );
+        assert_eq!(
+            spec.post_kubeadm_commands,
+            Some(vec!["bash /etc/kubernetes/hooks/post-kubeadm.sh".into()])
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources,
+            ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass,
+            ClusterClassVariables, ClusterClassVariablesSchema,
+        },
+        kubeadmconfigtemplates::{KubeadmConfigTemplate, KubeadmConfigTemplateTemplateSpecFiles},
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use cluster_feature_derive::ClusterFeatureValues;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the node labels which mark the nodes having a runtime handler,
+/// it is a subdomain of `node.cluster.x-k8s.io` so that Cluster API syncs the
+/// labels of the machines to their nodes.
+pub const NODE_LABEL_PREFIX: &str = "runtime.node.cluster.x-k8s.io";
+
+/// Extra runtime handlers for containerd, next to the default `runc` one, which
+/// run the containers in a sandbox.  The shims of the runtimes have to be
+/// installed in the image of the nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerRuntime {
+    /// gVisor
+    Runsc,
+
+    /// Kata Containers
+    Kata,
+}
+
+impl ContainerRuntime {
+    pub const ALL: [ContainerRuntime; 2] = [ContainerRuntime::Runsc, ContainerRuntime::Kata];
+
+    /// Name of the runtime handler in the containerd configuration.
+    pub fn handler(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Runsc => "runsc",
+            ContainerRuntime::Kata => "kata",
+        }
+    }
+
+    /// Name of the `RuntimeClass` which the workloads refer to.
+    pub fn runtime_class_name(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Runsc => "gvisor",
+            ContainerRuntime::Kata => "kata",
+        }
+    }
+
+    fn runtime_type(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Runsc => "io.containerd.runsc.v1",
+            ContainerRuntime::Kata => "io.containerd.kata.v2",
+        }
+    }
+
+    /// Label of the nodes which have the runtime handler configured.
+    pub fn node_label(&self) -> String {
+        format!("{}/{}", NODE_LABEL_PREFIX, self.handler())
+    }
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "containerRuntimes")]
+    pub container_runtimes: Vec<ContainerRuntime>,
+}
+
+/// Returns a Go template which renders the containerd drop-in with the runtime
+/// handlers listed in `containerRuntimes`, it is picked up by the `imports` of
+/// the main configuration.
+fn containerd_drop_in_template() -> String {
+    let mut template = String::from("version = 2\n{{- range .containerRuntimes }}\n");
+    for runtime in ContainerRuntime::ALL {
+        template.push_str(&format!(
+            concat!(
+                "{{{{- if eq . \"{handler}\" }}}}\n",
+                "[plugins.\"io.containerd.grpc.v1.cri\".containerd.runtimes.{handler}]\n",
+                "  runtime_type = \"{runtime_type}\"\n",
+                "{{{{- end }}}}\n",
+            ),
+            handler = runtime.handler(),
+            runtime_type = runtime.runtime_type(),
+        ));
+    }
+    template.push_str("{{- end }}\n");
+
+    template
+}
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        // NOTE: The drop-in is always written since runtimes can be set per
+        //       node group, see [`ClusterFeaturePatches`].  The control plane
+        //       is left out since it does not run workloads.
+        vec![ClusterClassPatches {
+            name: "containerRuntimes".into(),
+            definitions: Some(vec![ClusterClassPatchesDefinitions {
+                selector: ClusterClassPatchesDefinitionsSelector {
+                    api_version: KubeadmConfigTemplate::api_resource().api_version,
+                    kind: KubeadmConfigTemplate::api_resource().kind,
+                    match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                        machine_deployment_class: Some(
+                            ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
+                                names: Some(vec!["default-worker".to_string()]),
+                            },
+                        ),
+                        ..Default::default()
+                    },
+                },
+                json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                    op: "add".into(),
+                    path: "/spec/template/spec/files/-".into(),
+                    value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                        template: Some(
+                            serde_yaml::to_string(&KubeadmConfigTemplateTemplateSpecFiles {
+                                path: "/etc/containerd/conf.d/runtimes.toml".to_string(),
+                                owner: Some("root:root".into()),
+                                permissions: Some("0644".to_string()),
+                                content: Some(containerd_drop_in_template()),
+                                ..Default::default()
+                            })
+                            .unwrap(),
+                        ),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+            }]),
+            ..Default::default()
+        }]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        features::{test::TestClusterResources, KUBEADM_CONTROL_PLANE_TEMPLATE},
+        resources::fixtures::default_values,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn drop_in(resources: &TestClusterResources) -> Option<String> {
+        resources
+            .kubeadm_config_template
+            .spec
+            .template
+            .spec
+            .as_ref()
+            .expect("spec should be set")
+            .files
+            .as_ref()
+            .expect("files should be set")
+            .iter()
+            .find(|file| file.path == "/etc/containerd/conf.d/runtimes.toml")
+            .expect("drop-in should be added")
+            .content
+            .clone()
+    }
+
+    #[test]
+    fn test_patches_with_no_runtimes() {
+        let feature = Feature {};
+
+        let values = default_values();
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(drop_in(&resources), Some("version = 2\n".to_string()));
+        assert_eq!(
+            resources.kubeadm_control_plane_template,
+            KUBEADM_CONTROL_PLANE_TEMPLATE.clone()
+        );
+    }
+
+    #[test]
+    fn test_patches_with_runtimes() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.container_runtimes = vec![ContainerRuntime::Runsc, ContainerRuntime::Kata];
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            drop_in(&resources),
+            Some(
+                concat!(
+                    "version = 2\n",
+                    "[plugins.\"io.containerd.grpc.v1.cri\".containerd.runtimes.runsc]\n",
+                    "  runtime_type = \"io.containerd.runsc.v1\"\n",
+                    "[plugins.\"io.containerd.grpc.v1.cri\".containerd.runtimes.kata]\n",
+                    "  runtime_type = \"io.containerd.kata.v2\"\n",
+                )
+                .to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources, ClusterClassVariables,
+            ClusterClassVariablesSchema,
+        },
+        kubeadmcontrolplanetemplates::{
+            KubeadmControlPlaneTemplate,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding,
+        },
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use base64::prelude::*;
+use cluster_feature_derive::ClusterFeatureValues;
+use indoc::indoc;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use typed_builder::TypedBuilder;
+
+const DEFRAG_TIMER: &str = indoc!(
+    r#"
+    [Unit]
+    Description=Periodically defragment the local etcd member
+
+    [Timer]
+    OnCalendar={{ .etcdConfig.defrag.schedule }}
+    RandomizedDelaySec=1h
+    Persistent=true
+
+    [Install]
+    WantedBy=timers.target
+    "#
+);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct EtcdDefragConfig {
+    #[builder(default)]
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Calendar event expression of the systemd timer running the defragmentation.
+    #[builder(default = "daily".into())]
+    #[serde(default = "default_defrag_schedule")]
+    #[schemars(regex(pattern = r"^[A-Za-z0-9*:,./~ -]+$"))]
+    pub schedule: String,
+}
+
+impl Default for EtcdDefragConfig {
+    fn default() -> Self {
+        EtcdDefragConfig::builder().build()
+    }
+}
+
+fn default_defrag_schedule() -> String {
+    "daily".into()
+}
+
+/// Tuning of the local etcd members, where a zero or empty value keeps the
+/// default of etcd.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct EtcdConfig {
+    #[builder(default)]
+    #[serde(default, rename = "quotaBackendBytes")]
+    #[schemars(range(min = 0, max = 8_589_934_592_i64))]
+    pub quota_backend_bytes: i64,
+
+    #[builder(default)]
+    #[serde(default, rename = "snapshotCount")]
+    #[schemars(range(min = 0))]
+    pub snapshot_count: i64,
+
+    /// Time (in milliseconds) of a heartbeat interval.
+    #[builder(default)]
+    #[serde(default, rename = "heartbeatInterval")]
+    #[schemars(range(min = 0))]
+    pub heartbeat_interval: i64,
+
+    /// Time (in milliseconds) for an election to timeout.
+    #[builder(default)]
+    #[serde(default, rename = "electionTimeout")]
+    #[schemars(range(min = 0))]
+    pub election_timeout: i64,
+
+    #[builder(default)]
+    #[serde(default, rename = "autoCompactionMode")]
+    #[schemars(regex(pattern = r"^(periodic|revision)?$"))]
+    pub auto_compaction_mode: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "autoCompactionRetention")]
+    #[schemars(regex(pattern = r"^[0-9a-z.]*$"))]
+    pub auto_compaction_retention: String,
+
+    #[builder(default)]
+    #[serde(default)]
+    pub defrag: EtcdDefragConfig,
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "etcdConfig")]
+    pub etcd_config: EtcdConfig,
+}
+
+/// Patch name, etcd flag and field of the `etcdConfig` variable for every flag
+/// which is only set when the field is not empty.
+const FLAGS: &[(&str, &str, &str)] = &[
+    ("etcdQuotaBackendBytes", "quota-backend-bytes", "quotaBackendBytes"),
+    ("etcdSnapshotCount", "snapshot-count", "snapshotCount"),
+    ("etcdHeartbeatInterval", "heartbeat-interval", "heartbeatInterval"),
+    ("etcdElectionTimeout", "election-timeout", "electionTimeout"),
+    ("etcdAutoCompactionMode", "auto-compaction-mode", "autoCompactionMode"),
+    (
+        "etcdAutoCompactionRetention",
+        "auto-compaction-retention",
+        "autoCompactionRetention",
+    ),
+];
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        let selector = ClusterClassPatchesDefinitionsSelector {
+            api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+            kind: KubeadmControlPlaneTemplate::api_resource().kind,
+            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                control_plane: Some(true),
+                ..Default::default()
+            },
+        };
+
+        let mut patches: Vec<ClusterClassPatches> = FLAGS
+            .iter()
+            .map(|(name, flag, field)| ClusterClassPatches {
+                name: name.to_string(),
+                enabled_if: Some(format!("{{{{ if .etcdConfig.{} }}}}true{{{{end}}}}", field)),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: selector.clone(),
+                    json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: format!("/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/etcd/local/extraArgs/{}", flag),
+                        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                            template: Some(format!("\"{{{{ .etcdConfig.{} }}}}\"", field)),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }],
+                }]),
+                ..Default::default()
+            })
+            .collect();
+
+        patches.push(ClusterClassPatches {
+            name: "etcdDefrag".into(),
+            enabled_if: Some("{{ if .etcdConfig.defrag.enabled }}true{{end}}".into()),
+            definitions: Some(vec![ClusterClassPatchesDefinitions {
+                selector,
+                json_patches: vec![
+                    ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                        value: Some(json!(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+                            path: "/etc/kubernetes/etcd-defrag.sh".to_string(),
+                            owner: Some("root:root".into()),
+                            permissions: Some("0755".to_string()),
+                            content: Some(
+                                BASE64_STANDARD.encode(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/etcd/etcd-defrag.sh")))
+                            ),
+                            encoding: Some(KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding::Base64),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    },
+                    ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                        value: Some(json!(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+                            path: "/etc/systemd/system/etcd-defrag.service".to_string(),
+                            owner: Some("root:root".into()),
+                            permissions: Some("0644".to_string()),
+                            content: Some(
+                                BASE64_STANDARD.encode(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/etcd/etcd-defrag.service")))
+                            ),
+                            encoding: Some(KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding::Base64),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    },
+                    ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                            template: Some(
+                                serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+                                    path: "/etc/systemd/system/etcd-defrag.timer".to_string(),
+                                    owner: Some("root:root".into()),
+                                    permissions: Some("0644".to_string()),
+                                    content: Some(DEFRAG_TIMER.to_string()),
+                                    ..Default::default()
+                                }).unwrap(),
+                            ),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    },
+                    ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/kubeadmConfigSpec/postKubeadmCommands/-".into(),
+                        value: Some("systemctl daemon-reload && systemctl enable --now etcd-defrag.timer".into()),
+                        ..Default::default()
+                    },
+                ],
+            }]),
+            ..Default::default()
+        });
+
+        patches
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{features::test::TestClusterResources, resources::fixtures::default_values};
+    use maplit::btreemap;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_disabled() {
+        let feature = Feature {};
+
+        let values = default_values();
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources.kubeadm_control_plane_template,
+            crate::features::KUBEADM_CONTROL_PLANE_TEMPLATE.clone()
+        );
+    }
+
+    #[test]
+    fn test_tuning() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.etcd_config = EtcdConfig::builder()
+            .quota_backend_bytes(8589934592)
+            .snapshot_count(10000)
+            .heartbeat_interval(250)
+            .election_timeout(2500)
+            .auto_compaction_mode("periodic".into())
+            .auto_compaction_retention("8h".into())
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let kubeadm_config_spec = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+
+        assert_eq!(
+            kubeadm_config_spec
+                .cluster_configuration
+                .and_then(|cluster_configuration| cluster_configuration.etcd)
+                .and_then(|etcd| etcd.local)
+                .expect("local etcd should be set")
+                .extra_args,
+            Some(btreemap! {
+                "auto-compaction-mode".to_string() => "periodic".to_string(),
+                "auto-compaction-retention".to_string() => "8h".to_string(),
+                "election-timeout".to_string() => "2500".to_string(),
+                "heartbeat-interval".to_string() => "250".to_string(),
+                "listen-metrics-urls".to_string() => "http://0.0.0.0:2381".to_string(),
+                "quota-backend-bytes".to_string() => "8589934592".to_string(),
+                "snapshot-count".to_string() => "10000".to_string(),
+            })
+        );
+        assert_eq!(
+            kubeadm_config_spec.post_kubeadm_commands,
+            Some(vec!["echo PLACEHOLDER".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_defrag() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.etcd_config = EtcdConfig::builder()
+            .defrag(
+                EtcdDefragConfig::builder()
+                    .enabled(true)
+                    .schedule("*-*-* 03:00:00".into())
+                    .build(),
+            )
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let kubeadm_config_spec = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+
+        let timer = kubeadm_config_spec
+            .files
+            .expect("files should be set")
+            .into_iter()
+            .find(|file| file.path == "/etc/systemd/system/etcd-defrag.timer")
+            .expect("timer should be set");
+
+        assert_eq!(
+            timer.content,
+            Some(DEFRAG_TIMER.replace("{{ .etcdConfig.defrag.schedule }}", "*-*-* 03:00:00"))
+        );
+        assert_eq!(
+            kubeadm_config_spec.post_kubeadm_commands,
+            Some(vec![
+                "echo PLACEHOLDER".to_string(),
+                "systemctl daemon-reload && systemctl enable --now etcd-defrag.timer".to_string(),
+            ])
+        );
+    }
+}
@@ -0,0 +1,433 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources, ClusterClassVariables,
+            ClusterClassVariablesSchema,
+        },
+        kubeadmcontrolplanetemplates::{
+            KubeadmControlPlaneTemplate,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesContentFrom,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesContentFromSecret,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding,
+        },
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use base64::prelude::*;
+use cluster_feature_derive::ClusterFeatureValues;
+use indoc::indoc;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use typed_builder::TypedBuilder;
+
+const CONFIG: &str = indoc!(
+    r#"
+    ETCD_BACKUP_PREFIX={{ .etcdBackup.prefix }}
+    ETCD_BACKUP_RETENTION={{ .etcdBackup.retention }}
+    ETCD_BACKUP_RESTORE_SNAPSHOT={{ .etcdBackup.restore }}
+    "#
+);
+
+const BACKUP_TIMER: &str = indoc!(
+    r#"
+    [Unit]
+    Description=Periodically upload a snapshot of etcd to object storage
+
+    [Timer]
+    OnCalendar={{ .etcdBackup.schedule }}
+    RandomizedDelaySec=5m
+    Persistent=true
+
+    [Install]
+    WantedBy=timers.target
+    "#
+);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct EtcdBackupConfig {
+    #[builder(default)]
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Calendar event expression of the systemd timer taking the snapshots.
+    #[builder(default = "daily".into())]
+    #[serde(default = "default_schedule")]
+    #[schemars(regex(pattern = r"^[A-Za-z0-9*:,./~ -]+$"))]
+    pub schedule: String,
+
+    /// Number of snapshots which are kept in the object storage.
+    #[builder(default = 7)]
+    #[serde(default = "default_retention")]
+    #[schemars(range(min = 1))]
+    pub retention: i64,
+
+    /// Prefix of the snapshots of the cluster in the bucket.
+    #[builder(default)]
+    #[serde(default)]
+    #[schemars(regex(
+        pattern = r"^[A-Za-z0-9_-][A-Za-z0-9_.-]*(/[A-Za-z0-9_-][A-Za-z0-9_.-]*)*$|^$"
+    ))]
+    pub prefix: String,
+
+    /// Key of the snapshot restored by the first control plane node, which
+    /// is only used when creating a cluster.
+    #[builder(default)]
+    #[serde(default)]
+    #[schemars(regex(
+        pattern = r"^[A-Za-z0-9_-][A-Za-z0-9_.-]*(/[A-Za-z0-9_-][A-Za-z0-9_.-]*)*$|^$"
+    ))]
+    pub restore: String,
+}
+
+impl Default for EtcdBackupConfig {
+    fn default() -> Self {
+        EtcdBackupConfig::builder().build()
+    }
+}
+
+fn default_schedule() -> String {
+    "daily".into()
+}
+
+fn default_retention() -> i64 {
+    7
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "etcdBackup")]
+    pub etcd_backup: EtcdBackupConfig,
+}
+
+fn static_file(
+    path: &str,
+    permissions: &str,
+    content: &str,
+) -> KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+    KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+        path: path.to_string(),
+        owner: Some("root:root".into()),
+        permissions: Some(permissions.to_string()),
+        content: Some(BASE64_STANDARD.encode(content)),
+        encoding: Some(
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding::Base64,
+        ),
+        ..Default::default()
+    }
+}
+
+fn template_file(path: &str, content: &str) -> String {
+    serde_yaml::to_string(
+        &KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+            path: path.to_string(),
+            owner: Some("root:root".into()),
+            permissions: Some("0644".to_string()),
+            content: Some(content.to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        let selector = ClusterClassPatchesDefinitionsSelector {
+            api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+            kind: KubeadmControlPlaneTemplate::api_resource().kind,
+            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                control_plane: Some(true),
+                ..Default::default()
+            },
+        };
+
+        vec![
+            ClusterClassPatches {
+                name: "etcdBackupConfig".into(),
+                enabled_if: Some("{{ if or .etcdBackup.enabled .etcdBackup.restore }}true{{end}}".into()),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: selector.clone(),
+                    json_patches: vec![
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                            value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                template: Some(
+                                    serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+                                        path: "/etc/kubernetes/etcd-backup/credentials".to_string(),
+                                        owner: Some("root:root".into()),
+                                        permissions: Some("0600".to_string()),
+                                        content_from: Some(KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesContentFrom {
+                                            secret: KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesContentFromSecret {
+                                                name: "{{ .builtin.cluster.name }}-etcd-backup".to_string(),
+                                                key: "credentials".to_string(),
+                                            },
+                                        }),
+                                        ..Default::default()
+                                    }).unwrap(),
+                                ),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                            value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                template: Some(template_file("/etc/kubernetes/etcd-backup/config", CONFIG)),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                    ],
+                }]),
+                ..Default::default()
+            },
+            ClusterClassPatches {
+                name: "etcdBackup".into(),
+                enabled_if: Some("{{ if .etcdBackup.enabled }}true{{end}}".into()),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: selector.clone(),
+                    json_patches: vec![
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                            value: Some(json!(static_file(
+                                "/etc/kubernetes/etcd-backup/backup.sh",
+                                "0755",
+                                include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/etcd/etcd-backup.sh")),
+                            ))),
+                            ..Default::default()
+                        },
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                            value: Some(json!(static_file(
+                                "/etc/systemd/system/etcd-backup.service",
+                                "0644",
+                                include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/etcd/etcd-backup.service")),
+                            ))),
+                            ..Default::default()
+                        },
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                            value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                                template: Some(template_file("/etc/systemd/system/etcd-backup.timer", BACKUP_TIMER)),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/postKubeadmCommands/-".into(),
+                            value: Some("systemctl daemon-reload && systemctl enable --now etcd-backup.timer".into()),
+                            ..Default::default()
+                        },
+                    ],
+                }]),
+                ..Default::default()
+            },
+            ClusterClassPatches {
+                name: "etcdBackupRestore".into(),
+                enabled_if: Some("{{ if .etcdBackup.restore }}true{{end}}".into()),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector,
+                    json_patches: vec![
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/files/-".into(),
+                            value: Some(json!(static_file(
+                                "/etc/kubernetes/etcd-backup/restore.sh",
+                                "0755",
+                                include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/data/etcd/etcd-restore.sh")),
+                            ))),
+                            ..Default::default()
+                        },
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/preKubeadmCommands/-".into(),
+                            value: Some("/etc/kubernetes/etcd-backup/restore.sh".into()),
+                            ..Default::default()
+                        },
+                        ClusterClassPatchesDefinitionsJsonPatches {
+                            op: "add".into(),
+                            path: "/spec/template/spec/kubeadmConfigSpec/initConfiguration/nodeRegistration/ignorePreflightErrors".into(),
+                            value: Some(json!(["DirAvailable--var-lib-etcd"])),
+                            ..Default::default()
+                        },
+                    ],
+                }]),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        features::{test::TestClusterResources, KUBEADM_CONTROL_PLANE_TEMPLATE},
+        resources::fixtures::default_values,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn file(
+        resources: &TestClusterResources,
+        path: &str,
+    ) -> Option<KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles> {
+        resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec
+            .files
+            .clone()
+            .expect("files should be set")
+            .into_iter()
+            .find(|file| file.path == path)
+    }
+
+    #[test]
+    fn test_disabled() {
+        let feature = Feature {};
+
+        let values = default_values();
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources.kubeadm_control_plane_template,
+            KUBEADM_CONTROL_PLANE_TEMPLATE.clone()
+        );
+    }
+
+    #[test]
+    fn test_enabled() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.etcd_backup = EtcdBackupConfig::builder()
+            .enabled(true)
+            .schedule("hourly".into())
+            .retention(24)
+            .prefix("project/cluster".into())
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            file(&resources, "/etc/kubernetes/etcd-backup/credentials")
+                .expect("credentials should be set")
+                .content_from,
+            Some(KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesContentFrom {
+                secret: KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesContentFromSecret {
+                    name: "kube-abcde-etcd-backup".into(),
+                    key: "credentials".into(),
+                },
+            })
+        );
+        assert_eq!(
+            file(&resources, "/etc/kubernetes/etcd-backup/config")
+                .expect("config should be set")
+                .content,
+            Some(
+                indoc!(
+                    r#"
+                    ETCD_BACKUP_PREFIX=project/cluster
+                    ETCD_BACKUP_RETENTION=24
+                    ETCD_BACKUP_RESTORE_SNAPSHOT=
+                    "#
+                )
+                .to_string()
+            )
+        );
+        assert_eq!(
+            file(&resources, "/etc/systemd/system/etcd-backup.timer")
+                .expect("timer should be set")
+                .content,
+            Some(BACKUP_TIMER.replace("{{ .etcdBackup.schedule }}", "hourly"))
+        );
+        assert!(file(&resources, "/etc/kubernetes/etcd-backup/backup.sh").is_some());
+        assert!(file(&resources, "/etc/kubernetes/etcd-backup/restore.sh").is_none());
+
+        let kubeadm_config_spec = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+
+        assert_eq!(
+            kubeadm_config_spec.post_kubeadm_commands,
+            Some(vec![
+                "echo PLACEHOLDER".to_string(),
+                "systemctl daemon-reload && systemctl enable --now etcd-backup.timer".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_restore() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.etcd_backup = EtcdBackupConfig::builder()
+            .restore("project/cluster/etcd-snapshot-20261019T030000Z.db".into())
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert!(file(&resources, "/etc/kubernetes/etcd-backup/credentials").is_some());
+        assert!(file(&resources, "/etc/kubernetes/etcd-backup/restore.sh").is_some());
+        assert!(file(&resources, "/etc/kubernetes/etcd-backup/backup.sh").is_none());
+
+        let kubeadm_config_spec = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+
+        assert_eq!(
+            kubeadm_config_spec
+                .pre_kubeadm_commands
+                .expect("pre kubeadm commands should be set")
+                .last(),
+            Some(&"/etc/kubernetes/etcd-backup/restore.sh".to_string())
+        );
+        assert_eq!(
+            kubeadm_config_spec
+                .init_configuration
+                .and_then(|init_configuration| init_configuration.node_registration)
+                .and_then(|node_registration| node_registration.ignore_preflight_errors),
+            Some(vec!["DirAvailable--var-lib-etcd".to_string()])
+        );
+    }
+}
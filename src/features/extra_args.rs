@@ -0,0 +1,363 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources, ClusterClassVariables,
+            ClusterClassVariablesSchema,
+        },
+        kubeadmcontrolplanetemplates::KubeadmControlPlaneTemplate,
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables, KUBEADM_CONTROL_PLANE_TEMPLATE,
+    },
+};
+use cluster_feature_derive::ClusterFeatureValues;
+use indoc::formatdoc;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use typed_builder::TypedBuilder;
+
+/// Extra command line flags for the control plane components, which are
+/// allowed by the operator and validated by the driver before being set.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct ExtraArgsConfig {
+    #[builder(default)]
+    #[serde(default, rename = "apiServer")]
+    #[schemars(inner(regex(pattern = r#"^[^"\\]*$"#)))]
+    pub api_server: BTreeMap<String, String>,
+
+    #[builder(default)]
+    #[serde(default, rename = "controllerManager")]
+    #[schemars(inner(regex(pattern = r#"^[^"\\]*$"#)))]
+    pub controller_manager: BTreeMap<String, String>,
+
+    #[builder(default)]
+    #[serde(default)]
+    #[schemars(inner(regex(pattern = r#"^[^"\\]*$"#)))]
+    pub scheduler: BTreeMap<String, String>,
+
+    #[builder(default)]
+    #[serde(default)]
+    #[schemars(inner(regex(pattern = r#"^[^"\\]*$"#)))]
+    pub etcd: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "extraArgs")]
+    pub extra_args: ExtraArgsConfig,
+}
+
+/// A control plane component which accepts extra flags.
+struct Component {
+    /// Name of the component in the `extraArgs` variable.
+    name: &'static str,
+
+    /// Path of the `extraArgs` of the component in the `KubeadmControlPlaneTemplate`.
+    path: &'static str,
+
+    /// Flags which are set by other features and can not be overridden.
+    feature_flags: &'static [&'static str],
+}
+
+const COMPONENTS: &[Component] = &[
+    Component {
+        name: "apiServer",
+        path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/apiServer/extraArgs",
+        feature_flags: &[
+            "admission-control-config-file",
+            "audit-log-maxage",
+            "audit-log-maxbackup",
+            "audit-log-maxsize",
+            "audit-log-path",
+            "audit-policy-file",
+            "audit-webhook-batch-buffer-size",
+            "audit-webhook-batch-max-size",
+            "audit-webhook-batch-max-wait",
+            "audit-webhook-config-file",
+            "audit-webhook-mode",
+            "authentication-token-webhook-config-file",
+            "authorization-mode",
+            "cloud-provider",
+            "enable-admission-plugins",
+            "oidc-client-id",
+            "oidc-groups-claim",
+            "oidc-groups-prefix",
+            "oidc-issuer-url",
+            "oidc-username-claim",
+            "oidc-username-prefix",
+            "tls-cipher-suites",
+        ],
+    },
+    Component {
+        name: "controllerManager",
+        path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/controllerManager/extraArgs",
+        feature_flags: &[],
+    },
+    Component {
+        name: "scheduler",
+        path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/scheduler/extraArgs",
+        feature_flags: &[],
+    },
+    Component {
+        name: "etcd",
+        path: "/spec/template/spec/kubeadmConfigSpec/clusterConfiguration/etcd/local/extraArgs",
+        feature_flags: &[
+            "auto-compaction-mode",
+            "auto-compaction-retention",
+            "election-timeout",
+            "heartbeat-interval",
+            "quota-backend-bytes",
+            "snapshot-count",
+        ],
+    },
+];
+
+impl Component {
+    /// Flags which are set in the `KubeadmControlPlaneTemplate` for every cluster.
+    fn default_flags(&self) -> BTreeMap<String, String> {
+        let cluster_configuration = KUBEADM_CONTROL_PLANE_TEMPLATE
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec
+            .cluster_configuration
+            .clone()
+            .expect("cluster configuration should be set");
+
+        match self.name {
+            "apiServer" => cluster_configuration.api_server.and_then(|c| c.extra_args),
+            "controllerManager" => cluster_configuration
+                .controller_manager
+                .and_then(|c| c.extra_args),
+            "scheduler" => cluster_configuration.scheduler.and_then(|c| c.extra_args),
+            "etcd" => cluster_configuration
+                .etcd
+                .and_then(|c| c.local)
+                .and_then(|c| c.extra_args),
+            _ => None,
+        }
+        .unwrap_or_default()
+    }
+
+    fn owned_flags(&self) -> Vec<String> {
+        let mut flags: Vec<String> = self.default_flags().into_keys().collect();
+        flags.extend(self.feature_flags.iter().map(|flag| flag.to_string()));
+        flags.sort();
+        flags
+    }
+
+    /// Renders the whole `extraArgs` map since JSON patches can not merge maps,
+    /// with the flags owned by the driver taking precedence over the user ones.
+    fn template(&self) -> String {
+        let conditions = self
+            .owned_flags()
+            .iter()
+            .map(|flag| format!(r#"(ne $key "{}")"#, flag))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut template = formatdoc! {r#"
+            {{{{- range $key, $value := .extraArgs.{name} }}}}
+            {{{{- if and {conditions} }}}}
+            "{{{{ $key }}}}": "{{{{ $value }}}}"
+            {{{{- end }}}}
+            {{{{- end }}}}
+            "#,
+            name = self.name,
+            conditions = conditions,
+        };
+
+        for (flag, value) in self.default_flags() {
+            template.push_str(&format!("\"{}\": \"{}\"\n", flag, value));
+        }
+
+        template
+    }
+}
+
+/// Flags of every component which can not be set through `extraArgs`, which
+/// is used by the driver to reject them before creating the cluster.
+pub fn owned_flags() -> BTreeMap<&'static str, Vec<String>> {
+    COMPONENTS
+        .iter()
+        .map(|component| (component.name, component.owned_flags()))
+        .collect()
+}
+
+/// Returns `true` for the patches of this feature, which replace the whole
+/// `extraArgs` maps and have to be applied before the patches of the other
+/// features which add their own flags to them.
+pub fn is_extra_args_patch(patch: &ClusterClassPatches) -> bool {
+    COMPONENTS
+        .iter()
+        .any(|component| patch.name == format!("{}ExtraArgs", component.name))
+}
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        COMPONENTS
+            .iter()
+            .map(|component| ClusterClassPatches {
+                name: format!("{}ExtraArgs", component.name),
+                enabled_if: Some(format!(
+                    "{{{{ if .extraArgs.{} }}}}true{{{{end}}}}",
+                    component.name
+                )),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+                        kind: KubeadmControlPlaneTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            control_plane: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: component.path.into(),
+                        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                            template: Some(component.template()),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }],
+                }]),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{features::test::TestClusterResources, resources::fixtures::default_values};
+    use maplit::btreemap;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_disabled() {
+        let feature = Feature {};
+
+        let values = default_values();
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources.kubeadm_control_plane_template,
+            KUBEADM_CONTROL_PLANE_TEMPLATE.clone()
+        );
+    }
+
+    #[test]
+    fn test_enabled() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.extra_args = ExtraArgsConfig::builder()
+            .api_server(btreemap! {
+                "max-requests-inflight".to_string() => "800".to_string(),
+                "cloud-provider".to_string() => "openstack".to_string(),
+                "profiling".to_string() => "true".to_string(),
+            })
+            .controller_manager(btreemap! {
+                "terminated-pod-gc-threshold".to_string() => "100".to_string(),
+            })
+            .scheduler(btreemap! {
+                "feature-gates".to_string() => "A=true,B=false".to_string(),
+            })
+            .etcd(btreemap! {
+                "max-request-bytes".to_string() => "3145728".to_string(),
+            })
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let cluster_configuration = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec
+            .cluster_configuration
+            .expect("cluster configuration should be set");
+
+        assert_eq!(
+            cluster_configuration
+                .api_server
+                .expect("api server should be set")
+                .extra_args,
+            Some(btreemap! {
+                "max-requests-inflight".to_string() => "800".to_string(),
+                "profiling".to_string() => "false".to_string(),
+            })
+        );
+        assert_eq!(
+            cluster_configuration
+                .controller_manager
+                .expect("controller manager should be set")
+                .extra_args,
+            Some(btreemap! {
+                "bind-address".to_string() => "0.0.0.0".to_string(),
+                "cloud-provider".to_string() => "external".to_string(),
+                "profiling".to_string() => "false".to_string(),
+                "terminated-pod-gc-threshold".to_string() => "100".to_string(),
+            })
+        );
+        assert_eq!(
+            cluster_configuration
+                .scheduler
+                .expect("scheduler should be set")
+                .extra_args,
+            Some(btreemap! {
+                "bind-address".to_string() => "0.0.0.0".to_string(),
+                "feature-gates".to_string() => "A=true,B=false".to_string(),
+                "profiling".to_string() => "false".to_string(),
+            })
+        );
+        assert_eq!(
+            cluster_configuration
+                .etcd
+                .and_then(|etcd| etcd.local)
+                .expect("local etcd should be set")
+                .extra_args,
+            Some(btreemap! {
+                "listen-metrics-urls".to_string() => "http://0.0.0.0:2381".to_string(),
+                "max-request-bytes".to_string() => "3145728".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_owned_flags() {
+        let flags = owned_flags();
+
+        assert!(flags["apiServer"].contains(&"profiling".to_string()));
+        assert!(flags["apiServer"].contains(&"oidc-issuer-url".to_string()));
+        assert_eq!(
+            flags["controllerManager"],
+            vec!["bind-address", "cloud-provider", "profiling"]
+        );
+        assert!(flags["etcd"].contains(&"listen-metrics-urls".to_string()));
+        assert!(flags["etcd"].contains(&"quota-backend-bytes".to_string()));
+    }
+}
@@ -0,0 +1,612 @@
+//! Translation of a rendered `KubeadmConfigSpec` into an Ignition config.
+//!
+//! When the `format` of a kubeadm config is `ignition`, the Cluster API
+//! bootstrap provider converts the `files`, `preKubeadmCommands` and
+//! `postKubeadmCommands` into an Ignition config and merges the
+//! `ignition.containerLinuxConfig.additionalConfig` on top of it.  This module
+//! mirrors that conversion so that the driver can check the rendered specs of
+//! Flatcar clusters before their nodes fail to boot, and so that every feature
+//! is tested against Flatcar without needing Flatcar-specific patches.
+
+use base64::prelude::*;
+use ignition_config::v3_5::{Config, File, NodeGroup, NodeUser, Resource, Storage, Systemd, Unit};
+use indoc::indoc;
+use pyo3::{create_exception, exceptions::PyException, PyErr};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to serialize kubeadm config spec: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("failed to parse additional ignition config: {0}")]
+    AdditionalConfig(#[from] serde_yaml::Error),
+    #[error("file {path:?} has invalid permissions {permissions:?}")]
+    InvalidPermissions { path: String, permissions: String },
+    #[error("path {0:?} is not absolute")]
+    RelativePath(String),
+    #[error("path {0:?} is defined more than once")]
+    DuplicatePath(String),
+    #[error("invalid ignition config: {0}")]
+    Invalid(#[from] ignition_config::Error),
+    #[error("ignition config has unknown keys: {0:?}")]
+    UnknownKeys(Vec<String>),
+}
+
+create_exception!(magnum_cluster_api, IgnitionError, PyException);
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> PyErr {
+        IgnitionError::new_err(err.to_string())
+    }
+}
+
+/// The `kubeadm` command which the bootstrap script runs between the pre and
+/// post commands.
+#[derive(Clone, Copy, Debug)]
+pub enum KubeadmCommand {
+    Init,
+    Join,
+}
+
+impl KubeadmCommand {
+    fn as_str(&self) -> &'static str {
+        match self {
+            KubeadmCommand::Init => "kubeadm init --config /etc/kubeadm.yml",
+            KubeadmCommand::Join => "kubeadm join --config /etc/kubeadm.yml",
+        }
+    }
+}
+
+/// The subset of `KubeadmConfigSpec` which is translated, shared by both the
+/// `KubeadmControlPlaneTemplate` and `KubeadmConfigTemplate` flavours of it.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KubeadmConfigSpec {
+    #[serde(default)]
+    files: Vec<KubeadmConfigFile>,
+    #[serde(default)]
+    pre_kubeadm_commands: Vec<String>,
+    #[serde(default)]
+    post_kubeadm_commands: Vec<String>,
+    ignition: Option<KubeadmConfigIgnition>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KubeadmConfigFile {
+    path: String,
+    owner: Option<String>,
+    permissions: Option<String>,
+    encoding: Option<String>,
+    content: Option<String>,
+    content_from: Option<serde_json::Value>,
+    append: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KubeadmConfigIgnition {
+    container_linux_config: Option<KubeadmConfigContainerLinuxConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KubeadmConfigContainerLinuxConfig {
+    additional_config: Option<String>,
+}
+
+const KUBEADM_SERVICE: &str = indoc! {"
+    [Unit]
+    Description=kubeadm
+    # Run only once. After successful run, this file is moved to /tmp/.
+    ConditionPathExists=/etc/kubeadm.yml
+    After=network.target
+    [Service]
+    Type=oneshot
+    ExecStart=/etc/kubeadm.sh
+    ExecStartPost=/bin/mv /etc/kubeadm.yml /tmp/
+    [Install]
+    WantedBy=multi-user.target
+"};
+
+impl KubeadmConfigFile {
+    fn to_ignition(&self) -> Result<File, Error> {
+        let mode = self
+            .permissions
+            .as_deref()
+            .map(|permissions| {
+                i64::from_str_radix(permissions, 8).map_err(|_| Error::InvalidPermissions {
+                    path: self.path.clone(),
+                    permissions: permissions.into(),
+                })
+            })
+            .transpose()?;
+
+        let (user, group) = match self.owner.as_deref().map(|owner| owner.split_once(':')) {
+            Some(Some((user, group))) => (Some(user), Some(group)),
+            Some(None) => (self.owner.as_deref(), None),
+            None => (None, None),
+        };
+
+        // NOTE: The bootstrap provider reads `contentFrom` from its secret before
+        //       the conversion, which is only known for a given machine.
+        let content = match self.content_from {
+            Some(_) => String::new(),
+            None => self.content.clone().unwrap_or_default(),
+        };
+        let (data, compression) = match self.encoding.as_deref() {
+            Some("base64") => (content, None),
+            Some("gzip+base64") => (content, Some("gzip".to_string())),
+            Some("gzip") => (BASE64_STANDARD.encode(content), Some("gzip".to_string())),
+            _ => (BASE64_STANDARD.encode(content), None),
+        };
+        let resource = Resource {
+            compression,
+            http_headers: None,
+            source: Some(format!("data:;base64,{}", data)),
+            verification: None,
+        };
+
+        let mut file = File::new(self.path.clone());
+        file.mode = mode;
+        file.user = user.map(|name| NodeUser {
+            id: None,
+            name: Some(name.into()),
+        });
+        file.group = group.map(|name| NodeGroup {
+            id: None,
+            name: Some(name.into()),
+        });
+
+        if self.append.unwrap_or(false) {
+            file.append = Some(vec![resource]);
+        } else {
+            file.overwrite = Some(true);
+            file.contents = Some(resource);
+        }
+
+        Ok(file)
+    }
+}
+
+/// Renders the bootstrap script which runs the pre commands, `kubeadm` and
+/// the post commands in order, stopping at the first failure.
+fn kubeadm_script(spec: &KubeadmConfigSpec, command: KubeadmCommand) -> String {
+    let mut script = vec!["#!/bin/bash".to_string(), "set -e".to_string()];
+    script.extend(spec.pre_kubeadm_commands.iter().cloned());
+    script.push(command.as_str().into());
+    script.push(
+        "mkdir -p /run/cluster-api && echo success > /run/cluster-api/bootstrap-success.complete"
+            .into(),
+    );
+    script.extend(spec.post_kubeadm_commands.iter().cloned());
+
+    script.join("\n") + "\n"
+}
+
+/// Merges `other` into `config`, units with the same name are combined so
+/// that drop-ins can be added to the units generated from the spec.
+fn merge(config: &mut Config, other: Config) {
+    if let Some(other) = other.storage {
+        let storage = config.storage.get_or_insert_with(Storage::default);
+
+        if let Some(files) = other.files {
+            storage.files.get_or_insert_with(Vec::new).extend(files);
+        }
+        if let Some(directories) = other.directories {
+            storage
+                .directories
+                .get_or_insert_with(Vec::new)
+                .extend(directories);
+        }
+        if let Some(links) = other.links {
+            storage.links.get_or_insert_with(Vec::new).extend(links);
+        }
+    }
+
+    if let Some(units) = other.systemd.and_then(|systemd| systemd.units) {
+        let existing = config
+            .systemd
+            .get_or_insert_with(Systemd::default)
+            .units
+            .get_or_insert_with(Vec::new);
+
+        for unit in units {
+            match existing.iter_mut().find(|u| u.name == unit.name) {
+                Some(u) => {
+                    u.enabled = unit.enabled.or(u.enabled);
+                    u.mask = unit.mask.or(u.mask);
+                    u.contents = unit.contents.or(u.contents.take());
+                    if let Some(dropins) = unit.dropins {
+                        u.dropins.get_or_insert_with(Vec::new).extend(dropins);
+                    }
+                }
+                None => existing.push(unit),
+            }
+        }
+    }
+
+    if other.passwd.is_some() {
+        config.passwd = other.passwd;
+    }
+}
+
+/// Checks that every path is absolute and unique, and that the config
+/// round-trips through `ignition_config` as a v3.5 config without warnings.
+pub fn validate(config: &Config) -> Result<(), Error> {
+    let mut paths = HashSet::new();
+
+    let storage = config.storage.as_ref();
+    let files = storage
+        .and_then(|s| s.files.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|f| &f.path);
+    let directories = storage
+        .and_then(|s| s.directories.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|d| &d.path);
+    let links = storage
+        .and_then(|s| s.links.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|l| &l.path);
+
+    for path in files.chain(directories).chain(links) {
+        if !path.starts_with('/') {
+            return Err(Error::RelativePath(path.clone()));
+        }
+        if !paths.insert(path) {
+            return Err(Error::DuplicatePath(path.clone()));
+        }
+    }
+
+    let (_, warnings) = ignition_config::Config::parse_str(&serde_json::to_string(config)?)?;
+    if !warnings.is_empty() {
+        return Err(Error::UnknownKeys(
+            warnings.iter().map(|w| w.to_string()).collect(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Translates a rendered `KubeadmConfigSpec` into a validated Ignition config.
+pub fn to_ignition<T: Serialize>(spec: &T, command: KubeadmCommand) -> Result<Config, Error> {
+    let spec: KubeadmConfigSpec = serde_json::from_value(serde_json::to_value(spec)?)?;
+
+    let mut files = spec
+        .files
+        .iter()
+        .map(KubeadmConfigFile::to_ignition)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut script = File::new("/etc/kubeadm.sh".into());
+    script.mode = Some(0o700);
+    script.overwrite = Some(true);
+    script.contents = Some(Resource {
+        compression: None,
+        http_headers: None,
+        source: Some(format!(
+            "data:;base64,{}",
+            BASE64_STANDARD.encode(kubeadm_script(&spec, command))
+        )),
+        verification: None,
+    });
+    files.push(script);
+
+    let mut config = Config {
+        storage: Some(Storage {
+            files: Some(files),
+            ..Default::default()
+        }),
+        systemd: Some(Systemd {
+            units: Some(vec![Unit {
+                name: "kubeadm.service".into(),
+                enabled: Some(true),
+                contents: Some(KUBEADM_SERVICE.into()),
+                dropins: None,
+                mask: None,
+            }]),
+        }),
+        ..Default::default()
+    };
+
+    if let Some(additional_config) = spec
+        .ignition
+        .and_then(|ignition| ignition.container_linux_config)
+        .and_then(|clc| clc.additional_config)
+    {
+        merge(&mut config, serde_yaml::from_str(&additional_config)?);
+    }
+
+    validate(&config)?;
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        features::{
+            bootstrap_hooks::BootstrapHooksConfig,
+            container_runtimes::ContainerRuntime,
+            etcd::{EtcdConfig, EtcdDefragConfig},
+            etcd_backup::EtcdBackupConfig,
+            operating_system::OperatingSystem,
+            swap::{SwapBehavior, SwapConfig},
+            test::TestClusterResources,
+        },
+        resources::{fixtures::default_values, ClusterClassBuilder},
+    };
+    use kube::api::ObjectMeta;
+    use pretty_assertions::assert_eq;
+    use pyo3::Python;
+
+    fn decode(resource: &Resource) -> String {
+        let source = resource.source.as_ref().expect("source should be set");
+        let data = source
+            .strip_prefix("data:;base64,")
+            .expect("source should be a base64 data URL");
+
+        String::from_utf8(BASE64_STANDARD.decode(data).unwrap()).unwrap()
+    }
+
+    fn file_paths(config: &Config) -> Vec<&str> {
+        config
+            .storage
+            .as_ref()
+            .and_then(|storage| storage.files.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|file| file.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_files() {
+        let spec = serde_json::json!({
+            "files": [
+                {
+                    "path": "/etc/plain.conf",
+                    "owner": "root:root",
+                    "permissions": "0644",
+                    "content": "plain",
+                },
+                {
+                    "path": "/etc/encoded.conf",
+                    "owner": "core",
+                    "encoding": "base64",
+                    "content": BASE64_STANDARD.encode("encoded"),
+                },
+                {
+                    "path": "/etc/appended.conf",
+                    "content": "appended",
+                    "append": true,
+                },
+            ],
+        });
+
+        let config = to_ignition(&spec, KubeadmCommand::Join).expect("translation should succeed");
+        let files = config.storage.unwrap().files.unwrap();
+
+        assert_eq!(files[0].path, "/etc/plain.conf");
+        assert_eq!(files[0].mode, Some(0o644));
+        assert_eq!(files[0].user.as_ref().unwrap().name, Some("root".into()));
+        assert_eq!(files[0].group.as_ref().unwrap().name, Some("root".into()));
+        assert_eq!(decode(files[0].contents.as_ref().unwrap()), "plain");
+
+        assert_eq!(files[1].user.as_ref().unwrap().name, Some("core".into()));
+        assert_eq!(files[1].group, None);
+        assert_eq!(decode(files[1].contents.as_ref().unwrap()), "encoded");
+
+        assert_eq!(files[2].contents, None);
+        assert_eq!(decode(&files[2].append.as_ref().unwrap()[0]), "appended");
+    }
+
+    #[test]
+    fn test_commands() {
+        let spec = serde_json::json!({
+            "preKubeadmCommands": ["echo pre"],
+            "postKubeadmCommands": ["echo post"],
+        });
+
+        let config = to_ignition(&spec, KubeadmCommand::Init).expect("translation should succeed");
+        let files = config.storage.unwrap().files.unwrap();
+        let script = files
+            .iter()
+            .find(|f| f.path == "/etc/kubeadm.sh")
+            .expect("script should be set");
+
+        assert_eq!(script.mode, Some(0o700));
+        assert_eq!(
+            decode(script.contents.as_ref().unwrap()),
+            indoc! {"
+                #!/bin/bash
+                set -e
+                echo pre
+                kubeadm init --config /etc/kubeadm.yml
+                mkdir -p /run/cluster-api && echo success > /run/cluster-api/bootstrap-success.complete
+                echo post
+            "}
+        );
+    }
+
+    #[test]
+    fn test_additional_config_is_merged() {
+        let additional_config = Config {
+            systemd: Some(Systemd {
+                units: Some(vec![
+                    Unit {
+                        name: "kubeadm.service".into(),
+                        enabled: None,
+                        contents: None,
+                        dropins: Some(vec![ignition_config::v3_5::Dropin {
+                            name: "10-flatcar.conf".into(),
+                            contents: Some("[Unit]\n".into()),
+                        }]),
+                        mask: None,
+                    },
+                    Unit::new("extra.service".into()),
+                ]),
+            }),
+            ..Default::default()
+        };
+        let spec = serde_json::json!({
+            "ignition": {
+                "containerLinuxConfig": {
+                    "additionalConfig": serde_yaml::to_string(&additional_config).unwrap(),
+                },
+            },
+        });
+
+        let config = to_ignition(&spec, KubeadmCommand::Join).expect("translation should succeed");
+        let units = config.systemd.unwrap().units.unwrap();
+
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[0].name, "kubeadm.service");
+        assert_eq!(units[0].enabled, Some(true));
+        assert_eq!(units[0].contents, Some(KUBEADM_SERVICE.into()));
+        assert_eq!(units[0].dropins.as_ref().unwrap().len(), 1);
+        assert_eq!(units[1].name, "extra.service");
+    }
+
+    #[test]
+    fn test_duplicate_path() {
+        let spec = serde_json::json!({
+            "files": [
+                {"path": "/etc/kubeadm.sh", "content": "duplicate"},
+            ],
+        });
+
+        assert!(matches!(
+            to_ignition(&spec, KubeadmCommand::Join),
+            Err(Error::DuplicatePath(path)) if path == "/etc/kubeadm.sh"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_permissions() {
+        let spec = serde_json::json!({
+            "files": [
+                {"path": "/etc/foo", "permissions": "rw-r--r--", "content": "foo"},
+            ],
+        });
+
+        assert!(matches!(
+            to_ignition(&spec, KubeadmCommand::Join),
+            Err(Error::InvalidPermissions { .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_is_ignition_error() {
+        Python::initialize();
+        Python::attach(|py| {
+            let err: PyErr = Error::RelativePath("etc/foo".into()).into();
+            assert!(err.is_instance_of::<IgnitionError>(py));
+        });
+    }
+
+    #[test]
+    fn test_content_from() {
+        let spec = serde_json::json!({
+            "files": [
+                {
+                    "path": "/etc/foo",
+                    "permissions": "0600",
+                    "contentFrom": {"secret": {"name": "foo", "key": "bar"}},
+                },
+            ],
+        });
+
+        let config = to_ignition(&spec, KubeadmCommand::Join).expect("translation should succeed");
+        let files = config.storage.unwrap().files.unwrap();
+
+        assert_eq!(files[0].path, "/etc/foo");
+        assert_eq!(files[0].mode, Some(0o600));
+        assert_eq!(decode(files[0].contents.as_ref().unwrap()), "");
+    }
+
+    #[test]
+    fn test_all_features_render_for_flatcar() {
+        let mut values = default_values();
+        values.operating_system = OperatingSystem::Flatcar;
+        values.audit_log.enabled = true;
+        values.audit_log.policy = BASE64_STANDARD.encode("apiVersion: audit.k8s.io/v1\n");
+        values.audit_log.webhook.enabled = true;
+        values.audit_log.webhook.kubeconfig = BASE64_STANDARD.encode("apiVersion: v1\n");
+        values.enable_keystone_auth = true;
+        values.pod_security.enabled = true;
+        values.etcd_config = EtcdConfig::builder()
+            .defrag(EtcdDefragConfig::builder().enabled(true).build())
+            .build();
+        values.etcd_backup = EtcdBackupConfig::builder()
+            .enabled(true)
+            .schedule("hourly".into())
+            .retention(24)
+            .prefix("project/cluster".into())
+            .build();
+        values.swap = SwapConfig::builder()
+            .size(2048)
+            .behavior(SwapBehavior::LimitedSwap)
+            .build();
+        values.bootstrap_hooks = BootstrapHooksConfig::builder()
+            .enabled(true)
+            .pre_kubeadm_script("ZWNobyBwcmUK".into())
+            .post_kubeadm_script("ZWNobyBwb3N0Cg==".into())
+            .build();
+        values.container_runtimes = vec![ContainerRuntime::Runsc, ContainerRuntime::Kata];
+
+        let patches = ClusterClassBuilder::default(ObjectMeta::default())
+            .spec
+            .patches
+            .expect("patches should be set");
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let kubeadm_config_spec = &resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+        for command in [KubeadmCommand::Init, KubeadmCommand::Join] {
+            let config = to_ignition(kubeadm_config_spec, command)
+                .expect("control plane should translate to ignition");
+
+            let paths = file_paths(&config);
+            for path in [
+                "/etc/kubernetes/etcd-defrag.sh",
+                "/etc/systemd/system/etcd-defrag.timer",
+                "/etc/kubernetes/etcd-backup/config",
+                "/etc/kubernetes/swap.sh",
+                "/etc/kubernetes/hooks/pre-kubeadm.sh",
+            ] {
+                assert!(paths.contains(&path), "control plane is missing {}", path);
+            }
+        }
+
+        let kubeadm_config_template_spec = resources
+            .kubeadm_config_template
+            .spec
+            .template
+            .spec
+            .expect("spec should be set");
+        let config = to_ignition(&kubeadm_config_template_spec, KubeadmCommand::Join)
+            .expect("workers should translate to ignition");
+
+        let paths = file_paths(&config);
+        for path in [
+            "/etc/kubernetes/swap.sh",
+            "/etc/kubernetes/hooks/pre-kubeadm.sh",
+            "/etc/containerd/conf.d/runtimes.toml",
+        ] {
+            assert!(paths.contains(&path), "workers are missing {}", path);
+        }
+    }
+}
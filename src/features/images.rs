@@ -17,16 +17,57 @@ use crate::{
     },
 };
 use cluster_feature_derive::ClusterFeatureValues;
+use indoc::indoc;
 use kube::CustomResourceExt;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+/// A Glance query which is used instead of the image UUID when either the name
+/// or the tags are set, the query must match exactly one image.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct ImageFilter {
+    #[builder(default)]
+    #[serde(default)]
+    #[schemars(regex(pattern = r#"^[^"\\]*$"#))]
+    pub name: String,
+
+    #[builder(default)]
+    #[serde(default)]
+    #[schemars(inner(regex(pattern = r#"^[^"\\]+$"#)))]
+    pub tags: Vec<String>,
+}
 
 #[derive(Serialize, Deserialize, ClusterFeatureValues)]
 #[allow(dead_code)]
 pub struct FeatureValues {
     #[serde(rename = "imageUUID")]
     pub image_uuid: String,
+
+    #[serde(rename = "imageFilter")]
+    pub image_filter: ImageFilter,
 }
 
+// NOTE: The image is selected inside the template rather than with `enabledIf`
+//       since the latter does not take MachineDeployment-level overrides into
+//       account, which would prevent node groups from picking their own image.
+const IMAGE_TEMPLATE: &str = indoc! {r#"
+    {{- if or .imageFilter.name .imageFilter.tags }}
+    filter:
+      {{- if .imageFilter.name }}
+      name: "{{ .imageFilter.name }}"
+      {{- end }}
+      {{- if .imageFilter.tags }}
+      tags:
+        {{- range .imageFilter.tags }}
+        - "{{ . }}"
+        {{- end }}
+      {{- end }}
+    {{- else }}
+    id: "{{ .imageUUID }}"
+    {{- end }}
+"#};
+
 pub struct Feature {}
 
 impl ClusterFeaturePatches for Feature {
@@ -49,9 +90,9 @@ impl ClusterFeaturePatches for Feature {
                     json_patches: vec![
                         ClusterClassPatchesDefinitionsJsonPatches {
                             op: "add".into(),
-                            path: "/spec/template/spec/image/id".into(),
+                            path: "/spec/template/spec/image".into(),
                             value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                variable: Some("imageUUID".into()),
+                                template: Some(IMAGE_TEMPLATE.into()),
                                 ..Default::default()
                             }),
                             ..Default::default()
@@ -71,6 +112,9 @@ inventory::submit! {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cluster_api::openstackmachinetemplates::{
+        OpenStackMachineTemplateTemplateSpecImage, OpenStackMachineTemplateTemplateSpecImageFilter,
+    };
     use crate::features::test::TestClusterResources;
     use crate::resources::fixtures::default_values;
     use pretty_assertions::assert_eq;
@@ -107,4 +151,47 @@ mod tests {
             Some(values.image_uuid.clone())
         );
     }
+
+    #[test]
+    fn test_patches_with_image_filter() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.image_filter = ImageFilter::builder()
+            .name("ubuntu-2204-kube-v1.30".into())
+            .tags(vec!["kube-v1.30".into(), "latest".into()])
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let expected = OpenStackMachineTemplateTemplateSpecImage {
+            filter: Some(OpenStackMachineTemplateTemplateSpecImageFilter {
+                name: Some("ubuntu-2204-kube-v1.30".into()),
+                tags: Some(vec!["kube-v1.30".into(), "latest".into()]),
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            resources
+                .control_plane_openstack_machine_template
+                .spec
+                .template
+                .spec
+                .image,
+            expected
+        );
+        assert_eq!(
+            resources
+                .worker_openstack_machine_template
+                .spec
+                .template
+                .spec
+                .image,
+            expected
+        );
+    }
 }
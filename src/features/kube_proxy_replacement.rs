@@ -0,0 +1,117 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches, ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources, ClusterClassVariables,
+            ClusterClassVariablesSchema,
+        },
+        kubeadmcontrolplanetemplates::KubeadmControlPlaneTemplate,
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use cluster_feature_derive::ClusterFeatureValues;
+use kube::CustomResourceExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    /// Whether Cilium replaces kube-proxy, in which case kube-proxy is never
+    /// deployed in the cluster.
+    #[serde(rename = "kubeProxyReplacement")]
+    pub kube_proxy_replacement: bool,
+}
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        // NOTE: kube-proxy is only deployed by `kubeadm init` on the first
+        //       control plane node, so the phase does not need to be skipped
+        //       when the other nodes join the cluster.
+        vec![ClusterClassPatches {
+            name: "kubeProxyReplacement".into(),
+            enabled_if: Some("{{ if .kubeProxyReplacement }}true{{end}}".into()),
+            definitions: Some(vec![ClusterClassPatchesDefinitions {
+                selector: ClusterClassPatchesDefinitionsSelector {
+                    api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+                    kind: KubeadmControlPlaneTemplate::api_resource().kind,
+                    match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                        control_plane: Some(true),
+                        ..Default::default()
+                    },
+                },
+                json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                    op: "add".into(),
+                    path: "/spec/template/spec/kubeadmConfigSpec/initConfiguration/skipPhases"
+                        .into(),
+                    value: Some(json!(["addon/kube-proxy"])),
+                    ..Default::default()
+                }],
+            }]),
+            ..Default::default()
+        }]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::test::TestClusterResources;
+    use crate::features::KUBEADM_CONTROL_PLANE_TEMPLATE;
+    use crate::resources::fixtures::default_values;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_patches_if_enabled() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.kube_proxy_replacement = true;
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources
+                .kubeadm_control_plane_template
+                .spec
+                .template
+                .spec
+                .kubeadm_config_spec
+                .init_configuration
+                .expect("init configuration should be set")
+                .skip_phases,
+            Some(vec!["addon/kube-proxy".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_patches_if_disabled() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.kube_proxy_replacement = false;
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources.kubeadm_control_plane_template,
+            KUBEADM_CONTROL_PLANE_TEMPLATE.clone()
+        );
+    }
+}
@@ -1,5 +1,5 @@
 #[cfg(test)]
-mod test;
+pub(crate) mod test;
 
 use crate::cluster_api::{
     clusterclasses::{ClusterClassPatches, ClusterClassVariables, ClusterClassVariablesSchema},
@@ -45,29 +45,45 @@ use crate::cluster_api::{
 };
 use base64::prelude::*;
 use maplit::btreemap;
-use schemars::{generate::SchemaGenerator, JsonSchema, Schema};
+use schemars::{
+    generate::{SchemaGenerator, SchemaSettings},
+    JsonSchema, Schema,
+};
 use std::sync::LazyLock;
 
+pub mod additional_networks;
 pub mod admission_plugins;
 pub mod api_server_floating_ip;
 pub mod api_server_load_balancer;
 pub mod audit_log;
+pub mod bastion;
 pub mod boot_volume;
+pub mod bootstrap_hooks;
 pub mod cloud_provider;
 pub mod cluster_identity;
+pub mod container_runtimes;
 pub mod containerd_config;
 pub mod control_plane_availability_zones;
 pub mod disable_api_server_floating_ip;
+pub mod etcd;
+pub mod etcd_backup;
 pub mod external_network;
+pub mod extra_args;
 pub mod flavors;
+pub mod ignition;
 pub mod image_repository;
 pub mod images;
 pub mod keystone_auth;
+pub mod kube_proxy_replacement;
 pub mod networks;
 pub mod openid_connect;
 pub mod operating_system;
+pub mod rollout_strategy;
+pub mod security_groups;
 pub mod server_groups;
+pub mod server_metadata;
 pub mod ssh_key;
+pub mod swap;
 pub mod tls;
 pub mod volumes;
 
@@ -75,6 +91,13 @@ pub trait ClusterFeatureVariables: Sync {
     fn variables(&self) -> Vec<ClusterClassVariables>;
 }
 
+/// Provides the ClusterClass patches of a feature.
+///
+/// The `enabledIf` of a patch is only evaluated against the cluster-level
+/// variables, so it must not depend on a variable which node groups override
+/// through their MachineDeployment, such as `additionalVolumes` or
+/// `containerRuntimes`.  Patches which depend on those are left unguarded and
+/// check the variable from their templates instead.
 pub trait ClusterFeaturePatches: Sync {
     fn patches(&self) -> Vec<ClusterClassPatches>;
 }
@@ -167,7 +190,11 @@ pub trait ClusterClassVariablesSchemaExt {
 
 impl ClusterClassVariablesSchemaExt for ClusterClassVariablesSchema {
     fn from_object<T: JsonSchema>() -> Self {
-        let gen = SchemaGenerator::default();
+        // CAPI variable schemas do not support `$ref`, so nested types must
+        // be inlined into the schema.
+        let gen = SchemaSettings::default()
+            .with(|s| s.inline_subschemas = true)
+            .into_generator();
         let schema = gen.into_root_schema_for::<T>();
         Self::from_root_schema(schema)
     }
@@ -409,7 +436,8 @@ pub static OPENSTACK_CLUSTER_TEMPLATE: LazyLock<OpenStackClusterTemplate> = Lazy
                                     protocol: Some("tcp".to_string()),
                                     ..Default::default()
                                 }
-                            ])
+                            ]),
+                            ..Default::default()
                         },
                     ),
                     ..Default::default()
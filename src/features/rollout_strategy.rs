@@ -0,0 +1,139 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources, ClusterClassVariables,
+            ClusterClassVariablesSchema,
+        },
+        kubeadmcontrolplanetemplates::KubeadmControlPlaneTemplate,
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use cluster_feature_derive::ClusterFeatureValues;
+use indoc::indoc;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+/// Rollout strategy of the control plane, the machine deployments get their
+/// strategy from the cluster topology instead.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct ControlPlaneRolloutStrategy {
+    /// Number of control plane machines which can be created above the desired
+    /// number during a rolling update, setting it to `0` replaces the machines
+    /// in place without creating an extra one first.
+    #[builder(default = 1)]
+    #[serde(rename = "maxSurge")]
+    #[schemars(range(min = 0, max = 1))]
+    pub max_surge: i64,
+}
+
+impl Default for ControlPlaneRolloutStrategy {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "controlPlaneRolloutStrategy")]
+    pub control_plane_rollout_strategy: ControlPlaneRolloutStrategy,
+}
+
+const ROLLOUT_STRATEGY_TEMPLATE: &str = indoc! {r#"
+    type: RollingUpdate
+    rollingUpdate:
+      maxSurge: {{ .controlPlaneRolloutStrategy.maxSurge }}
+"#};
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        vec![ClusterClassPatches {
+            name: "controlPlaneRolloutStrategy".into(),
+            definitions: Some(vec![ClusterClassPatchesDefinitions {
+                selector: ClusterClassPatchesDefinitionsSelector {
+                    api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+                    kind: KubeadmControlPlaneTemplate::api_resource().kind,
+                    match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                        control_plane: Some(true),
+                        ..Default::default()
+                    },
+                },
+                json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                    op: "add".into(),
+                    path: "/spec/template/spec/rolloutStrategy".into(),
+                    value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                        template: Some(ROLLOUT_STRATEGY_TEMPLATE.into()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }],
+            }]),
+            ..Default::default()
+        }]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cluster_api::kubeadmcontrolplanetemplates::{
+            KubeadmControlPlaneTemplateTemplateSpecRolloutStrategy,
+            KubeadmControlPlaneTemplateTemplateSpecRolloutStrategyRollingUpdate,
+        },
+        features::test::TestClusterResources,
+        resources::fixtures::default_values,
+    };
+    use k8s_openapi::apimachinery::pkg::util::intstr::IntOrString;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    fn test_patches(#[case] max_surge: i64) {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.control_plane_rollout_strategy = ControlPlaneRolloutStrategy::builder()
+            .max_surge(max_surge)
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources
+                .kubeadm_control_plane_template
+                .spec
+                .template
+                .spec
+                .rollout_strategy,
+            Some(KubeadmControlPlaneTemplateTemplateSpecRolloutStrategy {
+                r#type: Some("RollingUpdate".into()),
+                rolling_update: Some(
+                    KubeadmControlPlaneTemplateTemplateSpecRolloutStrategyRollingUpdate {
+                        max_surge: Some(IntOrString::Int(max_surge as i32)),
+                    }
+                ),
+            })
+        );
+    }
+}
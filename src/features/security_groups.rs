@@ -0,0 +1,549 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources, ClusterClassVariables,
+            ClusterClassVariablesSchema,
+        },
+        openstackclustertemplates::OpenStackClusterTemplate,
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use cluster_feature_derive::ClusterFeatureValues;
+use indoc::{formatdoc, indoc};
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SecurityGroupRuleDirection {
+    #[default]
+    Ingress,
+    Egress,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum SecurityGroupRuleEtherType {
+    #[default]
+    IPv4,
+    IPv6,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecurityGroupRuleRemoteManagedGroup {
+    Bastion,
+    ControlPlane,
+    Worker,
+}
+
+/// A security group rule which is rendered into one of the managed security
+/// groups of the `OpenStackCluster`.
+///
+/// Every field is always serialized (empty values mean "unset") so that the
+/// ClusterClass patch template can test for them without tripping over
+/// missing keys.  The string fields are rendered as quoted YAML strings, so
+/// the schema rejects double quotes, backslashes and line breaks in the
+/// free-form ones and only allows protocol names or numbers and CIDRs in the
+/// others.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct SecurityGroupRule {
+    #[schemars(regex(pattern = r#"^[^"\\\n\r]+$"#))]
+    pub name: String,
+
+    #[serde(default)]
+    #[schemars(regex(pattern = r#"^[^"\\\n\r]*$"#))]
+    #[builder(default)]
+    pub description: String,
+
+    #[serde(default)]
+    #[builder(default)]
+    pub direction: SecurityGroupRuleDirection,
+
+    #[serde(default, rename = "etherType")]
+    #[builder(default)]
+    pub ether_type: SecurityGroupRuleEtherType,
+
+    #[serde(default)]
+    #[schemars(regex(pattern = r"^[a-z0-9-]*$"))]
+    #[builder(default)]
+    pub protocol: String,
+
+    #[serde(default, rename = "portRangeMin")]
+    #[builder(default)]
+    pub port_range_min: i64,
+
+    #[serde(default, rename = "portRangeMax")]
+    #[builder(default)]
+    pub port_range_max: i64,
+
+    #[serde(default, rename = "remoteIPPrefix")]
+    #[schemars(regex(pattern = r"^([0-9a-fA-F.:]+/[0-9]{1,3})?$"))]
+    #[builder(default)]
+    pub remote_ip_prefix: String,
+
+    #[serde(default, rename = "remoteManagedGroups")]
+    #[builder(default)]
+    pub remote_managed_groups: Vec<SecurityGroupRuleRemoteManagedGroup>,
+}
+
+/// An IPv4 CIDR which is allowed to reach the NodePort range, the rules are
+/// rendered with the `IPv4` ether type so IPv6 CIDRs are rejected.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct NodePortAllowedCidr(
+    #[schemars(regex(pattern = r"^[0-9]{1,3}(\.[0-9]{1,3}){3}/[0-9]{1,2}$"))] pub String,
+);
+
+impl From<&str> for NodePortAllowedCidr {
+    fn from(cidr: &str) -> Self {
+        Self(cidr.to_owned())
+    }
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "nodePortAllowedCIDRs")]
+    pub node_port_allowed_cidrs: Vec<NodePortAllowedCidr>,
+
+    #[serde(rename = "allNodesSecurityGroupRules")]
+    pub all_nodes_security_group_rules: Vec<SecurityGroupRule>,
+
+    #[serde(rename = "controlPlaneNodesSecurityGroupRules")]
+    pub control_plane_nodes_security_group_rules: Vec<SecurityGroupRule>,
+
+    #[serde(rename = "workerNodesSecurityGroupRules")]
+    pub worker_nodes_security_group_rules: Vec<SecurityGroupRule>,
+}
+
+/// Go template which renders the NodePort rules (TCP and UDP) for every CIDR
+/// in `nodePortAllowedCIDRs`.
+const NODE_PORT_RULES_TEMPLATE: &str = indoc! {r#"
+    {{- range .nodePortAllowedCIDRs }}
+    - name: "Node Port (UDP, {{ if eq . "0.0.0.0/0" }}anywhere{{ else }}{{ . }}{{ end }})"
+      direction: ingress
+      etherType: IPv4
+      protocol: udp
+      portRangeMin: 30000
+      portRangeMax: 32767
+      remoteIPPrefix: "{{ . }}"
+    - name: "Node Port (TCP, {{ if eq . "0.0.0.0/0" }}anywhere{{ else }}{{ . }}{{ end }})"
+      direction: ingress
+      etherType: IPv4
+      protocol: tcp
+      portRangeMin: 30000
+      portRangeMax: 32767
+      remoteIPPrefix: "{{ . }}"
+    {{- end }}
+"#};
+
+/// Returns a Go template which renders every [`SecurityGroupRule`] in the
+/// given variable as a CAPO `SecurityGroupRuleSpec`, omitting unset fields.
+fn security_group_rules_template(variable: &str) -> String {
+    formatdoc! {r#"
+        {{{{- range .{variable} }}}}
+        - name: "{{{{ .name }}}}"
+          direction: {{{{ .direction }}}}
+          etherType: {{{{ .etherType }}}}
+          {{{{- if .description }}}}
+          description: "{{{{ .description }}}}"
+          {{{{- end }}}}
+          {{{{- if .protocol }}}}
+          protocol: "{{{{ .protocol }}}}"
+          {{{{- end }}}}
+          {{{{- if .portRangeMin }}}}
+          portRangeMin: {{{{ .portRangeMin }}}}
+          {{{{- end }}}}
+          {{{{- if .portRangeMax }}}}
+          portRangeMax: {{{{ .portRangeMax }}}}
+          {{{{- end }}}}
+          {{{{- if .remoteIPPrefix }}}}
+          remoteIPPrefix: "{{{{ .remoteIPPrefix }}}}"
+          {{{{- end }}}}
+          {{{{- if .remoteManagedGroups }}}}
+          remoteManagedGroups:
+          {{{{- range .remoteManagedGroups }}}}
+            - {{{{ . }}}}
+          {{{{- end }}}}
+          {{{{- end }}}}
+        {{{{- end }}}}
+    "#}
+}
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        vec![
+            ClusterClassPatches {
+                name: "allNodesSecurityGroupRules".into(),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackClusterTemplate::api_resource().api_version,
+                        kind: OpenStackClusterTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            infrastructure_cluster: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/managedSecurityGroups/allNodesSecurityGroupRules"
+                            .into(),
+                        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                            template: Some(format!(
+                                "{{{{- if or .nodePortAllowedCIDRs .allNodesSecurityGroupRules }}}}\n{}{}{{{{- else }}}}\n[]\n{{{{- end }}}}\n",
+                                NODE_PORT_RULES_TEMPLATE,
+                                security_group_rules_template("allNodesSecurityGroupRules"),
+                            )),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }],
+                }]),
+                ..Default::default()
+            },
+            ClusterClassPatches {
+                name: "controlPlaneNodesSecurityGroupRules".into(),
+                enabled_if: Some(
+                    "{{ if .controlPlaneNodesSecurityGroupRules }}true{{end}}".into(),
+                ),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackClusterTemplate::api_resource().api_version,
+                        kind: OpenStackClusterTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            infrastructure_cluster: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/managedSecurityGroups/controlPlaneNodesSecurityGroupRules"
+                            .into(),
+                        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                            template: Some(security_group_rules_template(
+                                "controlPlaneNodesSecurityGroupRules",
+                            )),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }],
+                }]),
+                ..Default::default()
+            },
+            ClusterClassPatches {
+                name: "workerNodesSecurityGroupRules".into(),
+                enabled_if: Some("{{ if .workerNodesSecurityGroupRules }}true{{end}}".into()),
+                definitions: Some(vec![ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackClusterTemplate::api_resource().api_version,
+                        kind: OpenStackClusterTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            infrastructure_cluster: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
+                        op: "add".into(),
+                        path: "/spec/template/spec/managedSecurityGroups/workerNodesSecurityGroupRules"
+                            .into(),
+                        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                            template: Some(security_group_rules_template(
+                                "workerNodesSecurityGroupRules",
+                            )),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }],
+                }]),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cluster_api::openstackclustertemplates::{
+            OpenStackClusterTemplateTemplateSpecManagedSecurityGroupsAllNodesSecurityGroupRules,
+            OpenStackClusterTemplateTemplateSpecManagedSecurityGroupsControlPlaneNodesSecurityGroupRules,
+        },
+        features::{test::TestClusterResources, OPENSTACK_CLUSTER_TEMPLATE},
+        resources::fixtures::default_values,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn node_port_rule(
+        name: &str,
+        protocol: &str,
+        cidr: &str,
+    ) -> OpenStackClusterTemplateTemplateSpecManagedSecurityGroupsAllNodesSecurityGroupRules {
+        OpenStackClusterTemplateTemplateSpecManagedSecurityGroupsAllNodesSecurityGroupRules {
+            name: name.into(),
+            direction: "ingress".into(),
+            ether_type: Some("IPv4".into()),
+            protocol: Some(protocol.into()),
+            port_range_min: Some(30000),
+            port_range_max: Some(32767),
+            remote_ip_prefix: Some(cidr.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_node_port_rules_are_unchanged() {
+        let feature = Feature {};
+        let values = default_values();
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let managed_security_groups = resources
+            .openstack_cluster_template
+            .spec
+            .template
+            .spec
+            .managed_security_groups
+            .expect("managed security groups should be set");
+
+        assert_eq!(
+            managed_security_groups,
+            OPENSTACK_CLUSTER_TEMPLATE
+                .spec
+                .template
+                .spec
+                .managed_security_groups
+                .clone()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_restricted_node_port_rules() {
+        let feature = Feature {};
+        let mut values = default_values();
+        values.node_port_allowed_cidrs = vec!["192.168.0.0/16".into()];
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources
+                .openstack_cluster_template
+                .spec
+                .template
+                .spec
+                .managed_security_groups
+                .expect("managed security groups should be set")
+                .all_nodes_security_group_rules,
+            Some(vec![
+                node_port_rule("Node Port (UDP, 192.168.0.0/16)", "udp", "192.168.0.0/16"),
+                node_port_rule("Node Port (TCP, 192.168.0.0/16)", "tcp", "192.168.0.0/16"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_disabled_node_port_rules() {
+        let feature = Feature {};
+        let mut values = default_values();
+        values.node_port_allowed_cidrs = vec![];
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources
+                .openstack_cluster_template
+                .spec
+                .template
+                .spec
+                .managed_security_groups
+                .expect("managed security groups should be set")
+                .all_nodes_security_group_rules,
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn test_all_nodes_rules_are_appended_to_node_port_rules() {
+        let feature = Feature {};
+        let mut values = default_values();
+        values.node_port_allowed_cidrs = vec!["10.0.0.0/8".into()];
+        values.all_nodes_security_group_rules = vec![SecurityGroupRule::builder()
+            .name("Prometheus: node-exporter".into())
+            .description("Allow scraping from the monitoring network".into())
+            .protocol("tcp".into())
+            .port_range_min(9100)
+            .port_range_max(9100)
+            .remote_ip_prefix("172.16.0.0/24".into())
+            .build()];
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources
+                .openstack_cluster_template
+                .spec
+                .template
+                .spec
+                .managed_security_groups
+                .expect("managed security groups should be set")
+                .all_nodes_security_group_rules,
+            Some(vec![
+                node_port_rule("Node Port (UDP, 10.0.0.0/8)", "udp", "10.0.0.0/8"),
+                node_port_rule("Node Port (TCP, 10.0.0.0/8)", "tcp", "10.0.0.0/8"),
+                OpenStackClusterTemplateTemplateSpecManagedSecurityGroupsAllNodesSecurityGroupRules {
+                    name: "Prometheus: node-exporter".into(),
+                    description: Some("Allow scraping from the monitoring network".into()),
+                    direction: "ingress".into(),
+                    ether_type: Some("IPv4".into()),
+                    protocol: Some("tcp".into()),
+                    port_range_min: Some(9100),
+                    port_range_max: Some(9100),
+                    remote_ip_prefix: Some("172.16.0.0/24".into()),
+                    ..Default::default()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_control_plane_and_worker_rules() {
+        let feature = Feature {};
+        let mut values = default_values();
+        values.control_plane_nodes_security_group_rules = vec![SecurityGroupRule::builder()
+            .name("Konnectivity".into())
+            .protocol("tcp".into())
+            .port_range_min(8132)
+            .port_range_max(8132)
+            .remote_managed_groups(vec![SecurityGroupRuleRemoteManagedGroup::Worker])
+            .build()];
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let managed_security_groups = resources
+            .openstack_cluster_template
+            .spec
+            .template
+            .spec
+            .managed_security_groups
+            .expect("managed security groups should be set");
+
+        assert_eq!(
+            managed_security_groups.control_plane_nodes_security_group_rules,
+            Some(vec![
+                OpenStackClusterTemplateTemplateSpecManagedSecurityGroupsControlPlaneNodesSecurityGroupRules {
+                    name: "Konnectivity".into(),
+                    direction: "ingress".into(),
+                    ether_type: Some("IPv4".into()),
+                    protocol: Some("tcp".into()),
+                    port_range_min: Some(8132),
+                    port_range_max: Some(8132),
+                    remote_managed_groups: Some(vec!["worker".into()]),
+                    ..Default::default()
+                }
+            ])
+        );
+        assert_eq!(managed_security_groups.worker_nodes_security_group_rules, None);
+    }
+
+    #[test]
+    fn test_protocol_number_is_rendered_as_string() {
+        let feature = Feature {};
+        let mut values = default_values();
+        values.worker_nodes_security_group_rules = vec![SecurityGroupRule::builder()
+            .name("VRRP".into())
+            .protocol("112".into())
+            .remote_ip_prefix("10.0.0.0/8".into())
+            .build()];
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let rules = resources
+            .openstack_cluster_template
+            .spec
+            .template
+            .spec
+            .managed_security_groups
+            .expect("managed security groups should be set")
+            .worker_nodes_security_group_rules
+            .expect("worker rules should be set");
+
+        assert_eq!(rules[0].protocol, Some("112".into()));
+        assert_eq!(rules[0].remote_ip_prefix, Some("10.0.0.0/8".into()));
+    }
+
+    #[test]
+    fn test_protocol_and_remote_ip_prefix_are_constrained() {
+        let schema = serde_json::to_value(schemars::schema_for!(SecurityGroupRule)).unwrap();
+        let properties = &schema["properties"];
+
+        assert_eq!(properties["protocol"]["pattern"], "^[a-z0-9-]*$");
+        assert_eq!(
+            properties["remoteIPPrefix"]["pattern"],
+            "^([0-9a-fA-F.:]+/[0-9]{1,3})?$"
+        );
+    }
+
+    #[test]
+    fn test_free_form_fields_reject_line_breaks() {
+        let schema = serde_json::to_value(schemars::schema_for!(SecurityGroupRule)).unwrap();
+        let properties = &schema["properties"];
+
+        assert_eq!(properties["name"]["pattern"], r#"^[^"\\\n\r]+$"#);
+        assert_eq!(properties["description"]["pattern"], r#"^[^"\\\n\r]*$"#);
+    }
+
+    #[test]
+    fn test_node_port_allowed_cidrs_are_ipv4() {
+        let schema = serde_json::to_value(schemars::schema_for!(NodePortAllowedCidr)).unwrap();
+
+        assert_eq!(
+            schema["pattern"],
+            r"^[0-9]{1,3}(\.[0-9]{1,3}){3}/[0-9]{1,2}$"
+        );
+    }
+
+    #[test]
+    fn test_variables_are_inlined() {
+        let feature = Feature {};
+        let variables = feature.variables();
+
+        assert_eq!(variables.len(), 4);
+        for variable in variables {
+            let schema = serde_json::to_string(&variable.schema.open_apiv3_schema).unwrap();
+            assert!(
+                !schema.contains("$ref"),
+                "variable `{}` schema must not contain references: {}",
+                variable.name,
+                schema
+            );
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources,
+            ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass,
+            ClusterClassVariables, ClusterClassVariablesSchema,
+        },
+        openstackmachinetemplates::OpenStackMachineTemplate,
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use cluster_feature_derive::ClusterFeatureValues;
+use indoc::formatdoc;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+/// A user supplied key/value pair which is added to the server metadata, it
+/// is rendered as a quoted YAML string so double quotes and backslashes are
+/// rejected by the schema.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct ServerMetadata {
+    #[schemars(regex(pattern = r#"^[^"\\]{1,255}$"#))]
+    pub key: String,
+
+    #[schemars(regex(pattern = r#"^[^"\\]{0,255}$"#))]
+    pub value: String,
+}
+
+/// A user supplied Nova server tag, it is rendered as a quoted YAML string so
+/// it is held to the limits of Nova on top of rejecting double quotes and
+/// backslashes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct ServerTag(
+    #[schemars(regex(pattern = r#"^[^/"\\]+$"#), length(min = 1, max = 60))] pub String,
+);
+
+impl From<&str> for ServerTag {
+    fn from(tag: &str) -> Self {
+        Self(tag.to_owned())
+    }
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    #[serde(rename = "clusterUUID")]
+    pub cluster_uuid: String,
+
+    #[serde(rename = "nodeGroupName")]
+    pub node_group_name: String,
+
+    #[serde(rename = "serverTags")]
+    pub server_tags: Vec<ServerTag>,
+
+    #[serde(rename = "serverMetadata")]
+    pub server_metadata: Vec<ServerMetadata>,
+}
+
+/// Returns a Go template which renders the Nova server tags identifying the
+/// cluster, node group and role, followed by the user supplied tags.
+fn server_tags_template(role: &str) -> String {
+    formatdoc! {r#"
+        - "magnum-cluster-uuid={{{{ .clusterUUID }}}}"
+        - "magnum-nodegroup={{{{ .nodeGroupName }}}}"
+        - "magnum-role={role}"
+        {{{{- range .serverTags }}}}
+        - "{{{{ . }}}}"
+        {{{{- end }}}}
+    "#}
+}
+
+/// Returns a Go template which renders the Nova server metadata identifying
+/// the cluster, node group and role, followed by the user supplied metadata.
+fn server_metadata_template(role: &str) -> String {
+    formatdoc! {r#"
+        - key: magnum_cluster_uuid
+          value: "{{{{ .clusterUUID }}}}"
+        - key: magnum_nodegroup
+          value: "{{{{ .nodeGroupName }}}}"
+        - key: magnum_role
+          value: "{role}"
+        {{{{- range .serverMetadata }}}}
+        - key: "{{{{ .key }}}}"
+          value: "{{{{ .value }}}}"
+        {{{{- end }}}}
+    "#}
+}
+
+fn json_patches(role: &str) -> Vec<ClusterClassPatchesDefinitionsJsonPatches> {
+    vec![
+        ClusterClassPatchesDefinitionsJsonPatches {
+            op: "add".into(),
+            path: "/spec/template/spec/tags".into(),
+            value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                template: Some(server_tags_template(role)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ClusterClassPatchesDefinitionsJsonPatches {
+            op: "add".into(),
+            path: "/spec/template/spec/serverMetadata".into(),
+            value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                template: Some(server_metadata_template(role)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    ]
+}
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        vec![ClusterClassPatches {
+            name: "serverMetadata".into(),
+            definitions: Some(vec![
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackMachineTemplate::api_resource().api_version,
+                        kind: OpenStackMachineTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            control_plane: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: json_patches("control-plane"),
+                },
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackMachineTemplate::api_resource().api_version,
+                        kind: OpenStackMachineTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            machine_deployment_class: Some(
+                                ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
+                                    names: Some(vec!["default-worker".to_string()]),
+                                },
+                            ),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: json_patches("worker"),
+                },
+            ]),
+            ..Default::default()
+        }]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cluster_api::openstackmachinetemplates::OpenStackMachineTemplateTemplateSpecServerMetadata,
+        features::test::TestClusterResources, resources::fixtures::default_values,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn metadata(key: &str, value: &str) -> OpenStackMachineTemplateTemplateSpecServerMetadata {
+        OpenStackMachineTemplateTemplateSpecServerMetadata {
+            key: key.into(),
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn test_apply_patches() {
+        let feature = Feature {};
+
+        let values = default_values();
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        for (template, role) in [
+            (
+                &resources.control_plane_openstack_machine_template,
+                "control-plane",
+            ),
+            (&resources.worker_openstack_machine_template, "worker"),
+        ] {
+            let spec = &template.spec.template.spec;
+
+            assert_eq!(
+                spec.tags,
+                Some(vec![
+                    format!("magnum-cluster-uuid={}", values.cluster_uuid),
+                    format!("magnum-nodegroup={}", values.node_group_name),
+                    format!("magnum-role={}", role),
+                ])
+            );
+            assert_eq!(
+                spec.server_metadata,
+                Some(vec![
+                    metadata("magnum_cluster_uuid", &values.cluster_uuid),
+                    metadata("magnum_nodegroup", &values.node_group_name),
+                    metadata("magnum_role", role),
+                ])
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_patches_with_user_tags_and_metadata() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.server_tags = vec!["billing:team-a".into(), "env=prod".into()];
+        values.server_metadata = vec![ServerMetadata::builder()
+            .key("cost-center".into())
+            .value("1234".into())
+            .build()];
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let spec = &resources
+            .worker_openstack_machine_template
+            .spec
+            .template
+            .spec;
+
+        assert_eq!(
+            spec.tags,
+            Some(vec![
+                format!("magnum-cluster-uuid={}", values.cluster_uuid),
+                format!("magnum-nodegroup={}", values.node_group_name),
+                "magnum-role=worker".to_string(),
+                "billing:team-a".to_string(),
+                "env=prod".to_string(),
+            ])
+        );
+        assert_eq!(
+            spec.server_metadata,
+            Some(vec![
+                metadata("magnum_cluster_uuid", &values.cluster_uuid),
+                metadata("magnum_nodegroup", &values.node_group_name),
+                metadata("magnum_role", "worker"),
+                metadata("cost-center", "1234"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_server_tags_are_constrained() {
+        let feature = Feature {};
+        let variable = feature
+            .variables()
+            .into_iter()
+            .find(|variable| variable.name == "serverTags")
+            .expect("variable should be set");
+        let schema = serde_json::to_value(&variable.schema.open_apiv3_schema).unwrap();
+
+        assert_eq!(schema["items"]["pattern"], r#"^[^/"\\]+$"#);
+        assert_eq!(schema["items"]["minLength"], 1);
+        assert_eq!(schema["items"]["maxLength"], 60);
+    }
+}
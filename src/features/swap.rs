@@ -0,0 +1,315 @@
+use crate::{
+    cluster_api::{
+        clusterclasses::{
+            ClusterClassPatches, ClusterClassPatchesDefinitions,
+            ClusterClassPatchesDefinitionsJsonPatches,
+            ClusterClassPatchesDefinitionsJsonPatchesValueFrom,
+            ClusterClassPatchesDefinitionsSelector,
+            ClusterClassPatchesDefinitionsSelectorMatchResources,
+            ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass,
+            ClusterClassVariables, ClusterClassVariablesSchema,
+        },
+        kubeadmconfigtemplates::KubeadmConfigTemplate,
+        kubeadmcontrolplanetemplates::{
+            KubeadmControlPlaneTemplate,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles,
+            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding,
+        },
+    },
+    features::{
+        ClusterClassVariablesSchemaExt, ClusterFeatureEntry, ClusterFeaturePatches,
+        ClusterFeatureVariables,
+    },
+};
+use base64::prelude::*;
+use cluster_feature_derive::ClusterFeatureValues;
+use indoc::indoc;
+use kube::CustomResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use typed_builder::TypedBuilder;
+
+/// Directory of the kubeadm patches, which kubeadm applies to the generated
+/// kubelet configuration.
+const PATCHES_DIRECTORY: &str = "/etc/kubernetes/patches";
+
+const KUBELET_DROP_IN: &str = indoc!(
+    r#"
+    [Service]
+    ExecStartPre=/etc/kubernetes/swap.sh {{ .swap.size }}
+    "#
+);
+
+const KUBELET_CONFIGURATION_PATCH: &str = indoc!(
+    r#"
+    failSwapOn: false
+    memorySwap:
+      swapBehavior: {{ .swap.behavior }}
+    "#
+);
+
+/// Whether the workloads are allowed to use the swap of the node, `NoSwap`
+/// keeps it for the processes outside of Kubernetes.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum SwapBehavior {
+    #[default]
+    NoSwap,
+    LimitedSwap,
+}
+
+/// Swap file created on every node, where a zero size keeps swap disabled.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct SwapConfig {
+    /// Size (in MiB) of the swap file.
+    #[builder(default)]
+    #[serde(default)]
+    #[schemars(range(min = 0))]
+    pub size: i64,
+
+    #[builder(default)]
+    #[serde(default)]
+    pub behavior: SwapBehavior,
+}
+
+#[derive(Serialize, Deserialize, ClusterFeatureValues)]
+#[allow(dead_code)]
+pub struct FeatureValues {
+    pub swap: SwapConfig,
+}
+
+/// Returns the patches for the kubeadm config at `path`, which add the files
+/// and point the given configurations to the kubeadm patches.  The files have
+/// the same schema in the `KubeadmControlPlaneTemplate` and the
+/// `KubeadmConfigTemplate`, which is why they are rendered as templates.
+fn json_patches(
+    path: &str,
+    configurations: &[&str],
+) -> Vec<ClusterClassPatchesDefinitionsJsonPatches> {
+    let files = [
+        KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+            path: "/etc/kubernetes/swap.sh".to_string(),
+            owner: Some("root:root".into()),
+            permissions: Some("0755".to_string()),
+            content: Some(BASE64_STANDARD.encode(include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/data/swap/swap.sh"
+            )))),
+            encoding: Some(
+                KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFilesEncoding::Base64,
+            ),
+            ..Default::default()
+        },
+        // NOTE: The swap is enabled before every start of the kubelet, which
+        //       also brings it back after a reboot.  The `containerdConfig`
+        //       patch reloads systemd before kubeadm starts the kubelet.
+        KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+            path: "/etc/systemd/system/kubelet.service.d/05-swap.conf".to_string(),
+            owner: Some("root:root".into()),
+            permissions: Some("0644".to_string()),
+            content: Some(KUBELET_DROP_IN.to_string()),
+            ..Default::default()
+        },
+        KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecFiles {
+            path: format!("{}/kubeletconfiguration0+merge.yaml", PATCHES_DIRECTORY),
+            owner: Some("root:root".into()),
+            permissions: Some("0644".to_string()),
+            content: Some(KUBELET_CONFIGURATION_PATCH.to_string()),
+            ..Default::default()
+        },
+    ];
+
+    let mut patches: Vec<ClusterClassPatchesDefinitionsJsonPatches> = files
+        .iter()
+        .map(|file| ClusterClassPatchesDefinitionsJsonPatches {
+            op: "add".into(),
+            path: format!("{}/files/-", path),
+            value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+                template: Some(serde_yaml::to_string(file).unwrap()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .collect();
+
+    patches.extend(configurations.iter().map(|configuration| {
+        ClusterClassPatchesDefinitionsJsonPatches {
+            op: "add".into(),
+            path: format!("{}/{}/patches", path, configuration),
+            value: Some(json!({ "directory": PATCHES_DIRECTORY })),
+            ..Default::default()
+        }
+    }));
+
+    patches
+}
+
+pub struct Feature {}
+
+impl ClusterFeaturePatches for Feature {
+    fn patches(&self) -> Vec<ClusterClassPatches> {
+        vec![ClusterClassPatches {
+            name: "swap".into(),
+            enabled_if: Some("{{ if .swap.size }}true{{end}}".into()),
+            definitions: Some(vec![
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+                        kind: KubeadmControlPlaneTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            control_plane: Some(true),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: json_patches(
+                        "/spec/template/spec/kubeadmConfigSpec",
+                        &["initConfiguration", "joinConfiguration"],
+                    ),
+                },
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: KubeadmConfigTemplate::api_resource().api_version,
+                        kind: KubeadmConfigTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            machine_deployment_class: Some(
+                                ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
+                                    names: Some(vec!["default-worker".to_string()]),
+                                },
+                            ),
+                            ..Default::default()
+                        },
+                    },
+                    json_patches: json_patches("/spec/template/spec", &["joinConfiguration"]),
+                },
+            ]),
+            ..Default::default()
+        }]
+    }
+}
+
+inventory::submit! {
+    ClusterFeatureEntry{ feature: &Feature {} }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        features::{
+            test::TestClusterResources, KUBEADM_CONFIG_TEMPLATE, KUBEADM_CONTROL_PLANE_TEMPLATE,
+        },
+        resources::fixtures::default_values,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_disabled() {
+        let feature = Feature {};
+
+        let values = default_values();
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        assert_eq!(
+            resources.kubeadm_control_plane_template,
+            KUBEADM_CONTROL_PLANE_TEMPLATE.clone()
+        );
+        assert_eq!(
+            resources.kubeadm_config_template,
+            KUBEADM_CONFIG_TEMPLATE.clone()
+        );
+    }
+
+    #[test]
+    fn test_enabled() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.swap = SwapConfig::builder()
+            .size(2048)
+            .behavior(SwapBehavior::LimitedSwap)
+            .build();
+
+        let patches = feature.patches();
+
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let kubeadm_config_spec = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+
+        let files = kubeadm_config_spec.files.expect("files should be set");
+        let drop_in = files
+            .iter()
+            .find(|file| file.path == "/etc/systemd/system/kubelet.service.d/05-swap.conf")
+            .expect("kubelet drop-in should be added");
+        assert_eq!(
+            drop_in.content,
+            Some("[Service]\nExecStartPre=/etc/kubernetes/swap.sh 2048\n".to_string())
+        );
+        let configuration_patch = files
+            .iter()
+            .find(|file| file.path == "/etc/kubernetes/patches/kubeletconfiguration0+merge.yaml")
+            .expect("kubelet configuration patch should be added");
+        assert_eq!(
+            configuration_patch.content,
+            Some("failSwapOn: false\nmemorySwap:\n  swapBehavior: LimitedSwap\n".to_string())
+        );
+        assert!(files
+            .iter()
+            .any(|file| file.path == "/etc/kubernetes/swap.sh"));
+
+        assert_eq!(
+            kubeadm_config_spec
+                .init_configuration
+                .expect("init configuration should be set")
+                .patches
+                .expect("patches should be set")
+                .directory,
+            Some(PATCHES_DIRECTORY.to_string())
+        );
+        assert_eq!(
+            kubeadm_config_spec
+                .join_configuration
+                .expect("join configuration should be set")
+                .patches
+                .expect("patches should be set")
+                .directory,
+            Some(PATCHES_DIRECTORY.to_string())
+        );
+
+        let spec = resources
+            .kubeadm_config_template
+            .spec
+            .template
+            .spec
+            .expect("spec should be set");
+
+        let files = spec.files.expect("files should be set");
+        for path in [
+            "/etc/kubernetes/swap.sh",
+            "/etc/systemd/system/kubelet.service.d/05-swap.conf",
+            "/etc/kubernetes/patches/kubeletconfiguration0+merge.yaml",
+        ] {
+            assert!(
+                files.iter().any(|file| file.path == path),
+                "{} should be added",
+                path
+            );
+        }
+        assert_eq!(
+            spec.join_configuration
+                .expect("join configuration should be set")
+                .patches
+                .expect("patches should be set")
+                .directory,
+            Some(PATCHES_DIRECTORY.to_string())
+        );
+    }
+}
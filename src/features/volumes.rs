@@ -9,15 +9,8 @@ use crate::{
             ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass,
             ClusterClassVariables, ClusterClassVariablesSchema,
         },
-        kubeadmconfigtemplates::{
-            KubeadmConfigTemplate, KubeadmConfigTemplateTemplateSpecDiskSetupFilesystems,
-            KubeadmConfigTemplateTemplateSpecDiskSetupPartitions,
-        },
-        kubeadmcontrolplanetemplates::{
-            KubeadmControlPlaneTemplate,
-            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupFilesystems,
-            KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupPartitions,
-        },
+        kubeadmconfigtemplates::KubeadmConfigTemplate,
+        kubeadmcontrolplanetemplates::KubeadmControlPlaneTemplate,
         openstackmachinetemplates::OpenStackMachineTemplate,
     },
     features::{
@@ -28,8 +21,47 @@ use crate::{
 use cluster_feature_derive::ClusterFeatureValues;
 use indoc::indoc;
 use kube::CustomResourceExt;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use typed_builder::TypedBuilder;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AdditionalVolumeFilesystem {
+    #[default]
+    Ext4,
+    Xfs,
+}
+
+/// A volume which is attached to every machine, formatted and mounted at the
+/// given path.  The name is also used as the filesystem label, which is why it
+/// is limited to 12 characters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema, TypedBuilder)]
+pub struct AdditionalVolume {
+    #[schemars(regex(pattern = r"^[a-z0-9][a-z0-9-]{0,11}$"))]
+    pub name: String,
+
+    #[schemars(range(min = 1))]
+    pub size: i64,
+
+    #[builder(default)]
+    #[serde(default, rename = "type")]
+    #[schemars(regex(pattern = r#"^[^"\\]*$"#))]
+    pub volume_type: String,
+
+    #[builder(default)]
+    #[serde(default, rename = "availabilityZone")]
+    #[schemars(regex(pattern = r#"^[^"\\]*$"#))]
+    pub availability_zone: String,
+
+    #[builder(default)]
+    #[serde(default)]
+    pub filesystem: AdditionalVolumeFilesystem,
+
+    #[serde(rename = "mountPath")]
+    #[schemars(regex(pattern = r#"^/[^"\\]*$"#))]
+    pub mount_path: String,
+}
 
 #[derive(Serialize, Deserialize, ClusterFeatureValues)]
 #[allow(dead_code)]
@@ -57,429 +89,348 @@ pub struct FeatureValues {
 
     #[serde(rename = "availabilityZone")]
     pub availability_zone: String,
+
+    #[serde(rename = "additionalVolumes")]
+    pub additional_volumes: Vec<AdditionalVolume>,
+}
+
+/// The machines a set of volumes is rendered for, the etcd volume is only
+/// attached to the control plane while the docker volume and the additional
+/// volumes are attached to both.
+#[derive(Clone, Copy)]
+enum Role {
+    ControlPlane,
+    Worker,
+}
+
+impl Role {
+    /// Go template condition which is true if any volume is attached.
+    fn has_volumes(&self) -> &'static str {
+        match self {
+            Role::ControlPlane => {
+                "or .enableEtcdVolume .enableDockerVolume .additionalVolumes"
+            }
+            Role::Worker => "or .enableDockerVolume .additionalVolumes",
+        }
+    }
+
+    /// Wraps the given template so that it renders nothing, which leaves the
+    /// patched field unset, unless a volume is attached.
+    fn if_has_volumes(&self, template: &str) -> String {
+        format!(
+            "{{{{- if {} }}}}\n{}{{{{- end }}}}\n",
+            self.has_volumes(),
+            template
+        )
+    }
+
+    /// Go template which renders the device letter of the docker volume.
+    fn docker_letter(&self) -> &'static str {
+        match self {
+            Role::ControlPlane => "{{ if .enableEtcdVolume }}c{{ else }}b{{ end }}",
+            Role::Worker => "b",
+        }
+    }
+
+    /// Go template which renders the device letter of the additional volume at
+    /// index `$i`, which comes after the etcd and docker volumes.
+    fn additional_letter(&self) -> String {
+        match self {
+            Role::ControlPlane => format!(
+                "{{{{ if and $.enableEtcdVolume $.enableDockerVolume }}}}{}{{{{ else if or $.enableEtcdVolume $.enableDockerVolume }}}}{}{{{{ else }}}}{}{{{{ end }}}}",
+                letter_at_index(2),
+                letter_at_index(1),
+                letter_at_index(0)
+            ),
+            Role::Worker => format!(
+                "{{{{ if $.enableDockerVolume }}}}{}{{{{ else }}}}{}{{{{ end }}}}",
+                letter_at_index(1),
+                letter_at_index(0)
+            ),
+        }
+    }
+}
+
+// NOTE: Volumes are attached in the order of `additionalBlockDevices` right
+//       after the root disk, so the device of each volume is derived from its
+//       position in the list.
+const DEVICE_LETTERS: &str = "bcdefghijklmnopqrstuvwxyz";
+
+/// Returns a Go template which renders the device letter for the index `$i`
+/// once `offset` volumes have already been attached.
+fn letter_at_index(offset: usize) -> String {
+    let cases = DEVICE_LETTERS
+        .chars()
+        .skip(offset)
+        .enumerate()
+        .map(|(i, letter)| format!("eq $i {} }}}}{}", i, letter))
+        .collect::<Vec<_>>()
+        .join("{{ else if ");
+
+    format!("{{{{ if {}{{{{ end }}}}", cases)
+}
+
+fn device(letter: &str) -> String {
+    format!(
+        r#"{{{{ if eq $.hardwareDiskBus "scsi" }}}}/dev/sd{{{{ else }}}}/dev/vd{{{{ end }}}}{}"#,
+        letter
+    )
+}
+
+const ETCD_BLOCK_DEVICE_TEMPLATE: &str = indoc! {r#"
+    {{- if .enableEtcdVolume }}
+    - name: etcd
+      sizeGiB: {{ .etcdVolumeSize }}
+      storage:
+        type: Volume
+        volume:
+          type: "{{ .etcdVolumeType }}"
+          {{- if .availabilityZone }}
+          availabilityZone:
+            name: "{{ .availabilityZone }}"
+          {{- end }}
+    {{- end }}
+"#};
+
+const DOCKER_BLOCK_DEVICE_TEMPLATE: &str = indoc! {r#"
+    {{- if .enableDockerVolume }}
+    - name: docker
+      sizeGiB: {{ .dockerVolumeSize }}
+      storage:
+        type: Volume
+        volume:
+          type: "{{ .dockerVolumeType }}"
+          {{- if .availabilityZone }}
+          availabilityZone:
+            name: "{{ .availabilityZone }}"
+          {{- end }}
+    {{- end }}
+"#};
+
+const ADDITIONAL_BLOCK_DEVICES_TEMPLATE: &str = indoc! {r#"
+    {{- range .additionalVolumes }}
+    - name: "{{ .name }}"
+      sizeGiB: {{ .size }}
+      storage:
+        type: Volume
+        volume:
+          {{- if .type }}
+          type: "{{ .type }}"
+          {{- end }}
+          {{- if or .availabilityZone $.availabilityZone }}
+          availabilityZone:
+            name: "{{ if .availabilityZone }}{{ .availabilityZone }}{{ else }}{{ $.availabilityZone }}{{ end }}"
+          {{- end }}
+    {{- end }}
+"#};
+
+/// Returns a Go template which renders the `additionalBlockDevices` of the
+/// OpenStack machines for the given role.
+fn block_devices_template(role: Role) -> String {
+    let mut devices = String::new();
+    if let Role::ControlPlane = role {
+        devices.push_str(ETCD_BLOCK_DEVICE_TEMPLATE);
+    }
+    devices.push_str(DOCKER_BLOCK_DEVICE_TEMPLATE);
+    devices.push_str(ADDITIONAL_BLOCK_DEVICES_TEMPLATE);
+
+    role.if_has_volumes(&devices)
+}
+
+fn partition(device: &str) -> String {
+    format!(
+        indoc! {r#"
+            - device: {}
+              layout: true
+              overwrite: false
+              tableType: gpt
+        "#},
+        device
+    )
+}
+
+fn filesystem(device: &str, filesystem: &str, label: &str, extra_opts: &str) -> String {
+    format!(
+        indoc! {r#"
+            - device: {}
+              filesystem: {}
+              label: {}
+              extraOpts: {}
+        "#},
+        device, filesystem, label, extra_opts
+    )
+}
+
+const EXT4_EXTRA_OPTS: &str = r#"["-F", "-E", "lazy_itable_init=1,lazy_journal_init=1"]"#;
+
+/// Returns a Go template which renders the `diskSetup` of the kubeadm config
+/// for the given role, every volume gets a single GPT partition.
+fn disk_setup_template(role: Role) -> String {
+    let additional_device = device(&role.additional_letter());
+
+    let mut partitions = String::new();
+    let mut filesystems = String::new();
+
+    if let Role::ControlPlane = role {
+        partitions.push_str("{{- if .enableEtcdVolume }}\n");
+        partitions.push_str(&partition(&device("b")));
+        partitions.push_str("{{- end }}\n");
+
+        filesystems.push_str("{{- if .enableEtcdVolume }}\n");
+        filesystems.push_str(&filesystem(&device("b"), "ext4", "etcd_disk", EXT4_EXTRA_OPTS));
+        filesystems.push_str("{{- end }}\n");
+    }
+
+    partitions.push_str("{{- if .enableDockerVolume }}\n");
+    partitions.push_str(&partition(&device(role.docker_letter())));
+    partitions.push_str("{{- end }}\n");
+    partitions.push_str("{{- range $i, $volume := .additionalVolumes }}\n");
+    partitions.push_str(&partition(&additional_device));
+    partitions.push_str("{{- end }}\n");
+
+    filesystems.push_str("{{- if .enableDockerVolume }}\n");
+    filesystems.push_str(&filesystem(
+        &device(role.docker_letter()),
+        "ext4",
+        "docker_disk",
+        EXT4_EXTRA_OPTS,
+    ));
+    filesystems.push_str("{{- end }}\n");
+    filesystems.push_str("{{- range $i, $volume := .additionalVolumes }}\n");
+    filesystems.push_str(&filesystem(
+        &additional_device,
+        r#""{{ .filesystem }}""#,
+        r#""{{ .name }}""#,
+        &format!(r#"{{{{ if eq .filesystem "xfs" }}}}["-f"]{{{{ else }}}}{}{{{{ end }}}}"#, EXT4_EXTRA_OPTS),
+    ));
+    filesystems.push_str("{{- end }}\n");
+
+    role.if_has_volumes(&format!(
+        "partitions:\n{}filesystems:\n{}",
+        partitions, filesystems
+    ))
+}
+
+/// Returns a Go template which renders the `mounts` of the kubeadm config for
+/// the given role.
+fn mounts_template(role: Role) -> String {
+    let mut mounts = String::new();
+    if let Role::ControlPlane = role {
+        mounts.push_str(indoc! {r#"
+            {{- if .enableEtcdVolume }}
+            - ["LABEL=etcd_disk", "/var/lib/etcd"]
+            {{- end }}
+        "#});
+    }
+    mounts.push_str(indoc! {r#"
+        {{- if .enableDockerVolume }}
+        - ["LABEL=docker_disk", "/var/lib/containerd"]
+        {{- end }}
+        {{- range .additionalVolumes }}
+        - ["LABEL={{ .name }}", "{{ .mountPath }}"]
+        {{- end }}
+    "#});
+
+    role.if_has_volumes(&mounts)
+}
+
+fn template_patch(path: &str, template: String) -> ClusterClassPatchesDefinitionsJsonPatches {
+    ClusterClassPatchesDefinitionsJsonPatches {
+        op: "add".into(),
+        path: path.into(),
+        value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
+            template: Some(template),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
 }
 
 pub struct Feature {}
 
 impl ClusterFeaturePatches for Feature {
     fn patches(&self) -> Vec<ClusterClassPatches> {
-        vec![
-            ClusterClassPatches {
-                name: "addEmptyLists".into(),
-                enabled_if: Some(r#"{{ if or .enableEtcdVolume .enableDockerVolume }}true{{ end }}"#.into()),
-                definitions: Some(vec![
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: OpenStackMachineTemplate::api_resource().api_version,
-                            kind: OpenStackMachineTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                control_plane: Some(true),
-                                machine_deployment_class: Some(ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
-                                    names: Some(vec!["default-worker".to_string()])
-                                }),
-                                ..Default::default()
-                            },
-                        },
-                        json_patches: vec![
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/additionalBlockDevices".into(),
-                                value: Some(Vec::<String>::new().into()),
-                                ..Default::default()
-                            }
-                        ],
-                    },
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
-                            kind: KubeadmControlPlaneTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                control_plane: Some(true),
-                                ..Default::default()
-                            },
-                        },
-                        json_patches: vec![
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/mounts".into(),
-                                value: Some(Vec::<String>::new().into()),
-                                ..Default::default()
-                            },
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/diskSetup".into(),
-                                value: Some(json!({
-                                    "filesystems": [],
-                                    "partitions": [],
-                                })),
-                                ..Default::default()
-                            }
-                        ],
-                    },
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: KubeadmConfigTemplate::api_resource().api_version,
-                            kind: KubeadmConfigTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                machine_deployment_class: Some(ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
-                                    names: Some(vec!["default-worker".to_string()])
-                                }),
-                                ..Default::default()
-                            },
-                        },
-                        json_patches: vec![
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/mounts".into(),
-                                value: Some(Vec::<String>::new().into()),
-                                ..Default::default()
-                            },
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/diskSetup".into(),
-                                value: Some(json!({
-                                    "filesystems": [],
-                                    "partitions": [],
-                                })),
-                                ..Default::default()
-                            }
-                        ],
-                    },
-                ]),
-                ..Default::default()
-            },
-            ClusterClassPatches {
-                name: "etcdVolume".into(),
-                enabled_if: Some(r#"{{ if .enableEtcdVolume }}true{{ end }}"#.into()),
-                definitions: Some(vec![
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: OpenStackMachineTemplate::api_resource().api_version,
-                            kind: OpenStackMachineTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                control_plane: Some(true),
-                                ..Default::default()
-                            },
-                        },
-                        json_patches: vec![ClusterClassPatchesDefinitionsJsonPatches {
-                            op: "add".into(),
-                            path: "/spec/template/spec/additionalBlockDevices/-".into(),
-                            value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                template: Some(
-                                    indoc!(
-                                        r#"
-                                        name: etcd
-                                        sizeGiB: {{ .etcdVolumeSize }}
-                                        storage:
-                                            type: Volume
-                                            volume:
-                                                type: "{{ .etcdVolumeType }}"
-                                                {{- if .availabilityZone }}
-                                                availabilityZone:
-                                                  name: "{{ .availabilityZone }}"
-                                                {{- end }}
-                                        "#
-                                    )
-                                    .into(),
-                                ),
-                                ..Default::default()
-                            }),
+        // NOTE: The templates are guarded instead of the patch, see
+        //       [`ClusterFeaturePatches`] for why.
+        vec![ClusterClassPatches {
+            name: "volumes".into(),
+            definitions: Some(vec![
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackMachineTemplate::api_resource().api_version,
+                        kind: OpenStackMachineTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            control_plane: Some(true),
                             ..Default::default()
-                        }],
-                    },
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
-                            kind: KubeadmControlPlaneTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                control_plane: Some(true),
-                                ..Default::default()
-                            },
-                        },
-                        json_patches: vec![
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/diskSetup/partitions/-".into(),
-                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                    template: Some(
-                                        serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupPartitions {
-                                            device: r#"{{ if eq .hardwareDiskBus "scsi" }}/dev/sd{{ else }}/dev/vd{{end}}b"#.into(),
-                                            layout: true,
-                                            overwrite: Some(false),
-                                            table_type: Some("gpt".into()),
-                                        }).unwrap(),
-                                    ),
-                                    ..Default::default()
-                                }),
-                                ..Default::default()
-                            },
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/diskSetup/filesystems/-".into(),
-                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                    template: Some(
-                                        serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupFilesystems {
-                                            device: r#"{{ if eq .hardwareDiskBus "scsi" }}/dev/sd{{ else }}/dev/vd{{end}}b"#.into(),
-                                            extra_opts: Some(vec![
-                                                "-F".into(),
-                                                "-E".into(),
-                                                "lazy_itable_init=1,lazy_journal_init=1".into(),
-                                            ]),
-                                            filesystem: "ext4".into(),
-                                            label: "etcd_disk".into(),
-                                            ..Default::default()
-                                        }).unwrap(),
-                                    ),
-                                    ..Default::default()
-                                }),
-                                ..Default::default()
-                            },
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/mounts/-".into(),
-                                value: Some(json!(&vec!["LABEL=etcd_disk".to_string(), "/var/lib/etcd".to_string()])),
-                                ..Default::default()
-                            },
-                        ],
-                    },
-                ]),
-                ..Default::default()
-            },
-            ClusterClassPatches {
-                name: "dockerVolume".into(),
-                enabled_if: Some(r#"{{ if .enableDockerVolume }}true{{ end }}"#.into()),
-                definitions: Some(vec![
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: OpenStackMachineTemplate::api_resource().api_version,
-                            kind: OpenStackMachineTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                control_plane: Some(true),
-                                machine_deployment_class: Some(ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
-                                    names: Some(vec!["default-worker".to_string()])
-                                }),
-                                ..Default::default()
-                            },
                         },
-                        json_patches: vec![
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/additionalBlockDevices/-".into(),
-                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                    template: Some(
-                                        indoc!(
-                                            r#"
-                                            name: docker
-                                            sizeGiB: {{ .dockerVolumeSize }}
-                                            storage:
-                                                type: Volume
-                                                volume:
-                                                    type: "{{ .dockerVolumeType }}"
-                                                    {{- if .availabilityZone }}
-                                                    availabilityZone:
-                                                      name: "{{ .availabilityZone }}"
-                                                    {{- end }}
-                                            "#
-                                        )
-                                        .into(),
-                                    ),
-                                    ..Default::default()
-                                }),
-                                ..Default::default()
-                            }
-                        ],
                     },
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
-                            kind: KubeadmControlPlaneTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                control_plane: Some(true),
-                                ..Default::default()
-                            },
+                    json_patches: vec![template_patch(
+                        "/spec/template/spec/additionalBlockDevices",
+                        block_devices_template(Role::ControlPlane),
+                    )],
+                },
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: OpenStackMachineTemplate::api_resource().api_version,
+                        kind: OpenStackMachineTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            machine_deployment_class: Some(
+                                ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
+                                    names: Some(vec!["default-worker".to_string()]),
+                                },
+                            ),
+                            ..Default::default()
                         },
-                        json_patches: vec![
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/mounts/-".into(),
-                                value: Some(json!(&vec!["LABEL=docker_disk".to_string(), "/var/lib/containerd".to_string()])),
-                                ..Default::default()
-                            },
-                        ],
                     },
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: KubeadmConfigTemplate::api_resource().api_version,
-                            kind: KubeadmConfigTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                machine_deployment_class: Some(ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
-                                    names: Some(vec!["default-worker".to_string()])
-                                }),
-                                ..Default::default()
-                            },
-                        },
-                        json_patches: vec![
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/mounts/-".into(),
-                                value: Some(json!(&vec!["LABEL=docker_disk".to_string(), "/var/lib/containerd".to_string()])),
-                                ..Default::default()
-                            },
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/diskSetup/partitions/-".into(),
-                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                    template: Some(
-                                        serde_yaml::to_string(&KubeadmConfigTemplateTemplateSpecDiskSetupPartitions {
-                                            device: r#"{{ if eq .hardwareDiskBus "scsi" }}/dev/sd{{ else }}/dev/vd{{end}}b"#.into(),
-                                            layout: true,
-                                            overwrite: Some(false),
-                                            table_type: Some("gpt".into()),
-                                        }).unwrap(),
-                                    ),
-                                    ..Default::default()
-                                }),
-                                ..Default::default()
-                            },
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/diskSetup/filesystems/-".into(),
-                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                    template: Some(
-                                        serde_yaml::to_string(&KubeadmConfigTemplateTemplateSpecDiskSetupFilesystems {
-                                            device: r#"{{ if eq .hardwareDiskBus "scsi" }}/dev/sd{{ else }}/dev/vd{{end}}b"#.into(),
-                                            extra_opts: Some(vec![
-                                                "-F".into(),
-                                                "-E".into(),
-                                                "lazy_itable_init=1,lazy_journal_init=1".into(),
-                                            ]),
-                                            filesystem: "ext4".into(),
-                                            label: "docker_disk".into(),
-                                            ..Default::default()
-                                        }).unwrap(),
-                                    ),
-                                    ..Default::default()
-                                }),
-                                ..Default::default()
-                            },
-                        ],
-                    }
-                ]),
-                ..Default::default()
-            },
-            ClusterClassPatches {
-                name: "etcdAndDockerVolumeForControlPlane".into(),
-                enabled_if: Some(r#"{{ if and .enableEtcdVolume .enableDockerVolume }}true{{ end }}"#.into()),
-                definitions: Some(vec![
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
-                            kind: KubeadmControlPlaneTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                control_plane: Some(true),
-                                ..Default::default()
-                            },
+                    json_patches: vec![template_patch(
+                        "/spec/template/spec/additionalBlockDevices",
+                        block_devices_template(Role::Worker),
+                    )],
+                },
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
+                        kind: KubeadmControlPlaneTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            control_plane: Some(true),
+                            ..Default::default()
                         },
-                        json_patches: vec![
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/diskSetup/partitions/-".into(),
-                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                    template: Some(
-                                        serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupPartitions {
-                                            device: r#"{{ if eq .hardwareDiskBus "scsi" }}/dev/sd{{ else }}/dev/vd{{end}}c"#.into(),
-                                            layout: true,
-                                            overwrite: Some(false),
-                                            table_type: Some("gpt".into()),
-                                        }).unwrap(),
-                                    ),
-                                    ..Default::default()
-                                }),
-                                ..Default::default()
-                            },
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/diskSetup/filesystems/-".into(),
-                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                    template: Some(
-                                        serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupFilesystems {
-                                            device: r#"{{ if eq .hardwareDiskBus "scsi" }}/dev/sd{{ else }}/dev/vd{{end}}c"#.into(),
-                                            extra_opts: Some(vec![
-                                                "-F".into(),
-                                                "-E".into(),
-                                                "lazy_itable_init=1,lazy_journal_init=1".into(),
-                                            ]),
-                                            filesystem: "ext4".into(),
-                                            label: "docker_disk".into(),
-                                            ..Default::default()
-                                        }).unwrap(),
-                                    ),
-                                    ..Default::default()
-                                }),
-                                ..Default::default()
-                            },
-                        ],
                     },
-                ]),
-                ..Default::default()
-            },
-            ClusterClassPatches {
-                name: "onlyDockerVolumeForControlPlane".into(),
-                enabled_if: Some(r#"{{ if and .enableDockerVolume (not .enableEtcdVolume) }}true{{ end }}"#.into()),
-                definitions: Some(vec![
-                    ClusterClassPatchesDefinitions {
-                        selector: ClusterClassPatchesDefinitionsSelector {
-                            api_version: KubeadmControlPlaneTemplate::api_resource().api_version,
-                            kind: KubeadmControlPlaneTemplate::api_resource().kind,
-                            match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
-                                control_plane: Some(true),
-                                ..Default::default()
-                            },
+                    json_patches: vec![
+                        template_patch(
+                            "/spec/template/spec/kubeadmConfigSpec/mounts",
+                            mounts_template(Role::ControlPlane),
+                        ),
+                        template_patch(
+                            "/spec/template/spec/kubeadmConfigSpec/diskSetup",
+                            disk_setup_template(Role::ControlPlane),
+                        ),
+                    ],
+                },
+                ClusterClassPatchesDefinitions {
+                    selector: ClusterClassPatchesDefinitionsSelector {
+                        api_version: KubeadmConfigTemplate::api_resource().api_version,
+                        kind: KubeadmConfigTemplate::api_resource().kind,
+                        match_resources: ClusterClassPatchesDefinitionsSelectorMatchResources {
+                            machine_deployment_class: Some(
+                                ClusterClassPatchesDefinitionsSelectorMatchResourcesMachineDeploymentClass {
+                                    names: Some(vec!["default-worker".to_string()]),
+                                },
+                            ),
+                            ..Default::default()
                         },
-                        json_patches: vec![
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/diskSetup/partitions/-".into(),
-                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                    template: Some(
-                                        serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupPartitions {
-                                            device: r#"{{ if eq .hardwareDiskBus "scsi" }}/dev/sd{{ else }}/dev/vd{{end}}b"#.into(),
-                                            layout: true,
-                                            overwrite: Some(false),
-                                            table_type: Some("gpt".into()),
-                                        }).unwrap(),
-                                    ),
-                                    ..Default::default()
-                                }),
-                                ..Default::default()
-                            },
-                            ClusterClassPatchesDefinitionsJsonPatches {
-                                op: "add".into(),
-                                path: "/spec/template/spec/kubeadmConfigSpec/diskSetup/filesystems/-".into(),
-                                value_from: Some(ClusterClassPatchesDefinitionsJsonPatchesValueFrom {
-                                    template: Some(
-                                        serde_yaml::to_string(&KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupFilesystems {
-                                            device: r#"{{ if eq .hardwareDiskBus "scsi" }}/dev/sd{{ else }}/dev/vd{{end}}b"#.into(),
-                                            extra_opts: Some(vec![
-                                                "-F".into(),
-                                                "-E".into(),
-                                                "lazy_itable_init=1,lazy_journal_init=1".into(),
-                                            ]),
-                                            filesystem: "ext4".into(),
-                                            label: "docker_disk".into(),
-                                            ..Default::default()
-                                        }).unwrap(),
-                                    ),
-                                    ..Default::default()
-                                }),
-                                ..Default::default()
-                            },
-                        ],
                     },
-                ]),
-                ..Default::default()
-            }
-        ]
+                    json_patches: vec![
+                        template_patch("/spec/template/spec/mounts", mounts_template(Role::Worker)),
+                        template_patch(
+                            "/spec/template/spec/diskSetup",
+                            disk_setup_template(Role::Worker),
+                        ),
+                    ],
+                },
+            ]),
+            ..Default::default()
+        }]
     }
 }
 
@@ -492,8 +443,16 @@ mod tests {
     use super::*;
     use crate::{
         cluster_api::{
-            kubeadmconfigtemplates::KubeadmConfigTemplateTemplateSpecDiskSetup,
-            kubeadmcontrolplanetemplates::KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetup,
+            kubeadmconfigtemplates::{
+                KubeadmConfigTemplateTemplateSpecDiskSetup,
+                KubeadmConfigTemplateTemplateSpecDiskSetupFilesystems,
+                KubeadmConfigTemplateTemplateSpecDiskSetupPartitions,
+            },
+            kubeadmcontrolplanetemplates::{
+                KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetup,
+                KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupFilesystems,
+                KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupPartitions,
+            },
             openstackmachinetemplates::{
                 OpenStackMachineTemplateTemplateSpecAdditionalBlockDevices,
                 OpenStackMachineTemplateTemplateSpecAdditionalBlockDevicesStorage,
@@ -515,6 +474,31 @@ mod tests {
 
         let mut resources = TestClusterResources::new();
         resources.apply_patches(&patches, &values);
+
+        for template in [
+            &resources.control_plane_openstack_machine_template,
+            &resources.worker_openstack_machine_template,
+        ] {
+            assert_eq!(template.spec.template.spec.additional_block_devices, None);
+        }
+
+        let kcp_spec = &resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+        assert_eq!(kcp_spec.disk_setup, None);
+        assert_eq!(kcp_spec.mounts, None);
+
+        let kct_spec = resources
+            .kubeadm_config_template
+            .spec
+            .template
+            .spec
+            .expect("kubeadm config spec should be set");
+        assert_eq!(kct_spec.disk_setup, None);
+        assert_eq!(kct_spec.mounts, None);
     }
 
     #[test]
@@ -560,9 +544,8 @@ mod tests {
                 .spec
                 .template
                 .spec
-                .additional_block_devices
-                .expect("additional block devices should be set"),
-            vec![]
+                .additional_block_devices,
+            None
         );
 
         assert_eq!(
@@ -621,21 +604,8 @@ mod tests {
             .spec
             .expect("kubeadm config spec should be set");
 
-        assert_eq!(
-            kct_spec
-                .clone()
-                .disk_setup
-                .expect("disk setup should be set"),
-            KubeadmConfigTemplateTemplateSpecDiskSetup {
-                partitions: Some(vec![]),
-                filesystems: Some(vec![])
-            }
-        );
-
-        assert_eq!(
-            kct_spec.clone().mounts.expect("mounts should be set"),
-            Vec::<Vec<String>>::new()
-        );
+        assert_eq!(kct_spec.disk_setup, None);
+        assert_eq!(kct_spec.mounts, None);
     }
 
     #[test]
@@ -1255,4 +1225,235 @@ mod tests {
             ]]
         );
     }
+
+    #[test]
+    fn test_patches_with_additional_volumes() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.enable_etcd_volume = true;
+        values.etcd_volume_size = 80;
+        values.enable_docker_volume = true;
+        values.docker_volume_size = 160;
+        values.additional_volumes = vec![
+            AdditionalVolume::builder()
+                .name("longhorn".into())
+                .size(500)
+                .volume_type("nvme".into())
+                .mount_path("/var/lib/longhorn".into())
+                .build(),
+            AdditionalVolume::builder()
+                .name("logs".into())
+                .size(50)
+                .availability_zone("az2".into())
+                .filesystem(AdditionalVolumeFilesystem::Xfs)
+                .mount_path("/var/log/pods".into())
+                .build(),
+        ];
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let additional_block_devices = [
+            OpenStackMachineTemplateTemplateSpecAdditionalBlockDevices {
+                name: "longhorn".into(),
+                size_gi_b: 500,
+                storage: OpenStackMachineTemplateTemplateSpecAdditionalBlockDevicesStorage {
+                    r#type: "Volume".into(),
+                    volume: Some(OpenStackMachineTemplateTemplateSpecAdditionalBlockDevicesStorageVolume {
+                        r#type: Some("nvme".into()),
+                        availability_zone: Some(OpenStackMachineTemplateTemplateSpecAdditionalBlockDevicesStorageVolumeAvailabilityZone {
+                            name: Some(values.availability_zone.clone()),
+                            ..Default::default()
+                        }),
+                    }),
+                },
+            },
+            OpenStackMachineTemplateTemplateSpecAdditionalBlockDevices {
+                name: "logs".into(),
+                size_gi_b: 50,
+                storage: OpenStackMachineTemplateTemplateSpecAdditionalBlockDevicesStorage {
+                    r#type: "Volume".into(),
+                    volume: Some(OpenStackMachineTemplateTemplateSpecAdditionalBlockDevicesStorageVolume {
+                        r#type: None,
+                        availability_zone: Some(OpenStackMachineTemplateTemplateSpecAdditionalBlockDevicesStorageVolumeAvailabilityZone {
+                            name: Some("az2".into()),
+                            ..Default::default()
+                        }),
+                    }),
+                },
+            },
+        ];
+
+        let control_plane_block_devices = resources
+            .control_plane_openstack_machine_template
+            .spec
+            .template
+            .spec
+            .additional_block_devices
+            .expect("additional block devices should be set");
+        assert_eq!(
+            control_plane_block_devices
+                .iter()
+                .map(|device| device.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["etcd", "docker", "longhorn", "logs"]
+        );
+        assert_eq!(control_plane_block_devices[2..], additional_block_devices[..]);
+
+        let worker_block_devices = resources
+            .worker_openstack_machine_template
+            .spec
+            .template
+            .spec
+            .additional_block_devices
+            .expect("additional block devices should be set");
+        assert_eq!(
+            worker_block_devices
+                .iter()
+                .map(|device| device.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["docker", "longhorn", "logs"]
+        );
+        assert_eq!(worker_block_devices[1..], additional_block_devices[..]);
+
+        let kcp_spec = resources
+            .kubeadm_control_plane_template
+            .spec
+            .template
+            .spec
+            .kubeadm_config_spec;
+        let control_plane_disk_setup = kcp_spec.disk_setup.expect("disk setup should be set");
+
+        assert_eq!(
+            control_plane_disk_setup
+                .partitions
+                .expect("partitions should be set")
+                .iter()
+                .map(|partition| partition.device.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/dev/vdb", "/dev/vdc", "/dev/vdd", "/dev/vde"]
+        );
+        assert_eq!(
+            control_plane_disk_setup
+                .filesystems
+                .expect("filesystems should be set")[2..],
+            vec![
+                KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupFilesystems {
+                    device: "/dev/vdd".into(),
+                    extra_opts: Some(vec![
+                        "-F".into(),
+                        "-E".into(),
+                        "lazy_itable_init=1,lazy_journal_init=1".into(),
+                    ]),
+                    filesystem: "ext4".into(),
+                    label: "longhorn".into(),
+                    ..Default::default()
+                },
+                KubeadmControlPlaneTemplateTemplateSpecKubeadmConfigSpecDiskSetupFilesystems {
+                    device: "/dev/vde".into(),
+                    extra_opts: Some(vec!["-f".into()]),
+                    filesystem: "xfs".into(),
+                    label: "logs".into(),
+                    ..Default::default()
+                },
+            ][..]
+        );
+        assert_eq!(
+            kcp_spec.mounts.expect("mounts should be set"),
+            vec![
+                vec!["LABEL=etcd_disk".to_string(), "/var/lib/etcd".to_string()],
+                vec!["LABEL=docker_disk".to_string(), "/var/lib/containerd".to_string()],
+                vec!["LABEL=longhorn".to_string(), "/var/lib/longhorn".to_string()],
+                vec!["LABEL=logs".to_string(), "/var/log/pods".to_string()],
+            ]
+        );
+
+        let kct_spec = resources
+            .kubeadm_config_template
+            .spec
+            .template
+            .spec
+            .expect("kubeadm config spec should be set");
+        let worker_disk_setup = kct_spec.disk_setup.expect("disk setup should be set");
+
+        assert_eq!(
+            worker_disk_setup
+                .partitions
+                .expect("partitions should be set")
+                .iter()
+                .map(|partition| partition.device.as_str())
+                .collect::<Vec<_>>(),
+            vec!["/dev/vdb", "/dev/vdc", "/dev/vdd"]
+        );
+        assert_eq!(
+            worker_disk_setup
+                .filesystems
+                .expect("filesystems should be set")
+                .iter()
+                .map(|filesystem| (filesystem.device.as_str(), filesystem.label.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("/dev/vdb", "docker_disk"),
+                ("/dev/vdc", "longhorn"),
+                ("/dev/vdd", "logs"),
+            ]
+        );
+        assert_eq!(
+            kct_spec.mounts.expect("mounts should be set"),
+            vec![
+                vec!["LABEL=docker_disk".to_string(), "/var/lib/containerd".to_string()],
+                vec!["LABEL=longhorn".to_string(), "/var/lib/longhorn".to_string()],
+                vec!["LABEL=logs".to_string(), "/var/log/pods".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_patches_with_additional_volumes_only() {
+        let feature = Feature {};
+
+        let mut values = default_values();
+        values.hardware_disk_bus = "scsi".into();
+        values.additional_volumes = vec![AdditionalVolume::builder()
+            .name("longhorn".into())
+            .size(500)
+            .mount_path("/var/lib/longhorn".into())
+            .build()];
+
+        let patches = feature.patches();
+        let mut resources = TestClusterResources::new();
+        resources.apply_patches(&patches, &values);
+
+        let kct_spec = resources
+            .kubeadm_config_template
+            .spec
+            .template
+            .spec
+            .expect("kubeadm config spec should be set");
+
+        assert_eq!(
+            kct_spec.disk_setup.expect("disk setup should be set"),
+            KubeadmConfigTemplateTemplateSpecDiskSetup {
+                partitions: Some(vec![KubeadmConfigTemplateTemplateSpecDiskSetupPartitions {
+                    device: "/dev/sdb".into(),
+                    layout: true,
+                    overwrite: Some(false),
+                    table_type: Some("gpt".into()),
+                }]),
+                filesystems: Some(vec![KubeadmConfigTemplateTemplateSpecDiskSetupFilesystems {
+                    device: "/dev/sdb".into(),
+                    extra_opts: Some(vec![
+                        "-F".into(),
+                        "-E".into(),
+                        "lazy_itable_init=1,lazy_journal_init=1".into(),
+                    ]),
+                    filesystem: "ext4".into(),
+                    label: "longhorn".into(),
+                    ..Default::default()
+                }]),
+            }
+        );
+    }
 }
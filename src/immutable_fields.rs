@@ -61,6 +61,10 @@ struct FieldMapping {
     /// `pkg/webhooks/openstackcluster_webhook.go::ValidateUpdate` which
     /// falls back to `reflect.DeepEqual`).
     presence_variable_path: Option<&'static str>,
+    /// Optional conversion of the existing value into the shape of the
+    /// variable, for fields which the ClusterClass patch renders from a
+    /// simpler variable.
+    convert: Option<fn(&Value) -> Value>,
 }
 
 impl FieldMapping {
@@ -84,7 +88,7 @@ impl FieldMapping {
         }
 
         let value = if let Some(v) = existing_value {
-            v.clone()
+            self.convert.map_or_else(|| v.clone(), |convert| convert(v))
         } else if let Some(label_val) = labels.get(self.label) {
             Value::String(label_val.clone())
         } else {
@@ -156,6 +160,17 @@ where
     }
 }
 
+/// Returns the names of the security groups of the bastion, which it is
+/// rendered with as `filter`s.
+fn security_group_names(value: &Value) -> Value {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|group| group.pointer("/filter/name").cloned())
+        .collect()
+}
+
 pub const OPENSTACK_CLUSTER_FIELDS: ResourceFieldMappings<OpenStackCluster> =
     ResourceFieldMappings {
         fields: &[
@@ -164,18 +179,21 @@ pub const OPENSTACK_CLUSTER_FIELDS: ResourceFieldMappings<OpenStackCluster> =
                 variable_path: "/apiServerLoadBalancer/provider",
                 label: "octavia_provider",
                 presence_variable_path: None,
+                convert: None,
             },
             FieldMapping {
                 resource_path: "/spec/apiServerLoadBalancer/flavor",
                 variable_path: "/apiServerLoadBalancer/flavor",
                 label: "api_server_lb_flavor",
                 presence_variable_path: None,
+                convert: None,
             },
             FieldMapping {
                 resource_path: "/spec/apiServerLoadBalancer/availabilityZone",
                 variable_path: "/apiServerLoadBalancer/availabilityZone",
                 label: "api_server_lb_availability_zone",
                 presence_variable_path: None,
+                convert: None,
             },
             // Preserve `disableAPIServerFloatingIP` so that upgrades from old
             // magnum-cluster-api versions (pre-v0.25.x) which unconditionally
@@ -190,6 +208,53 @@ pub const OPENSTACK_CLUSTER_FIELDS: ResourceFieldMappings<OpenStackCluster> =
                 variable_path: "/disableAPIServerFloatingIP",
                 label: "",
                 presence_variable_path: Some("/disableAPIServerFloatingIPManaged"),
+                convert: None,
+            },
+            // CAPO only removes the bastion from the spec once it has been
+            // disabled and deleted, and rejects changes to a running bastion.
+            // Keep the patch firing once a bastion exists so that disabling
+            // it renders `enabled: false`, and carry its spec through.
+            FieldMapping {
+                resource_path: "/spec/bastion/spec/flavor",
+                variable_path: "/bastion/flavor",
+                label: "",
+                presence_variable_path: Some("/bastion/managed"),
+                convert: None,
+            },
+            FieldMapping {
+                resource_path: "/spec/bastion/spec/image/id",
+                variable_path: "/bastion/imageUUID",
+                label: "",
+                presence_variable_path: None,
+                convert: None,
+            },
+            FieldMapping {
+                resource_path: "/spec/bastion/spec/sshKeyName",
+                variable_path: "/bastion/sshKeyName",
+                label: "",
+                presence_variable_path: None,
+                convert: None,
+            },
+            FieldMapping {
+                resource_path: "/spec/bastion/spec/securityGroups",
+                variable_path: "/bastion/securityGroups",
+                label: "",
+                presence_variable_path: None,
+                convert: Some(security_group_names),
+            },
+            FieldMapping {
+                resource_path: "/spec/bastion/availabilityZone",
+                variable_path: "/bastion/availabilityZone",
+                label: "",
+                presence_variable_path: None,
+                convert: None,
+            },
+            FieldMapping {
+                resource_path: "/spec/bastion/floatingIP",
+                variable_path: "/bastion/floatingIP",
+                label: "",
+                presence_variable_path: None,
+                convert: None,
             },
         ],
         _resource: PhantomData,
@@ -227,6 +292,7 @@ mod tests {
             variable_path: "/apiServerLoadBalancer/provider",
             label: "octavia_provider",
             presence_variable_path: None,
+            convert: None,
         };
 
         #[test]
@@ -603,4 +669,185 @@ mod tests {
             );
         }
     }
+
+    mod bastion_preservation {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn preserves_spec_and_sets_managed_flag() {
+            let existing = json!({
+                "spec": {
+                    "bastion": {
+                        "enabled": true,
+                        "availabilityZone": "az1",
+                        "floatingIP": "203.0.113.10",
+                        "spec": {
+                            "flavor": "m1.small",
+                            "image": {"id": "b6a4e1c0-3f2d-4c5b-8a9e-7d6c5b4a3f2e"},
+                            "sshKeyName": "my-key",
+                            "securityGroups": [
+                                {"filter": {"name": "ssh"}},
+                                {"filter": {"name": "admin"}}
+                            ]
+                        }
+                    }
+                }
+            });
+
+            let result = OPENSTACK_CLUSTER_FIELDS
+                .apply(
+                    &existing,
+                    &HashMap::new(),
+                    json!({
+                        "bastion": {
+                            "enabled": false,
+                            "managed": false,
+                            "flavor": "m1.large",
+                            "imageUUID": "",
+                            "sshKeyName": "",
+                            "availabilityZone": "az2",
+                            "floatingIP": "",
+                            "securityGroups": []
+                        }
+                    }),
+                )
+                .expect("apply failed");
+
+            assert_eq!(
+                result,
+                json!({
+                    "bastion": {
+                        "enabled": false,
+                        "managed": true,
+                        "flavor": "m1.small",
+                        "imageUUID": "b6a4e1c0-3f2d-4c5b-8a9e-7d6c5b4a3f2e",
+                        "sshKeyName": "my-key",
+                        "availabilityZone": "az1",
+                        "floatingIP": "203.0.113.10",
+                        "securityGroups": ["ssh", "admin"]
+                    }
+                })
+            );
+        }
+
+        #[test]
+        fn missing_bastion_leaves_variables_untouched() {
+            let variables = json!({
+                "bastion": {
+                    "enabled": true,
+                    "managed": false,
+                    "flavor": "m1.small"
+                }
+            });
+
+            let result = OPENSTACK_CLUSTER_FIELDS
+                .apply(&json!({"spec": {}}), &HashMap::new(), variables.clone())
+                .expect("apply failed");
+
+            assert_eq!(result, variables);
+        }
+    }
+
+    mod api_server_load_balancer_allowed_cidrs {
+        //! The `allowedCIDRs` are not immutable, but they are part of the
+        //! `apiServerLoadBalancer` variable which goes through the resolution,
+        //! so they must come out of it unchanged.
+        use super::*;
+        use crate::features::{
+            api_server_load_balancer::{APIServerLoadBalancerConfig, Feature},
+            test::TestClusterResources,
+            ClusterFeaturePatches,
+        };
+        use crate::resources::fixtures::default_values;
+        use pretty_assertions::assert_eq;
+
+        fn render(variables: &Value, fixed_network_id: &str) -> Option<Vec<String>> {
+            let mut values = default_values();
+            values.fixed_network_id = fixed_network_id.into();
+            values.api_server_load_balancer =
+                serde_json::from_value(variables["apiServerLoadBalancer"].clone())
+                    .expect("apiServerLoadBalancer should deserialize");
+
+            let mut resources = TestClusterResources::new();
+            resources.apply_patches(&Feature {}.patches(), &values);
+
+            resources
+                .openstack_cluster_template
+                .spec
+                .template
+                .spec
+                .api_server_load_balancer
+                .expect("apiServerLoadBalancer should be set")
+                .allowed_cid_rs
+        }
+
+        #[test]
+        fn pass_through_resolution_with_new_network() {
+            let existing = json!({
+                "spec": {
+                    "apiServerLoadBalancer": {
+                        "enabled": true,
+                        "provider": "amphora",
+                        "allowedCIDRs": ["10.0.0.0/24"]
+                    }
+                }
+            });
+            let labels = hashmap! { "octavia_provider".into() => "ovn".into() };
+            let variables = json!({
+                "apiServerLoadBalancer": {
+                    "enabled": true,
+                    "allowedCIDRs": ["192.168.0.0/24", "203.0.113.10/32"]
+                }
+            });
+
+            let result = OPENSTACK_CLUSTER_FIELDS
+                .apply(&existing, &labels, variables)
+                .expect("apply failed");
+
+            assert_eq!(
+                result,
+                json!({
+                    "apiServerLoadBalancer": {
+                        "enabled": true,
+                        "provider": "amphora",
+                        "allowedCIDRs": ["192.168.0.0/24", "203.0.113.10/32"]
+                    }
+                })
+            );
+
+            let config: APIServerLoadBalancerConfig =
+                serde_json::from_value(result["apiServerLoadBalancer"].clone())
+                    .expect("apiServerLoadBalancer should deserialize");
+            assert_eq!(config.provider, Some("amphora".into()));
+            assert_eq!(
+                render(&result, ""),
+                Some(vec![
+                    "192.168.0.0/24".to_string(),
+                    "203.0.113.10/32".to_string(),
+                    default_values().node_cidr,
+                ])
+            );
+        }
+
+        #[test]
+        fn pass_through_resolution_with_existing_network() {
+            let variables = json!({
+                "apiServerLoadBalancer": {
+                    "enabled": true,
+                    "allowedCIDRs": ["192.168.0.0/24"]
+                }
+            });
+
+            let result = OPENSTACK_CLUSTER_FIELDS
+                .apply(&json!({"spec": {}}), &HashMap::new(), variables.clone())
+                .expect("apply failed");
+
+            assert_eq!(result, variables);
+            assert_eq!(
+                render(&result, "5d8c7a4e-8a70-4f4e-9a3e-3e1a0e6b6b39"),
+                Some(vec!["192.168.0.0/24".to_string()])
+            );
+        }
+    }
 }
@@ -2,13 +2,17 @@ mod addons;
 mod client;
 mod clients;
 mod cluster_api;
+mod cluster_network;
 mod driver;
+mod etcd_snapshots;
 mod features;
 pub mod immutable_fields;
 mod logging;
 mod magnum;
 mod monitor;
+mod orc;
 mod resources;
+mod validation;
 
 use pyo3::{prelude::*, Bound};
 use std::sync::LazyLock;
@@ -27,6 +31,8 @@ fn magnum_cluster_api(m: &Bound<'_, PyModule>) -> PyResult<()> {
     logging::init();
 
     m.add("CLUSTER_CLASS_NAME", CLUSTER_CLASS_NAME.as_str())?;
+    m.add("EXTRA_ARGS_OWNED_FLAGS", features::extra_args::owned_flags())?;
+    m.add("IgnitionError", m.py().get_type::<features::ignition::IgnitionError>())?;
     m.add_class::<client::KubeClient>()?;
     m.add_class::<driver::Driver>()?;
     m.add_class::<monitor::Monitor>()?;
@@ -1,9 +1,13 @@
 use crate::{
-    addons::{cilium, ClusterAddon},
+    addons::{calico, cilium, ClusterAddon},
     cluster_api::clusterresourcesets::{
         ClusterResourceSet, ClusterResourceSetClusterSelector, ClusterResourceSetResources,
         ClusterResourceSetResourcesKind, ClusterResourceSetSpec, ClusterResourceSetStrategy,
     },
+    orc::servergroups::{
+        ServerGroup, ServerGroupCloudCredentialsRef, ServerGroupManagementPolicy,
+        ServerGroupResource, ServerGroupResourcePolicy, ServerGroupSpec,
+    },
 };
 use k8s_openapi::api::core::v1::Secret;
 use kube::{
@@ -26,6 +30,16 @@ pub struct ClusterTemplate {
 #[derive(Clone, Default, Deserialize, FromPyObject, TypedBuilder)]
 #[pyo3(from_item_all)]
 pub struct ClusterLabels {
+    /// The IP address range to use for the Calico IPAM pool.
+    #[builder(default="10.100.0.0/16".to_owned())]
+    #[pyo3(default="10.100.0.0/16".to_owned())]
+    pub calico_ipv4pool: String,
+
+    /// The version of Calico to deploy in the cluster.
+    #[builder(default="v3.32.1".to_owned())]
+    #[pyo3(default="v3.32.1".to_owned())]
+    pub calico_tag: String,
+
     /// The tag of the Cilium container image to use for the cluster.
     #[builder(default="v1.15.3".to_owned())]
     #[pyo3(default="v1.15.3".to_owned())]
@@ -42,6 +56,43 @@ pub struct ClusterLabels {
     #[pyo3(default="false".to_owned())]
     pub cilium_hubble_ui_enabled: String,
 
+    /// Enable the Hubble metrics of Cilium.
+    /// Note: OpenStack labels are always strings, so this accepts "true"/"false".
+    #[builder(default="false".to_owned())]
+    #[pyo3(default="false".to_owned())]
+    pub cilium_hubble_metrics_enabled: String,
+
+    /// Enable the ServiceMonitor for the Hubble metrics of Cilium, which
+    /// requires the Prometheus Operator CRDs in the cluster.
+    /// Note: OpenStack labels are always strings, so this accepts "true"/"false".
+    #[builder(default="false".to_owned())]
+    #[pyo3(default="false".to_owned())]
+    pub cilium_hubble_metrics_service_monitor_enabled: String,
+
+    /// Enable the kube-proxy replacement of Cilium, in which case kube-proxy
+    /// is not deployed in the cluster.
+    /// Note: OpenStack labels are always strings, so this accepts "true"/"false".
+    #[builder(default="false".to_owned())]
+    #[pyo3(default="false".to_owned())]
+    pub cilium_kube_proxy_replacement: String,
+
+    /// Enable the bandwidth manager of Cilium.
+    /// Note: OpenStack labels are always strings, so this accepts "true"/"false".
+    #[builder(default="false".to_owned())]
+    #[pyo3(default="false".to_owned())]
+    pub cilium_bandwidth_manager_enabled: String,
+
+    /// The transparent encryption of Cilium, which is either "wireguard" or
+    /// "ipsec", and disabled if not set.
+    #[builder(default)]
+    #[pyo3(default)]
+    pub cilium_encryption: Option<String>,
+
+    /// The routing mode of Cilium, which is either "tunnel" or "native".
+    #[builder(default="tunnel".to_owned())]
+    #[pyo3(default="tunnel".to_owned())]
+    pub cilium_routing_mode: String,
+
     /// Enable the use of the Cinder CSI driver for the cluster.
     #[builder(default="true".to_owned())]
     #[pyo3(default="true".to_owned())]
@@ -52,6 +103,12 @@ pub struct ClusterLabels {
     #[pyo3(default="v1.32.0".to_owned())]
     pub cinder_csi_plugin_tag: String,
 
+    /// JSON list of the storage classes to create for the Cinder volume types,
+    /// which replace the ones created for every volume type if set.
+    #[builder(default)]
+    #[pyo3(default)]
+    pub cinder_csi_storage_classes: Option<String>,
+
     /// Enable the use of the Manila CSI driver for the cluster.
     #[builder(default="true".to_owned())]
     #[pyo3(default="true".to_owned())]
@@ -62,6 +119,22 @@ pub struct ClusterLabels {
     #[pyo3(default="v1.32.0".to_owned())]
     pub manila_csi_plugin_tag: String,
 
+    /// Comma-separated list of the share protocols of the Manila CSI driver.
+    #[builder(default="NFS".to_owned())]
+    #[pyo3(default="NFS".to_owned())]
+    pub manila_csi_share_protocols: String,
+
+    /// The ID of the share network to create the Manila shares in.
+    #[builder(default)]
+    #[pyo3(default)]
+    pub manila_csi_share_network_id: Option<String>,
+
+    /// The share type of the Manila shares, which the storage classes of the
+    /// share protocols are created for if set.
+    #[builder(default)]
+    #[pyo3(default)]
+    pub manila_csi_share_type: Option<String>,
+
     /// The tag to use for the OpenStack cloud controller provider
     /// when bootstrapping the cluster. If not specified, it will be
     /// automatically selected based on the Kubernetes version.
@@ -105,9 +178,30 @@ pub struct ClusterLabels {
     #[pyo3(default="v8.1.0".to_owned())]
     pub csi_snapshotter_tag: String,
 
+    /// The DNS domain of the services of the cluster.
+    #[builder(default="cluster.local".to_owned())]
+    #[pyo3(default="cluster.local".to_owned())]
+    pub dns_cluster_domain: String,
+
+    /// The IP address range of the subnet of the nodes.
+    #[builder(default="10.0.0.0/24".to_owned())]
+    #[pyo3(default="10.0.0.0/24".to_owned())]
+    pub fixed_subnet_cidr: String,
+
     /// The Kubernetes version to use for the cluster.
     #[builder(default="v1.30.0".to_owned())]
     pub kube_tag: String,
+
+    /// Comma-separated list of server group policies to use for the control
+    /// plane, which node groups fall back to if they do not set their own.
+    #[builder(default)]
+    #[pyo3(default)]
+    pub server_group_policies: Option<String>,
+
+    /// The IP address range to use for the services of the cluster.
+    #[builder(default="10.254.0.0/16".to_owned())]
+    #[pyo3(default="10.254.0.0/16".to_owned())]
+    pub service_cluster_ip_range: String,
 }
 
 impl ClusterLabels {
@@ -119,6 +213,34 @@ impl ClusterLabels {
         self.cilium_hubble_ui_enabled.eq_ignore_ascii_case("true")
     }
 
+    /// Returns true if the Hubble metrics of Cilium are enabled.
+    /// Parses the string label value "true"/"false" to a boolean.
+    pub fn is_cilium_hubble_metrics_enabled(&self) -> bool {
+        self.cilium_hubble_metrics_enabled
+            .eq_ignore_ascii_case("true")
+    }
+
+    /// Returns true if the ServiceMonitor for the Hubble metrics is enabled.
+    /// Parses the string label value "true"/"false" to a boolean.
+    pub fn is_cilium_hubble_metrics_service_monitor_enabled(&self) -> bool {
+        self.cilium_hubble_metrics_service_monitor_enabled
+            .eq_ignore_ascii_case("true")
+    }
+
+    /// Returns true if the kube-proxy replacement of Cilium is enabled.
+    /// Parses the string label value "true"/"false" to a boolean.
+    pub fn is_cilium_kube_proxy_replacement_enabled(&self) -> bool {
+        self.cilium_kube_proxy_replacement
+            .eq_ignore_ascii_case("true")
+    }
+
+    /// Returns true if the bandwidth manager of Cilium is enabled.
+    /// Parses the string label value "true"/"false" to a boolean.
+    pub fn is_cilium_bandwidth_manager_enabled(&self) -> bool {
+        self.cilium_bandwidth_manager_enabled
+            .eq_ignore_ascii_case("true")
+    }
+
     pub fn get_cloud_provider_tag(&self) -> String {
         if let Some(tag) = &self.cloud_provider_tag {
             return tag.clone();
@@ -150,6 +272,24 @@ impl ClusterLabels {
     }
 }
 
+#[derive(Clone, Default, Deserialize, FromPyObject)]
+pub struct NodeGroup {
+    pub name: String,
+    pub labels: BTreeMap<String, String>,
+}
+
+/// The server group policy used when none is set in the labels.
+const DEFAULT_SERVER_GROUP_POLICY: ServerGroupResourcePolicy =
+    ServerGroupResourcePolicy::SoftAntiAffinity;
+
+/// Returns the first server group policy from a comma-separated label which
+/// is supported by Nova, since a server group can only have a single policy.
+fn server_group_policy_from_label(label: Option<&str>) -> Option<ServerGroupResourcePolicy> {
+    label?
+        .split(',')
+        .find_map(|policy| serde_plain::from_str(policy.trim()).ok())
+}
+
 #[derive(Debug, Error)]
 pub enum ClusterError {
     #[error("missing stack id for cluster: {0}")]
@@ -172,6 +312,15 @@ pub enum ClusterError {
 
     #[error("failed to build shared kube client")]
     SharedClient(#[source] kube::Error),
+
+    #[error("failed waiting for server group: {0}")]
+    ServerGroupWait(#[from] kube::runtime::wait::Error),
+
+    #[error("timed out waiting for server group to be created: {0}")]
+    ServerGroupTimeout(String),
+
+    #[error("timed out waiting for server group to be deleted: {0}")]
+    ServerGroupDeleteTimeout(String),
 }
 
 impl From<ClusterError> for PyErr {
@@ -222,8 +371,19 @@ impl<'a, 'py> FromPyObject<'a, 'py> for ClusterStatus {
 #[derive(Clone, Default, Deserialize, FromPyObject)]
 pub struct Cluster {
     pub uuid: String,
+    pub project_id: String,
     pub cluster_template: ClusterTemplate,
     pub stack_id: Option<String>,
+    /// The address of the API server, which is only known once the
+    /// control plane endpoint of the cluster has been provisioned.
+    #[pyo3(default)]
+    pub api_address: Option<String>,
+    /// The existing network of the nodes, if any.
+    #[pyo3(default)]
+    pub fixed_network: Option<String>,
+    /// The existing subnet of the nodes, if any.
+    #[pyo3(default)]
+    pub fixed_subnet: Option<String>,
     pub labels: ClusterLabels,
     pub status: ClusterStatus,
 }
@@ -244,6 +404,31 @@ impl Cluster {
             .ok_or_else(|| ClusterError::MissingStackId(self.uuid.clone()))
     }
 
+    /// Host and port of the API server, parsed from its address.
+    pub fn api_server_endpoint(&self) -> Option<(String, String)> {
+        let address = self.api_address.as_deref()?;
+        let address = address
+            .strip_prefix("https://")
+            .unwrap_or(address)
+            .trim_end_matches('/');
+
+        address
+            .rsplit_once(':')
+            .map(|(host, port)| (host.to_owned(), port.to_owned()))
+    }
+
+    pub fn etcd_backup_secret_name(&self) -> Result<String, ClusterError> {
+        let stack_id = self.stack_id()?;
+
+        Ok(format!("{}-etcd-backup", stack_id))
+    }
+
+    /// Prefix of the etcd snapshots of the cluster in the bucket, which is
+    /// scoped by project so that snapshots are only restored in the same one.
+    pub fn etcd_backup_prefix(&self) -> String {
+        format!("{}/{}", self.project_id, self.uuid)
+    }
+
     fn kubeconfig_secret_name(&self) -> Result<String, ClusterError> {
         let stack_id = self.stack_id()?;
 
@@ -323,6 +508,64 @@ impl Cluster {
             ..Default::default()
         })
     }
+
+    // NOTE: The server groups use the same names as the ones which were
+    //       previously created through the Nova API, which allows ORC to
+    //       adopt them for existing clusters instead of creating new ones.
+    fn server_group(
+        &self,
+        name: String,
+        policy: ServerGroupResourcePolicy,
+    ) -> Result<ServerGroup, ClusterError> {
+        let stack_id = self.stack_id()?;
+
+        Ok(ServerGroup {
+            metadata: ObjectMeta {
+                name: Some(name.clone()),
+                labels: Some(btreemap! {
+                    "cluster.x-k8s.io/cluster-name".to_owned() => stack_id.clone(),
+                }),
+                ..Default::default()
+            },
+            spec: ServerGroupSpec {
+                cloud_credentials_ref: ServerGroupCloudCredentialsRef {
+                    cloud_name: "default".into(),
+                    secret_name: format!("{}-cloud-config", stack_id),
+                },
+                management_policy: Some(ServerGroupManagementPolicy::Managed),
+                resource: Some(ServerGroupResource {
+                    name: Some(name),
+                    policy,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            status: None,
+        })
+    }
+
+    pub fn control_plane_server_group(&self) -> Result<ServerGroup, ClusterError> {
+        let policy = server_group_policy_from_label(self.labels.server_group_policies.as_deref())
+            .unwrap_or(DEFAULT_SERVER_GROUP_POLICY);
+
+        self.server_group(self.stack_id()?, policy)
+    }
+
+    pub fn node_group_server_group(
+        &self,
+        node_group: &NodeGroup,
+    ) -> Result<ServerGroup, ClusterError> {
+        let policy = server_group_policy_from_label(
+            node_group
+                .labels
+                .get("server_group_policies")
+                .map(String::as_str),
+        )
+        .or_else(|| server_group_policy_from_label(self.labels.server_group_policies.as_deref()))
+        .unwrap_or(DEFAULT_SERVER_GROUP_POLICY);
+
+        self.server_group(format!("{}-{}", self.stack_id()?, node_group.name), policy)
+    }
 }
 
 impl From<&Cluster> for ClusterResourceSet {
@@ -351,8 +594,11 @@ impl From<Cluster> for Secret {
     fn from(cluster: Cluster) -> Self {
         let mut data = BTreeMap::<String, String>::new();
 
+        // NOTE: This secret is only applied once, before the API server address
+        //       is known, so Cilium is deployed by the cloud provider secret
+        //       instead when it replaces kube-proxy.
         let cilium = cilium::Addon::new(cluster.clone());
-        if cilium.enabled() {
+        if cilium.enabled() && !cluster.labels.is_cilium_kube_proxy_replacement_enabled() {
             data.insert(
                 "cilium.yaml".to_owned(),
                 cilium
@@ -364,6 +610,11 @@ impl From<Cluster> for Secret {
             );
         }
 
+        let calico = calico::Addon::new(cluster.clone());
+        if calico.enabled() {
+            data.extend(calico.manifests().unwrap());
+        }
+
         Secret {
             metadata: cluster.clone().into(),
             type_: Some("addons.cluster.x-k8s.io/resource-set".into()),
@@ -433,6 +684,93 @@ mod tests {
         assert_eq!(object_meta.name, Some("sample-uuid".into()));
     }
 
+    #[test]
+    fn test_control_plane_server_group() {
+        let cluster = Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: ClusterLabels::builder()
+                .server_group_policies(Some("invalid,anti-affinity".to_owned()))
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            ..Default::default()
+        };
+
+        let server_group = cluster
+            .control_plane_server_group()
+            .expect("failed to build server group");
+
+        assert_eq!(server_group.metadata.name, Some("kube-abcde".into()));
+        assert_eq!(
+            server_group.metadata.labels,
+            Some(btreemap! {
+                "cluster.x-k8s.io/cluster-name".to_owned() => "kube-abcde".to_owned(),
+            })
+        );
+        assert_eq!(
+            server_group.spec,
+            ServerGroupSpec {
+                cloud_credentials_ref: ServerGroupCloudCredentialsRef {
+                    cloud_name: "default".into(),
+                    secret_name: "kube-abcde-cloud-config".into(),
+                },
+                management_policy: Some(ServerGroupManagementPolicy::Managed),
+                resource: Some(ServerGroupResource {
+                    name: Some("kube-abcde".into()),
+                    policy: ServerGroupResourcePolicy::AntiAffinity,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[rstest]
+    #[case(None, None, ServerGroupResourcePolicy::SoftAntiAffinity)]
+    #[case(Some("affinity"), None, ServerGroupResourcePolicy::Affinity)]
+    #[case(None, Some("soft-affinity"), ServerGroupResourcePolicy::SoftAffinity)]
+    #[case(
+        Some("affinity"),
+        Some("anti-affinity"),
+        ServerGroupResourcePolicy::AntiAffinity
+    )]
+    #[case(Some("affinity"), Some("invalid"), ServerGroupResourcePolicy::Affinity)]
+    fn test_node_group_server_group(
+        #[case] cluster_policies: Option<&str>,
+        #[case] node_group_policies: Option<&str>,
+        #[case] expected: ServerGroupResourcePolicy,
+    ) {
+        let cluster = Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: ClusterLabels::builder()
+                .server_group_policies(cluster_policies.map(str::to_owned))
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            ..Default::default()
+        };
+        let node_group = NodeGroup {
+            name: "default-worker".into(),
+            labels: node_group_policies
+                .map(|policies| {
+                    btreemap! {
+                        "server_group_policies".to_owned() => policies.to_owned(),
+                    }
+                })
+                .unwrap_or_default(),
+        };
+
+        let server_group = cluster
+            .node_group_server_group(&node_group)
+            .expect("failed to build server group");
+        let resource = server_group.spec.resource.expect("resource should be set");
+
+        assert_eq!(
+            server_group.metadata.name,
+            Some("kube-abcde-default-worker".into())
+        );
+        assert_eq!(resource.name, Some("kube-abcde-default-worker".into()));
+        assert_eq!(resource.policy, expected);
+    }
+
     #[test]
     fn test_get_cloud_provider_tag_explicit() {
         let labels = ClusterLabels::builder()
@@ -523,6 +861,27 @@ mod tests {
         }
     }
 
+    #[rstest]
+    #[case(None, None)]
+    #[case(Some("https://10.0.0.10:6443"), Some(("10.0.0.10", "6443")))]
+    #[case(Some("https://k8s.example.com:443/"), Some(("k8s.example.com", "443")))]
+    #[case(Some("https://10.0.0.10"), None)]
+    fn test_cluster_api_server_endpoint(
+        #[case] api_address: Option<&str>,
+        #[case] expected: Option<(&str, &str)>,
+    ) {
+        let cluster = Cluster {
+            uuid: "sample-uuid".to_string(),
+            api_address: api_address.map(str::to_owned),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            cluster.api_server_endpoint(),
+            expected.map(|(host, port)| (host.to_owned(), port.to_owned()))
+        );
+    }
+
     #[test]
     fn test_cluster_addon_cluster_resource_set() {
         let cluster = Cluster {
@@ -734,7 +1093,7 @@ mod tests {
     fn test_secret_from_cluster() {
         let cluster = Cluster {
             uuid: "sample-uuid".to_string(),
-            labels: ClusterLabels::default(),
+            labels: ClusterLabels::builder().build(),
             stack_id: "kube-abcde".to_string().into(),
             cluster_template: ClusterTemplate {
                 network_driver: "calico".to_string(),
@@ -749,5 +1108,32 @@ mod tests {
             secret.type_,
             Some("addons.cluster.x-k8s.io/resource-set".into())
         );
+        assert_eq!(
+            secret
+                .string_data
+                .expect("string data should be set")
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["calico.yml"]
+        );
+    }
+
+    #[test]
+    fn test_secret_from_cluster_with_kube_proxy_replacement() {
+        let cluster = Cluster {
+            uuid: "sample-uuid".to_string(),
+            labels: ClusterLabels::builder()
+                .cilium_kube_proxy_replacement("true".to_owned())
+                .build(),
+            stack_id: "kube-abcde".to_string().into(),
+            cluster_template: ClusterTemplate {
+                network_driver: "cilium".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let secret: Secret = cluster.into();
+
+        assert_eq!(secret.string_data, Some(BTreeMap::new()));
     }
 }
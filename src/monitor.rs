@@ -1,10 +1,14 @@
 use crate::{
     clients::kubernetes,
-    cluster_api::{kubeadmcontrolplane::KubeadmControlPlane, machines::Machine},
+    cluster_api::{
+        kubeadmcontrolplane::KubeadmControlPlane, machines::Machine,
+        openstackclusters::OpenStackCluster,
+    },
     magnum,
 };
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::LabelSelector;
 use kube::{api::ListParams, Api};
+use log::warn;
 use maplit::btreemap;
 use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyDict};
 use pyo3_async_runtimes::tokio::get_runtime;
@@ -49,6 +53,20 @@ impl MachineExt for Machine {
     }
 }
 
+trait OpenStackClusterExt {
+    fn bastion_floating_ip(&self) -> Option<&str>;
+}
+
+impl OpenStackClusterExt for OpenStackCluster {
+    fn bastion_floating_ip(&self) -> Option<&str> {
+        self.status
+            .as_ref()
+            .and_then(|status| status.bastion.as_ref())
+            .and_then(|bastion| bastion.floating_ip.as_deref())
+            .filter(|floating_ip| !floating_ip.is_empty())
+    }
+}
+
 struct MachineHealthStatusReason(HashMap<String, bool>);
 
 impl<'py> IntoPyObject<'py> for MachineHealthStatusReason {
@@ -142,16 +160,7 @@ impl Monitor {
             }
         };
 
-        let list_params = ListParams::default().labels_from(
-            &LabelSelector {
-                match_labels: Some(btreemap! {
-                    "cluster.x-k8s.io/cluster-name".to_string() => stack_id.to_string(),
-                }),
-                ..Default::default()
-            }
-            .try_into()
-            .map_err(MonitorError::ParseLabelSelector)?,
-        );
+        let list_params = list_params(stack_id)?;
 
         let machine_api: Api<Machine> = Api::namespaced(self.client.clone(), "magnum-system");
         let kcp_api: Api<KubeadmControlPlane> =
@@ -183,6 +192,45 @@ impl Monitor {
 
         Ok(data.into())
     }
+
+    /// Returns the floating IP of the bastion host, which is `None` if the
+    /// cluster has no bastion or its OpenStackCluster could not be listed.
+    fn get_bastion_floating_ip(&self, py: Python<'_>) -> PyResult<Option<String>> {
+        let Some(stack_id) = &self.cluster.stack_id else {
+            return Ok(None);
+        };
+
+        let list_params = list_params(stack_id)?;
+        let osc_api: Api<OpenStackCluster> = Api::namespaced(self.client.clone(), "magnum-system");
+
+        let osc_list = Python::detach(py, || get_runtime().block_on(osc_api.list(&list_params)));
+
+        match osc_list {
+            Ok(osc_list) => Ok(osc_list
+                .items
+                .first()
+                .and_then(|osc| osc.bastion_floating_ip())
+                .map(str::to_owned)),
+            Err(err) => {
+                warn!("Failed to get OpenStackCluster for {}: {}", stack_id, err);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Returns the parameters which select the objects of the given cluster.
+fn list_params(stack_id: &str) -> Result<ListParams, MonitorError> {
+    Ok(ListParams::default().labels_from(
+        &LabelSelector {
+            match_labels: Some(btreemap! {
+                "cluster.x-k8s.io/cluster-name".to_string() => stack_id.to_string(),
+            }),
+            ..Default::default()
+        }
+        .try_into()
+        .map_err(MonitorError::ParseLabelSelector)?,
+    ))
 }
 
 #[cfg(test)]
@@ -191,6 +239,7 @@ mod tests {
     use crate::cluster_api::{
         kubeadmcontrolplane::KubeadmControlPlaneStatus,
         machines::{MachineSpec, MachineStatus},
+        openstackclusters::{OpenStackClusterStatus, OpenStackClusterStatusBastion},
     };
     use k8s_openapi::{
         api::core::v1::ObjectReference,
@@ -382,6 +431,39 @@ mod tests {
         assert_eq!(machine.is_ready(), false);
     }
 
+    #[test]
+    fn test_openstack_cluster_bastion_floating_ip() {
+        let osc = OpenStackCluster {
+            status: Some(OpenStackClusterStatus {
+                bastion: Some(OpenStackClusterStatusBastion {
+                    floating_ip: Some("203.0.113.10".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(osc.bastion_floating_ip(), Some("203.0.113.10"));
+    }
+
+    #[test]
+    fn test_openstack_cluster_without_bastion_floating_ip() {
+        let osc = OpenStackCluster {
+            status: Some(OpenStackClusterStatus {
+                bastion: Some(OpenStackClusterStatusBastion {
+                    floating_ip: Some("".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(osc.bastion_floating_ip(), None);
+        assert_eq!(OpenStackCluster::default().bastion_floating_ip(), None);
+    }
+
     #[test]
     fn test_machines_to_health_status_dict() {
         let machines = vec![
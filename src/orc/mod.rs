@@ -0,0 +1,3 @@
+#![allow(clippy::all)]
+#![allow(dead_code)]
+pub mod servergroups;
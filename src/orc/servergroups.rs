@@ -0,0 +1,236 @@
+// WARNING: generated by kopium - manual changes will be overwritten
+// kopium command: kopium -D Default -D PartialEq -A -d servergroups.openstack.k-orc.cloud
+// kopium version: 0.21.2
+
+#[allow(unused_imports)]
+mod prelude {
+    pub use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+    pub use kube::CustomResource;
+    pub use schemars::JsonSchema;
+    pub use serde::{Deserialize, Serialize};
+}
+use self::prelude::*;
+
+/// spec specifies the desired state of the resource.
+#[derive(CustomResource, Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[kube(
+    group = "openstack.k-orc.cloud",
+    version = "v1alpha1",
+    kind = "ServerGroup",
+    plural = "servergroups"
+)]
+#[kube(namespaced)]
+#[kube(status = "ServerGroupStatus")]
+#[kube(derive = "Default")]
+#[kube(derive = "PartialEq")]
+pub struct ServerGroupSpec {
+    /// cloudCredentialsRef points to a secret containing OpenStack credentials
+    #[serde(rename = "cloudCredentialsRef")]
+    pub cloud_credentials_ref: ServerGroupCloudCredentialsRef,
+    /// import refers to an existing OpenStack resource which will be imported instead of
+    /// creating a new one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import: Option<ServerGroupImport>,
+    /// managedOptions specifies options which may be applied to managed objects.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "managedOptions"
+    )]
+    pub managed_options: Option<ServerGroupManagedOptions>,
+    /// managementPolicy defines how ORC will treat the object. Valid values are
+    /// `managed`: ORC will create, update, and delete the resource; `unmanaged`:
+    /// ORC will import an existing resource, and will not apply updates to it or
+    /// delete it.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "managementPolicy"
+    )]
+    pub management_policy: Option<ServerGroupManagementPolicy>,
+    /// resource specifies the desired state of the resource.
+    ///
+    /// resource may not be specified if the management policy is `unmanaged`.
+    ///
+    /// resource must be specified if the management policy is `managed`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<ServerGroupResource>,
+}
+
+/// cloudCredentialsRef points to a secret containing OpenStack credentials
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ServerGroupCloudCredentialsRef {
+    /// cloudName specifies the name of the entry in the clouds.yaml file to use.
+    #[serde(rename = "cloudName")]
+    pub cloud_name: String,
+    /// secretName is the name of a secret in the same namespace as the resource being provisioned.
+    /// The secret must contain a key named `clouds.yaml` which contains an OpenStack clouds.yaml file.
+    /// The secret may optionally contain a key named `cacert` containing a PEM-encoded CA certificate.
+    #[serde(rename = "secretName")]
+    pub secret_name: String,
+}
+
+/// import refers to an existing OpenStack resource which will be imported instead of
+/// creating a new one.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ServerGroupImport {
+    /// filter contains a resource query which is expected to return a single
+    /// result. The controller will continue to retry if filter returns no
+    /// results. If filter returns multiple results the controller will set an
+    /// error state and will not continue to retry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<ServerGroupImportFilter>,
+    /// id contains the unique identifier of an existing OpenStack resource. Note
+    /// that when specifying an import by ID, the resource MUST already exist.
+    /// The ORC object will enter an error state if the resource does not exist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// filter contains a resource query which is expected to return a single
+/// result. The controller will continue to retry if filter returns no
+/// results. If filter returns multiple results the controller will set an
+/// error state and will not continue to retry.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ServerGroupImportFilter {
+    /// name of the existing resource
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// managedOptions specifies options which may be applied to managed objects.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ServerGroupManagedOptions {
+    /// onDelete specifies the behaviour of the controller when the ORC
+    /// object is deleted. Options are `delete` - delete the OpenStack resource;
+    /// `detach` - do not delete the OpenStack resource. If not specified, the
+    /// default is `delete`.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "onDelete")]
+    pub on_delete: Option<ServerGroupManagedOptionsOnDelete>,
+}
+
+/// managedOptions specifies options which may be applied to managed objects.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum ServerGroupManagedOptionsOnDelete {
+    #[serde(rename = "delete")]
+    Delete,
+    #[serde(rename = "detach")]
+    Detach,
+}
+
+/// spec specifies the desired state of the resource.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum ServerGroupManagementPolicy {
+    #[serde(rename = "managed")]
+    Managed,
+    #[serde(rename = "unmanaged")]
+    Unmanaged,
+}
+
+/// resource specifies the desired state of the resource.
+///
+/// resource may not be specified if the management policy is `unmanaged`.
+///
+/// resource must be specified if the management policy is `managed`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ServerGroupResource {
+    /// name will be the name of the created resource. If not specified, the
+    /// name of the ORC object will be used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// policy is the policy to use for the server group.
+    pub policy: ServerGroupResourcePolicy,
+    /// rules is the rules to use for the server group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<ServerGroupResourceRules>,
+}
+
+/// resource specifies the desired state of the resource.
+///
+/// resource may not be specified if the management policy is `unmanaged`.
+///
+/// resource must be specified if the management policy is `managed`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub enum ServerGroupResourcePolicy {
+    #[default]
+    #[serde(rename = "affinity")]
+    Affinity,
+    #[serde(rename = "anti-affinity")]
+    AntiAffinity,
+    #[serde(rename = "soft-affinity")]
+    SoftAffinity,
+    #[serde(rename = "soft-anti-affinity")]
+    SoftAntiAffinity,
+}
+
+/// rules is the rules to use for the server group.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ServerGroupResourceRules {
+    /// maxServerPerHost specifies how many servers can reside on a single compute host.
+    /// It can be used only with the "anti-affinity" policy.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "maxServerPerHost"
+    )]
+    pub max_server_per_host: Option<i32>,
+}
+
+/// status defines the observed state of the resource.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ServerGroupStatus {
+    /// conditions represents the observed status of the object.
+    /// Known .status.conditions.type are: "Available", "Progressing"
+    ///
+    /// Available represents the availability of the OpenStack resource. If it is
+    /// true then the resource is ready for use.
+    ///
+    /// Progressing indicates whether the controller is still attempting to
+    /// reconcile the current state of the OpenStack resource to the desired
+    /// state. Progressing will be False either because the desired state has
+    /// been achieved, or because some terminal error prevents it from ever being
+    /// achieved and the controller is no longer attempting to reconcile. If
+    /// Progressing is True, an observer waiting on the resource should continue
+    /// to wait.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conditions: Option<Vec<Condition>>,
+    /// id is the unique identifier of the OpenStack resource.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// resource contains the observed state of the OpenStack resource.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<ServerGroupStatusResource>,
+}
+
+/// resource contains the observed state of the OpenStack resource.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ServerGroupStatusResource {
+    /// name is a Human-readable name for the servergroup. Might not be unique.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// policy is the policy of the servergroup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+    /// projectID is the project owner of the resource.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "projectID")]
+    pub project_id: Option<String>,
+    /// rules is the rules of the server group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<ServerGroupStatusResourceRules>,
+    /// userID of the server group.
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "userID")]
+    pub user_id: Option<String>,
+}
+
+/// rules is the rules of the server group.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ServerGroupStatusResourceRules {
+    /// maxServerPerHost specifies how many servers can reside on a single compute host.
+    /// It can be used only with the "anti-affinity" policy.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "maxServerPerHost"
+    )]
+    pub max_server_per_host: Option<i32>,
+}
@@ -20,7 +20,7 @@ use crate::{
         openstackclustertemplates::OpenStackClusterTemplate,
         openstackmachinetemplates::OpenStackMachineTemplate,
     },
-    features::ClusterFeatureEntry,
+    features::{bootstrap_hooks, extra_args, ClusterFeatureEntry},
 };
 use k8s_openapi::{api::core::v1::ObjectReference, apimachinery::pkg::util::intstr::IntOrString};
 use kube::{api::ObjectMeta, CustomResourceExt};
@@ -149,6 +149,17 @@ impl ClusterClassBuilder {
             cc.patches.extend(entry.feature.patches());
         }
 
+        // NOTE: The `extraArgs` patches replace the whole maps of flags, so they
+        //       need to run before the features adding single flags, while the
+        //       bootstrap hooks append to the lists of commands created by the
+        //       other features, so they need to run last.
+        cc.patches.sort_by_key(|patch| {
+            (
+                !extra_args::is_extra_args_patch(patch),
+                bootstrap_hooks::is_bootstrap_hooks_patch(patch),
+            )
+        });
+
         cc.build(metadata)
     }
 }
@@ -177,7 +188,9 @@ impl From<Values> for Vec<ClusterTopologyVariables> {
 pub mod fixtures {
     use crate::{
         features::{
-            api_server_load_balancer, audit_log, boot_volume, openid_connect, operating_system,
+            admission_plugins, api_server_load_balancer, audit_log, bastion, boot_volume,
+            bootstrap_hooks, etcd, etcd_backup, extra_args, images, openid_connect,
+            operating_system, rollout_strategy, swap,
         },
         resources::Values,
     };
@@ -241,6 +254,7 @@ pub mod fixtures {
             .flavor("worker".into())
             .image_repository("registry.example.com/cluster-api".into())
             .image_uuid("bar".into())
+            .image_filter(images::ImageFilter::builder().build())
             .enable_keystone_auth(true)
             .node_cidr("10.0.0.0/24".into())
             .dns_nameservers(vec!["1.1.1.1".into()])
@@ -271,8 +285,35 @@ pub mod fixtures {
             .enable_etcd_volume(false)
             .etcd_volume_size(0)
             .etcd_volume_type("".into())
+            .additional_volumes(vec![])
             .availability_zone("az1".into())
             .admission_control_list("NodeRestriction".into())
+            .pod_security(
+                admission_plugins::PodSecurityConfig::builder()
+                    .enabled(false)
+                    .build(),
+            )
+            .event_rate_limits(vec![])
+            .node_port_allowed_cidrs(vec!["0.0.0.0/0".into()])
+            .all_nodes_security_group_rules(vec![])
+            .control_plane_nodes_security_group_rules(vec![])
+            .worker_nodes_security_group_rules(vec![])
+            .cluster_uuid("8a3d5c1e-3b0f-4a44-9d7e-2f7d3a1c0b9e".into())
+            .node_group_name("default-master".into())
+            .server_tags(vec![])
+            .server_metadata(vec![])
+            .additional_networks(vec![])
+            .bastion(bastion::BastionConfig::builder().build())
+            .control_plane_rollout_strategy(
+                rollout_strategy::ControlPlaneRolloutStrategy::builder().build(),
+            )
+            .extra_args(extra_args::ExtraArgsConfig::builder().build())
+            .etcd_config(etcd::EtcdConfig::builder().build())
+            .etcd_backup(etcd_backup::EtcdBackupConfig::builder().build())
+            .swap(swap::SwapConfig::builder().build())
+            .bootstrap_hooks(bootstrap_hooks::BootstrapHooksConfig::builder().build())
+            .container_runtimes(vec![])
+            .kube_proxy_replacement(false)
             .build()
     }
 }
@@ -305,12 +346,36 @@ mod tests {
         assert_eq!(cluster_class.spec.workers.is_some(), true);
     }
 
+    #[test]
+    fn test_default_cluster_class_extra_args_patches_first() {
+        let cluster_class = ClusterClassBuilder::default(ObjectMeta::default());
+        let patches = cluster_class.spec.patches.expect("patches should be set");
+
+        let extra_args_patches = patches
+            .iter()
+            .take_while(|patch| extra_args::is_extra_args_patch(patch))
+            .count();
+
+        assert_eq!(extra_args_patches, 4);
+        assert!(!patches[4..].iter().any(extra_args::is_extra_args_patch));
+    }
+
+    #[test]
+    fn test_default_cluster_class_bootstrap_hooks_patch_last() {
+        let cluster_class = ClusterClassBuilder::default(ObjectMeta::default());
+        let patches = cluster_class.spec.patches.expect("patches should be set");
+
+        assert!(bootstrap_hooks::is_bootstrap_hooks_patch(
+            patches.last().expect("patches should not be empty")
+        ));
+    }
+
     #[test]
     fn test_convert_values_to_cluster_topology_variables() {
         let values = default_values();
         let variables: Vec<ClusterTopologyVariables> = values.into();
 
-        assert_eq!(variables.len(), 39);
+        assert_eq!(variables.len(), 61);
 
         for var in &variables {
             match var.name.as_str() {
@@ -327,7 +392,10 @@ mod tests {
                     assert_eq!(var.value, json!(default_values().boot_volume));
                 }
                 "bootVolumeAvailabilityZone" => {
-                    assert_eq!(var.value, json!(default_values().boot_volume_availability_zone));
+                    assert_eq!(
+                        var.value,
+                        json!(default_values().boot_volume_availability_zone)
+                    );
                 }
                 "clusterIdentityRefName" => {
                     assert_eq!(var.value, json!(default_values().cluster_identity_ref_name));
@@ -440,12 +508,90 @@ mod tests {
                 "etcdVolumeType" => {
                     assert_eq!(var.value, json!(default_values().etcd_volume_type));
                 }
+                "additionalVolumes" => {
+                    assert_eq!(var.value, json!(default_values().additional_volumes));
+                }
                 "availabilityZone" => {
                     assert_eq!(var.value, json!(default_values().availability_zone));
                 }
                 "admissionControlList" => {
                     assert_eq!(var.value, json!(default_values().admission_control_list));
                 }
+                "clusterUUID" => {
+                    assert_eq!(var.value, json!(default_values().cluster_uuid));
+                }
+                "nodeGroupName" => {
+                    assert_eq!(var.value, json!(default_values().node_group_name));
+                }
+                "serverTags" => {
+                    assert_eq!(var.value, json!(default_values().server_tags));
+                }
+                "serverMetadata" => {
+                    assert_eq!(var.value, json!(default_values().server_metadata));
+                }
+                "imageFilter" => {
+                    assert_eq!(var.value, json!(default_values().image_filter));
+                }
+                "bastion" => {
+                    assert_eq!(var.value, json!(default_values().bastion));
+                }
+                "controlPlaneRolloutStrategy" => {
+                    assert_eq!(
+                        var.value,
+                        json!(default_values().control_plane_rollout_strategy)
+                    );
+                }
+                "extraArgs" => {
+                    assert_eq!(var.value, json!(default_values().extra_args));
+                }
+                "etcdConfig" => {
+                    assert_eq!(var.value, json!(default_values().etcd_config));
+                }
+                "etcdBackup" => {
+                    assert_eq!(var.value, json!(default_values().etcd_backup));
+                }
+                "swap" => {
+                    assert_eq!(var.value, json!(default_values().swap));
+                }
+                "bootstrapHooks" => {
+                    assert_eq!(var.value, json!(default_values().bootstrap_hooks));
+                }
+                "containerRuntimes" => {
+                    assert_eq!(var.value, json!(default_values().container_runtimes));
+                }
+                "kubeProxyReplacement" => {
+                    assert_eq!(var.value, json!(default_values().kube_proxy_replacement));
+                }
+                "additionalNetworks" => {
+                    assert_eq!(var.value, json!(default_values().additional_networks));
+                }
+                "podSecurity" => {
+                    assert_eq!(var.value, json!(default_values().pod_security));
+                }
+                "eventRateLimits" => {
+                    assert_eq!(var.value, json!(default_values().event_rate_limits));
+                }
+                "nodePortAllowedCIDRs" => {
+                    assert_eq!(var.value, json!(default_values().node_port_allowed_cidrs));
+                }
+                "allNodesSecurityGroupRules" => {
+                    assert_eq!(
+                        var.value,
+                        json!(default_values().all_nodes_security_group_rules)
+                    );
+                }
+                "controlPlaneNodesSecurityGroupRules" => {
+                    assert_eq!(
+                        var.value,
+                        json!(default_values().control_plane_nodes_security_group_rules)
+                    );
+                }
+                "workerNodesSecurityGroupRules" => {
+                    assert_eq!(
+                        var.value,
+                        json!(default_values().worker_nodes_security_group_rules)
+                    );
+                }
                 other => panic!("Unexpected field name: {}", other),
             }
         }
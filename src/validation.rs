@@ -0,0 +1,36 @@
+/// Returns `true` if the domain is a lowercase RFC 1123 subdomain.
+pub fn is_valid_domain(domain: &str) -> bool {
+    domain.len() <= 253
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_domain() {
+        for domain in ["cluster.local", "k8s.example.com", "ssd-1"] {
+            assert!(is_valid_domain(domain), "{} should be valid", domain);
+        }
+
+        for domain in [
+            "",
+            "Cluster.Local",
+            "cluster..local",
+            "-cluster.local",
+            "cluster-.local",
+            "cluster_local",
+        ] {
+            assert!(!is_valid_domain(domain), "{} should be invalid", domain);
+        }
+    }
+}